@@ -0,0 +1,121 @@
+use rocket::figment::Figment;
+use serde::{Deserialize, Serialize};
+use crate::models::money::Ton;
+
+// API yapılandırması.
+// Rocket'in kendi figment'ından okunur: Rocket.toml (profil bazlı: [default], [debug],
+// [release], [staging] ...) ve ardından `ROCKET_` önekli ortam değişkenleri
+// (örn. ROCKET_MONGO_URI, ROCKET_JWT_SECRET). Profil `ROCKET_PROFILE` ile seçilir.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AppConfig {
+    pub mongo_uri: String,
+    pub redis_url: String,
+    pub telegram_bot_token: String,
+    pub jwt_secret: String,
+    #[serde(default = "default_jwt_ttl_secs")]
+    pub jwt_ttl_secs: u64,
+    #[serde(default = "default_telegram_auth_max_age_secs")]
+    pub telegram_auth_max_age_secs: i64,
+    #[serde(default = "default_idempotency_ttl_secs")]
+    pub idempotency_ttl_secs: u64,
+    // Yönetici uç noktalarına erişebilen telegram_id'ler
+    #[serde(default)]
+    pub admin_telegram_ids: Vec<i64>,
+    // TON yatırımlarının gönderildiği cüzdan ve zinciri izleyen indexer ("toncenter" ya da "mock")
+    pub deposit_wallet_address: String,
+    #[serde(default = "default_ton_indexer")]
+    pub ton_indexer: String,
+    #[serde(default = "default_ton_indexer_url")]
+    pub ton_indexer_url: String,
+    #[serde(default)]
+    pub ton_indexer_api_key: Option<String>,
+    #[serde(default = "default_deposit_poll_interval_secs")]
+    pub deposit_poll_interval_secs: u64,
+    #[serde(default = "default_deposit_batch_size")]
+    pub deposit_batch_size: u32,
+    #[serde(default = "default_deposit_pending_timeout_secs")]
+    pub deposit_pending_timeout_secs: i64,
+    // Çekimlerin gönderimi ("wallet_service" ya da "fake") ve en küçük çekim tutarı (nanoTON)
+    #[serde(default = "default_ton_payouts")]
+    pub ton_payouts: String,
+    #[serde(default)]
+    pub ton_wallet_service_url: Option<String>,
+    #[serde(default)]
+    pub ton_wallet_service_token: Option<String>,
+    #[serde(default = "default_min_withdrawal")]
+    pub min_withdrawal: Ton,
+    // Ekonomi oranlarının Mongo'dan yeniden okunma aralığı
+    #[serde(default = "default_economy_reload_interval_secs")]
+    pub economy_reload_interval_secs: u64,
+}
+
+fn default_jwt_ttl_secs() -> u64 { 60 * 60 } // 1 saat
+fn default_telegram_auth_max_age_secs() -> i64 { 24 * 60 * 60 }
+fn default_idempotency_ttl_secs() -> u64 { 24 * 60 * 60 }
+fn default_ton_indexer() -> String { "toncenter".to_string() }
+fn default_ton_indexer_url() -> String { "https://toncenter.com/api/v2".to_string() }
+fn default_deposit_poll_interval_secs() -> u64 { 15 }
+fn default_deposit_batch_size() -> u32 { 50 }
+fn default_deposit_pending_timeout_secs() -> i64 { 60 * 60 } // 1 saat
+fn default_ton_payouts() -> String { "wallet_service".to_string() }
+fn default_min_withdrawal() -> Ton { Ton::from_ton(1) }
+fn default_economy_reload_interval_secs() -> u64 { 10 }
+
+impl AppConfig {
+    // Yapılandırmayı yükler ve doğrular; hatalı yapılandırmayla API başlamaz
+    pub fn load(figment: &Figment) -> Result<Self, String> {
+        let config: AppConfig = figment
+            .extract()
+            .map_err(|e| format!("Yapılandırma okunamadı: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !self.mongo_uri.starts_with("mongodb://") && !self.mongo_uri.starts_with("mongodb+srv://") {
+            return Err(format!("mongo_uri geçersiz: {}", self.mongo_uri));
+        }
+        if !self.redis_url.starts_with("redis://") && !self.redis_url.starts_with("rediss://") {
+            return Err(format!("redis_url geçersiz: {}", self.redis_url));
+        }
+        // Bot token biçimi: "<bot_id>:<secret>"
+        match self.telegram_bot_token.split_once(':') {
+            Some((id, secret)) if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) && !secret.is_empty() => {}
+            _ => return Err("telegram_bot_token geçersiz".to_string()),
+        }
+        if self.jwt_secret.len() < 32 {
+            return Err("jwt_secret en az 32 karakter olmalı".to_string());
+        }
+        if self.jwt_ttl_secs == 0 || self.telegram_auth_max_age_secs <= 0 {
+            return Err("jwt_ttl_secs ve telegram_auth_max_age_secs pozitif olmalı".to_string());
+        }
+        if self.idempotency_ttl_secs == 0 {
+            return Err("idempotency_ttl_secs pozitif olmalı".to_string());
+        }
+        if self.deposit_wallet_address.trim().is_empty() {
+            return Err("deposit_wallet_address boş olamaz".to_string());
+        }
+        match self.ton_indexer.as_str() {
+            "mock" => {}
+            "toncenter" if self.ton_indexer_url.starts_with("http://") || self.ton_indexer_url.starts_with("https://") => {}
+            "toncenter" => return Err(format!("ton_indexer_url geçersiz: {}", self.ton_indexer_url)),
+            other => return Err(format!("ton_indexer geçersiz: {} (toncenter ya da mock olmalı)", other)),
+        }
+        if self.deposit_poll_interval_secs == 0 || self.deposit_batch_size == 0 || self.deposit_pending_timeout_secs <= 0 {
+            return Err("deposit_poll_interval_secs, deposit_batch_size ve deposit_pending_timeout_secs pozitif olmalı".to_string());
+        }
+        if self.economy_reload_interval_secs == 0 {
+            return Err("economy_reload_interval_secs pozitif olmalı".to_string());
+        }
+        match (self.ton_payouts.as_str(), self.ton_wallet_service_url.as_deref()) {
+            ("fake", _) => {}
+            ("wallet_service", Some(url)) if url.starts_with("http://") || url.starts_with("https://") => {}
+            ("wallet_service", _) => return Err("ton_payouts = \"wallet_service\" için geçerli bir ton_wallet_service_url gerekli".to_string()),
+            (other, _) => return Err(format!("ton_payouts geçersiz: {} (wallet_service ya da fake olmalı)", other)),
+        }
+        if self.min_withdrawal.nanoton() <= 0 {
+            return Err("min_withdrawal pozitif olmalı".to_string());
+        }
+        Ok(())
+    }
+}
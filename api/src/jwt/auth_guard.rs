@@ -0,0 +1,66 @@
+// src/jwt/auth_guard.rs
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use crate::jwt::jwt_helper::verify_token;
+use crate::config::app_config::AppConfig;
+
+// İsteği yapan kullanıcının kimliğinin tek kaynağı.
+// `Authorization: Bearer <token>` başlığındaki JWT doğrulanır ve `sub` alanı telegram_id olarak okunur.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthenticatedUser {
+    pub telegram_id: i64,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuthenticatedUser {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = match request.headers().get_one("Authorization").and_then(|h| h.strip_prefix("Bearer ")) {
+            Some(token) => token.trim(),
+            None => return Outcome::Error((Status::Unauthorized, "Missing bearer token".to_string())),
+        };
+
+        let config = match request.rocket().state::<AppConfig>() {
+            Some(config) => config,
+            None => return Outcome::Error((Status::InternalServerError, "Configuration is not loaded".to_string())),
+        };
+
+        let claims = match verify_token(token, config) {
+            Ok(claims) => claims,
+            Err(e) => return Outcome::Error((Status::Unauthorized, e)),
+        };
+
+        match claims.sub.parse::<i64>() {
+            Ok(telegram_id) => Outcome::Success(AuthenticatedUser { telegram_id }),
+            Err(_) => Outcome::Error((Status::Unauthorized, "Invalid token subject".to_string())),
+        }
+    }
+}
+
+// Yönetici uç noktaları için: kimliği doğrulanmış ve `admin_telegram_ids` içinde olan kullanıcı
+#[derive(Debug, Clone, Copy)]
+pub struct AdminUser {
+    pub telegram_id: i64,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AdminUser {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let user = match request.guard::<AuthenticatedUser>().await {
+            Outcome::Success(user) => user,
+            Outcome::Error(e) => return Outcome::Error(e),
+            Outcome::Forward(status) => return Outcome::Forward(status),
+        };
+
+        match request.rocket().state::<AppConfig>() {
+            Some(config) if config.admin_telegram_ids.contains(&user.telegram_id) => {
+                Outcome::Success(AdminUser { telegram_id: user.telegram_id })
+            }
+            Some(_) => Outcome::Error((Status::Forbidden, "Admin access required".to_string())),
+            None => Outcome::Error((Status::InternalServerError, "Configuration is not loaded".to_string())),
+        }
+    }
+}
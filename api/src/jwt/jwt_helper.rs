@@ -1,21 +1,20 @@
 // src/jwt/jwt_helper.rs
 use jsonwebtoken::{encode, decode, Header, Algorithm, Validation, EncodingKey, DecodingKey, errors::Result as JwtResult};
 use crate::jwt::claims::Claims;
+use crate::config::app_config::AppConfig;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-const SECRET: &[u8] = b"your_secret_key"; // Gizli anahtarınızı buraya koyun
-
-pub fn create_token(telegram_id: i64) -> JwtResult<String> {
+pub fn create_token(telegram_id: i64, config: &AppConfig) -> JwtResult<String> {
     let my_claims = Claims {
         sub: telegram_id.to_string(),
-        exp: (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 60 * 60) as usize, // 1 saat geçerlilik süresi
+        exp: (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + config.jwt_ttl_secs) as usize,
     };
 
-    encode(&Header::default(), &my_claims, &EncodingKey::from_secret(SECRET))
+    encode(&Header::default(), &my_claims, &EncodingKey::from_secret(config.jwt_secret.as_bytes()))
 }
 
-pub fn verify_token(token: &str) -> Result<Claims, String> {
-    decode::<Claims>(token, &DecodingKey::from_secret(SECRET), &Validation::default())
+pub fn verify_token(token: &str, config: &AppConfig) -> Result<Claims, String> {
+    decode::<Claims>(token, &DecodingKey::from_secret(config.jwt_secret.as_bytes()), &Validation::default())
         .map(|data| data.claims)
         .map_err(|_| "Invalid token".to_string())
-}
\ No newline at end of file
+}
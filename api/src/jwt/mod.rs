@@ -1,3 +1,4 @@
 // src/jwt/mod.rs
 pub mod jwt_helper;
 pub mod claims;
+pub mod auth_guard;
@@ -5,19 +5,24 @@ mod models;      // Modellerinizi içe aktarın
 mod repository;  // User repository'nizi içe aktarın
 mod jwt;         // JWT işlemleriniz
 mod services;
-use rand::Rng;
+mod config;
+use mongodb::ClientSession;
 use mongodb::bson::{Binary, Bson};
 use rocket::{http::Status, serde::{json::Json, Deserialize, Serialize}, State};
-use services::{redis_service::setup_redis, telegram_auth_fairing::TelegramAuthFairing}; 
+use services::{redis_service::setup_redis, telegram_auth_fairing::{TelegramAuthFairing, TelegramInitDataValidator}, provably_fair::{verify_game, GameVerification}, transaction_service::{run_transaction, TxError}, idempotency::{Idempotency, IdempotencyStore, ReplayableResponse, IDEMPOTENCY_HEADER}, deposit_watcher::{DepositWatcher, IncomingTransfer, MockIndexer, TonCenterIndexer, TonIndexer}, ton_transfer::{FakeTonTransfer, TonTransfer, WalletServiceTransfer}, withdrawal_service::WithdrawalService, economy_service::EconomyService}; 
 use rocket_db_pools::mongodb::Client;
-use repository::{game_repository::GameRepository, market_repository::MarketRepository, salon_repository::SalonRepository, table_repository::TableRepository, user_repository::UserRepository};
-use models::{game::GameResult, market::Market, salon::Salon, table::Table, user::{Item, ReferenceLevel, References, User}};
-use rocket::{get, post, options, catch, catchers, routes};
+use repository::{game_repository::GameRepository, live_game_repository::LiveGameRepository, ledger_repository::LedgerRepository, market_repository::MarketRepository, deposit_repository::DepositRepository, money_migration::MoneyMigration, salon_migration::SalonMigration, withdrawal_repository::WithdrawalRepository, salon_repository::SalonRepository, table_repository::TableRepository, user_repository::UserRepository};
+use models::{deposit::{deposit_memo, Deposit, DepositAddress, DepositStatus}, economy::{EconomyConfig, EconomyRules}, game::{GameResult, VoidRequest, VoidedGame}, ledger::{user_account, Asset, BalanceReconciliation, LedgerTransaction, Reason, SYSTEM_BETS, SYSTEM_EXCHANGE, SYSTEM_REWARDS, SYSTEM_SHOP}, market::Market, money::Ton, salon::Salon, table::Table, user::{Boost, Item, ReferenceLevel, References, User}, withdrawal::{is_valid_ton_address, Withdrawal, WithdrawalStatus}};
+use rocket::{get, post, put, options, catch, catchers, routes};
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::http::Header;
 use rocket::{Request, Response};
 use uuid::Uuid;
-use crate::models::table::Player;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::models::table::{GameState as TableState, Player};
+use crate::jwt::auth_guard::{AdminUser, AuthenticatedUser};
+use crate::config::app_config::AppConfig;
 
 
 // CORS fairing tanımı
@@ -40,7 +45,7 @@ impl Fairing for CORS {
         ));
         response.set_header(Header::new(
             "Access-Control-Allow-Headers",
-            "Content-Type, Authorization",
+            format!("Content-Type, Authorization, {}", IDEMPOTENCY_HEADER),
         ));
     }
 }
@@ -58,9 +63,41 @@ pub struct ApiResponse<T> {
     pub result: Option<T>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
-pub struct TelegramId {
-    pub telegram_id: i64,
+// `Idempotency-Key` ile tekrar eden isteklere saklanan yanıt aynen döner
+impl<T: Serialize + for<'de> Deserialize<'de>> ReplayableResponse for (Status, Json<ApiResponse<T>>) {
+    fn status(&self) -> Status {
+        self.0
+    }
+
+    fn body_to_string(&self) -> Option<String> {
+        serde_json::to_string(&self.1.0).ok()
+    }
+
+    fn from_stored(status: Status, body: &str) -> Option<Self> {
+        serde_json::from_str(body).ok().map(|response| (status, Json(response)))
+    }
+
+    fn error(status: Status, message: &str) -> Self {
+        (status, Json(ApiResponse { message: message.to_string(), result: None }))
+    }
+}
+
+impl ReplayableResponse for (Status, String) {
+    fn status(&self) -> Status {
+        self.0
+    }
+
+    fn body_to_string(&self) -> Option<String> {
+        Some(self.1.clone())
+    }
+
+    fn from_stored(status: Status, body: &str) -> Option<Self> {
+        Some((status, body.to_string()))
+    }
+
+    fn error(status: Status, message: &str) -> Self {
+        (status, message.to_string())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -87,7 +124,7 @@ pub struct GameUserData {
 
 // Tüm kullanıcıları alma
 #[get("/users")]
-async fn get_all_users(user_repo: &rocket::State<UserRepository>) -> Json<ApiResponse<Vec<User>>> {
+async fn get_all_users(_auth: AuthenticatedUser, user_repo: &rocket::State<UserRepository>) -> Json<ApiResponse<Vec<User>>> {
     match user_repo.get_all_users().await {
         Ok(users) if !users.is_empty() => Json(ApiResponse {
             message: "200: Success".to_string(),
@@ -105,6 +142,7 @@ async fn get_all_users(user_repo: &rocket::State<UserRepository>) -> Json<ApiRes
 }
 #[get("/get_leaderboard")]
 async fn get_leaderboard(
+    _auth: AuthenticatedUser,
     user_repo: &rocket::State<UserRepository>,
     game_repo: &rocket::State<GameRepository>,
 ) -> Json<ApiResponse<LeaderboardResponse>> {
@@ -179,12 +217,46 @@ async fn get_leaderboard(
 }
 
 
+// Provably-fair doğrulama: açıklanan seed'lerden zarları yeniden hesaplar
+#[get("/games/<game_id>/verify")]
+async fn verify_game_rolls(
+    _auth: AuthenticatedUser,
+    game_repo: &State<GameRepository>,
+    game_id: &str,
+) -> (Status, Json<ApiResponse<GameVerification>>) {
+    match game_repo.find_fairness_record(game_id).await {
+        Ok(Some(record)) => match verify_game(&record) {
+            Some(verification) => (Status::Ok, Json(ApiResponse {
+                message: "200: Success".to_string(),
+                result: Some(verification),
+            })),
+            None => (Status::UnprocessableEntity, Json(ApiResponse {
+                message: "422: Unprocessable Entity - Game has no fairness data".to_string(),
+                result: None,
+            })),
+        },
+        Ok(None) => (Status::NotFound, Json(ApiResponse {
+            message: format!("404: Not Found - Game {} not found", game_id),
+            result: None,
+        })),
+        Err(e) => {
+            eprintln!("Error finding game: {:?}", e);
+            (Status::InternalServerError, Json(ApiResponse {
+                message: format!("500: Internal Server Error - {:?}", e),
+                result: None,
+            }))
+        }
+    }
+}
+
+
 // Yeni kullanıcı oluşturma
 #[post("/users", format = "json", data = "<new_user>")]
 async fn create_user(
+    auth: AuthenticatedUser,
     user_repo: &rocket::State<UserRepository>,
     new_user: Json<User>,
-) -> (Status, Json<ApiResponse<(User, String)>>) {
+) -> (Status, Json<ApiResponse<User>>) {
     if new_user.telegram_id <= 0 {
         return (Status::BadRequest, Json(ApiResponse {
             message: "400: Bad Request - telegram_id is required".to_string(),
@@ -192,42 +264,31 @@ async fn create_user(
         }));
     }
 
+    // Token'lar yalnızca /auth/telegram ile verilir; kullanıcı sadece kendi kaydını oluşturabilir
+    if new_user.telegram_id != auth.telegram_id {
+        return (Status::Forbidden, Json(ApiResponse {
+            message: "403: Forbidden - telegram_id does not match the authenticated user".to_string(),
+            result: None,
+        }));
+    }
+
     // Aynı telegram_id ile kullanıcı var mı kontrol et
     match user_repo.find_user_by_telegram_id(new_user.telegram_id).await {
-        Ok(Some(mut existing_user)) => {
-
-            if let Some(ref mut boost) = existing_user.boost {
-                let current_time = get_current_unix_timestamp(); // Şu anki zaman damgası
-                let boost_end_time = boost.start_time + (boost.duration_days as i64 * 24 * 60 * 60); // Günleri saniyeye çevir
-
-                if current_time >= boost_end_time {
-                    // Boost süresi dolmuş, boostu sıfırla
-                    existing_user.boost = None;
-                    println!("Boost süresi dolmuş, boost durduruldu.");
-                    user_repo.remove_boost(existing_user.telegram_id).await.unwrap(); // Boost'u kaldır
-                }
-            }
-
+        Ok(Some(existing_user)) => {
+            // Süresi dolan boost'ları sunucudaki boost süpürücüsü kaldırır
             // Kullanıcı zaten varsa, onu geri döndür ve yeni kayıt yapma
-        
-            let token = jwt::jwt_helper::create_token(existing_user.telegram_id)
-                .unwrap_or_else(|_| "Error creating token".to_string());
-                
             (Status::Ok, Json(ApiResponse {
                 message: "200: User already exists".to_string(),
-                result: Some((existing_user, token)),
+                result: Some(existing_user),
             }))
         },
         Ok(None) => {
             // Kullanıcı yoksa, yeni kullanıcı oluştur
             match user_repo.create_user(new_user.into_inner()).await {
                 Ok(Some(created_user)) => {
-                    let token = jwt::jwt_helper::create_token(created_user.telegram_id)
-                        .unwrap_or_else(|_| "Error creating token".to_string());
-                    
                     (Status::Created, Json(ApiResponse {
                         message: "201: Created".to_string(),
-                        result: Some((created_user, token)),
+                        result: Some(created_user),
                     }))
                 },
                 Ok(None) => {
@@ -260,6 +321,76 @@ async fn create_user(
 }
 
 
+#[derive(Deserialize)]
+pub struct TelegramAuthRequest {
+    pub init_data: String, // Telegram.WebApp.initData (ham query string)
+}
+
+// Geçerli Telegram initData karşılığında JWT verir; kullanıcı yoksa oluşturulur
+#[post("/auth/telegram", format = "json", data = "<auth_data>")]
+async fn auth_telegram(
+    config: &State<AppConfig>,
+    validator: &State<TelegramInitDataValidator>,
+    user_repo: &State<UserRepository>,
+    auth_data: Json<TelegramAuthRequest>,
+) -> (Status, Json<ApiResponse<(User, String)>>) {
+    let validated = match validator.validate(&auth_data.init_data) {
+        Ok(validated) => validated,
+        Err(e) => {
+            return (Status::Unauthorized, Json(ApiResponse {
+                message: format!("401: Unauthorized - {}", e),
+                result: None,
+            }));
+        }
+    };
+
+    // create_user kullanıcı zaten varsa mevcut kaydı döndürür
+    let user = match user_repo.create_user(validated.user.into_user()).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (Status::InternalServerError, Json(ApiResponse {
+                message: "500: Internal Server Error - User was not created".to_string(),
+                result: None,
+            }));
+        }
+        Err(e) => {
+            eprintln!("Error creating user: {:?}", e);
+            return (Status::InternalServerError, Json(ApiResponse {
+                message: format!("500: Internal Server Error - {:?}", e),
+                result: None,
+            }));
+        }
+    };
+
+    match jwt::jwt_helper::create_token(user.telegram_id, config) {
+        Ok(token) => (Status::Ok, Json(ApiResponse {
+            message: "200: Authenticated".to_string(),
+            result: Some((user, token)),
+        })),
+        Err(e) => {
+            eprintln!("Error creating token: {:?}", e);
+            (Status::InternalServerError, Json(ApiResponse {
+                message: "500: Internal Server Error - Unable to create token".to_string(),
+                result: None,
+            }))
+        }
+    }
+}
+
+// İşlem hatasını API yanıtına çevirir
+fn tx_error_response<T>(error: TxError) -> (Status, Json<ApiResponse<T>>) {
+    match error {
+        TxError::Rejected(status, message) => (status, Json(ApiResponse { message, result: None })),
+        TxError::Database(e) => {
+            eprintln!("Transaction failed: {:?}", e);
+            (Status::InternalServerError, Json(ApiResponse {
+                message: "500: Internal Server Error".to_string(),
+                result: None,
+            }))
+        }
+    }
+}
+
 fn get_current_unix_timestamp() -> i64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now().duration_since(UNIX_EPOCH)
@@ -270,6 +401,7 @@ fn get_current_unix_timestamp() -> i64 {
 // Telegram ID'ye göre kullanıcıyı alma
 #[get("/users/<telegram_id>")]
 async fn get_user_by_telegram_id(
+    _auth: AuthenticatedUser,
     user_repo: &rocket::State<UserRepository>,
     telegram_id: i64,
 ) -> (Status, Json<ApiResponse<User>>) {
@@ -300,18 +432,31 @@ async fn get_user_by_telegram_id(
 struct UpdateUserProfileRequest {
     username: Option<String>,
     photo_url: Option<String>,
+    wallet_address: Option<String>, // TON çekimlerinin gönderileceği adres
 }
 
 #[post("/users/<telegram_id>", data = "<update_request>")]
 async fn update_user_profile(
+    auth: AuthenticatedUser,
     user_repo: &State<UserRepository>,
     telegram_id: i64,
     update_request: Json<UpdateUserProfileRequest>,
 ) -> (Status, Json<ApiResponse<String>>) {
+    // Kullanıcı yalnızca kendi profilini güncelleyebilir
+    if auth.telegram_id != telegram_id {
+        return (
+            Status::Forbidden,
+            Json(ApiResponse {
+                message: "403: Forbidden - Cannot update another user's profile".to_string(),
+                result: None,
+            }),
+        );
+    }
+
     let update_data = update_request.into_inner();
 
-    // Kullanıcı adı ve fotoğraf URL'si boşsa işlem yapma
-    if update_data.username.is_none() && update_data.photo_url.is_none() {
+    // Kullanıcı adı, fotoğraf URL'si ve cüzdan adresi boşsa işlem yapma
+    if update_data.username.is_none() && update_data.photo_url.is_none() && update_data.wallet_address.is_none() {
         return (
             Status::BadRequest,
             Json(ApiResponse {
@@ -321,9 +466,19 @@ async fn update_user_profile(
         );
     }
 
+    if update_data.wallet_address.as_deref().is_some_and(|address| !is_valid_ton_address(address)) {
+        return (
+            Status::BadRequest,
+            Json(ApiResponse {
+                message: "400: Bad Request - Invalid TON wallet address".to_string(),
+                result: None,
+            }),
+        );
+    }
+
     // Güncelleme işlemini gerçekleştir
     match user_repo
-        .update_user_profile(telegram_id, update_data.username, update_data.photo_url)
+        .update_user_profile(telegram_id, update_data.username, update_data.photo_url, update_data.wallet_address)
         .await
     {
         Ok(_) => (
@@ -346,11 +501,129 @@ async fn update_user_profile(
     }
 }
 
+// Kullanıcının defter kayıtları, yeniden eskiye sayfalanır
+#[get("/users/<telegram_id>/transactions?<page>&<limit>")]
+async fn get_user_transactions(
+    auth: AuthenticatedUser,
+    ledger_repo: &State<LedgerRepository>,
+    telegram_id: i64,
+    page: Option<u64>,
+    limit: Option<i64>,
+) -> (Status, Json<ApiResponse<Vec<LedgerTransaction>>>) {
+    if auth.telegram_id != telegram_id {
+        return (
+            Status::Forbidden,
+            Json(ApiResponse {
+                message: "403: Forbidden - Cannot view another user's transactions".to_string(),
+                result: None,
+            }),
+        );
+    }
+
+    let page = page.unwrap_or(1).max(1);
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+
+    match ledger_repo.find_for_account(&user_account(telegram_id), page, limit).await {
+        Ok(transactions) => (
+            Status::Ok,
+            Json(ApiResponse {
+                message: format!("200: Success - page {}", page),
+                result: Some(transactions),
+            }),
+        ),
+        Err(e) => {
+            eprintln!("Error fetching transactions: {:?}", e);
+            (
+                Status::InternalServerError,
+                Json(ApiResponse {
+                    message: format!("500: Internal Server Error - {:?}", e),
+                    result: None,
+                }),
+            )
+        }
+    }
+}
+
+// Kullanıcı belgesindeki bakiyeleri defterden türetilen bakiyelerle karşılaştırır
+#[get("/users/<telegram_id>/balances/reconcile")]
+async fn reconcile_user_balances(
+    auth: AuthenticatedUser,
+    user_repo: &State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
+    telegram_id: i64,
+) -> (Status, Json<ApiResponse<Vec<BalanceReconciliation>>>) {
+    if auth.telegram_id != telegram_id {
+        return (
+            Status::Forbidden,
+            Json(ApiResponse {
+                message: "403: Forbidden - Cannot reconcile another user's balances".to_string(),
+                result: None,
+            }),
+        );
+    }
+
+    let user = match user_repo.find_user_by_telegram_id(telegram_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return (
+                Status::NotFound,
+                Json(ApiResponse {
+                    message: "404: Not Found - User not found".to_string(),
+                    result: None,
+                }),
+            )
+        }
+        Err(e) => {
+            eprintln!("Error finding user: {:?}", e);
+            return (
+                Status::InternalServerError,
+                Json(ApiResponse {
+                    message: format!("500: Internal Server Error - {:?}", e),
+                    result: None,
+                }),
+            );
+        }
+    };
+
+    match ledger_repo.balances_for_account(&user_account(telegram_id)).await {
+        Ok(balances) => {
+            let ledger_balance = |asset: Asset| balances.get(&asset).copied().unwrap_or(0);
+            let report = vec![
+                BalanceReconciliation::new(Asset::Hp, user.hp.unwrap_or(0) as i64, ledger_balance(Asset::Hp)),
+                BalanceReconciliation::new(Asset::Ton, user.ton_amount.unwrap_or(Ton::ZERO).nanoton(), ledger_balance(Asset::Ton)),
+                BalanceReconciliation::new(Asset::GamePass, user.game_pass.unwrap_or(0) as i64, ledger_balance(Asset::GamePass)),
+                BalanceReconciliation::new(Asset::ClickScore, user.click_score.unwrap_or(0) as i64, ledger_balance(Asset::ClickScore)),
+            ];
+            let message = if report.iter().all(|r| r.matches) {
+                "200: Success - Balances match the ledger".to_string()
+            } else {
+                "200: Success - Balances differ from the ledger".to_string()
+            };
+            (Status::Ok, Json(ApiResponse { message, result: Some(report) }))
+        }
+        Err(e) => {
+            eprintln!("Error computing ledger balances: {:?}", e);
+            (
+                Status::InternalServerError,
+                Json(ApiResponse {
+                    message: format!("500: Internal Server Error - {:?}", e),
+                    result: None,
+                }),
+            )
+        }
+    }
+}
+
 #[post("/salons", format = "json", data = "<new_salon>")]
 async fn add_salon(
+    _admin: AdminUser,
     new_salon: Json<Salon>, 
     salon_repo: &State<SalonRepository>
 ) -> (Status, Json<ApiResponse<String>>) {
+    // Salonun ücreti, kesintisi, kapasitesi ve ödül dağılımı belgeden okunur; tutarsızsa kaydetmiyoruz
+    if let Err(e) = new_salon.validate() {
+        return tx_error_response(TxError::Rejected(Status::BadRequest, format!("400: Bad Request - {}", e)));
+    }
     match salon_repo.add_salon(new_salon.into_inner()).await {
         Ok(_) => (
             Status::Created, 
@@ -371,10 +644,14 @@ async fn add_salon(
 
 #[post("/salons/<salon_id>/tables", format = "json", data = "<new_table>")]
 async fn add_table(
+    _admin: AdminUser,
     salon_id: i32, 
     new_table: Json<Table>, 
     table_repo: &State<TableRepository>
 ) -> (Status, Json<ApiResponse<String>>) {
+    if let Err(e) = new_table.validate() {
+        return tx_error_response(TxError::Rejected(Status::BadRequest, format!("400: Bad Request - {}", e)));
+    }
     match table_repo.add_table_to_salon(salon_id, new_table.into_inner()).await {
         Ok(_) => (
             Status::Ok, 
@@ -394,6 +671,7 @@ async fn add_table(
 }
 #[get("/salons")]
 async fn get_all_salons(
+    _auth: AuthenticatedUser,
     salon_repo: &rocket::State<SalonRepository>
 ) -> (Status, Json<ApiResponse<Vec<Salon>>>) {
     match salon_repo.get_all_salons().await {
@@ -422,14 +700,14 @@ async fn get_all_salons(
 }
 
 
-#[post("/salons/<salon_id>/tables/<table_id>/join", format = "json", data = "<telegram_data>")]
+#[post("/salons/<salon_id>/tables/<table_id>/join")]
 async fn join_table(
     salon_id: i32,
     table_id: i32,
-    telegram_data: Json<TelegramId>,
+    auth: AuthenticatedUser,
     salon_repo: &State<SalonRepository>
 ) -> (Status, Json<ApiResponse<String>>) {
-    let telegram_id = telegram_data.telegram_id;
+    let telegram_id = auth.telegram_id;
 
     // Kullanıcı zaten başka bir masada mı kontrol et
     if let Ok(Some(salon)) = salon_repo.find_salon_by_id(salon_id).await {
@@ -443,21 +721,39 @@ async fn join_table(
         }
 
         // Masaya oyuncu ekleme işlemi
-        let mut updated_salon = salon;
-        if let Some(table) = updated_salon.tables.iter_mut().find(|t| t.table_id == table_id) {
-            table.players.push(Player {
+        let max_players = salon.max_players as usize;
+        if let Some(table) = salon.tables.iter().find(|t| t.table_id == table_id) {
+            if table.players.len() >= max_players {
+                return (Status::Conflict, Json(ApiResponse {
+                    message: format!("409: Conflict - Table {} is full", table_id),
+                    result: None,
+                }));
+            }
+            if !matches!(table.game_state, TableState::Waiting) {
+                return (Status::Conflict, Json(ApiResponse {
+                    message: format!("409: Conflict - A game is already starting or running at table {}", table_id),
+                    result: None,
+                }));
+            }
+            let player = Player {
                 player_id: telegram_id,
                 has_paid: false,
                 dice_rolls: vec![],
                 is_active: true,
-            });
+            };
 
-            salon_repo.update_salon(updated_salon).await.unwrap();
-
-            return (Status::Ok, Json(ApiResponse {
-                message: format!("Player {} successfully joined table {}", telegram_id, table_id),
-                result: None,
-            }));
+            // Okumadan bu yana masa dolmuş ya da oyun başlamış olabilir; koşullu ekleme bunu yakalar
+            return match salon_repo.seat_player(salon_id, table_id, &player, max_players, false).await {
+                Ok(true) => (Status::Ok, Json(ApiResponse {
+                    message: format!("Player {} successfully joined table {}", telegram_id, table_id),
+                    result: None,
+                })),
+                Ok(false) => (Status::Conflict, Json(ApiResponse {
+                    message: format!("409: Conflict - Table {} is no longer open for player {}", table_id, telegram_id),
+                    result: None,
+                })),
+                Err(e) => tx_error_response(TxError::Database(e)),
+            };
         } else {
             return (Status::NotFound, Json(ApiResponse {
                 message: format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id),
@@ -472,14 +768,14 @@ async fn join_table(
     }
 }
 
-#[post("/salons/<salon_id>/tables/<table_id>/join_with_bots", format = "json", data = "<telegram_data>")]
+#[post("/salons/<salon_id>/tables/<table_id>/join_with_bots")]
 async fn join_table_with_bots(
     salon_id: i32,
     table_id: i32,
-    telegram_data: Json<TelegramId>,
+    auth: AuthenticatedUser,
     salon_repo: &State<SalonRepository>
 ) -> (Status, Json<ApiResponse<String>>) {
-    let telegram_id = telegram_data.telegram_id;
+    let telegram_id = auth.telegram_id;
 
     // Kullanıcı zaten başka bir masada mı kontrol et
     if let Ok(Some(salon)) = salon_repo.find_salon_by_id(salon_id).await {
@@ -492,35 +788,41 @@ async fn join_table_with_bots(
             }
         }
 
-        // Masaya kullanıcı ve 3 bot ekleme işlemi
-        let mut updated_salon = salon;
-        if let Some(table) = updated_salon.tables.iter_mut().find(|t| t.table_id == table_id) {
-            // Ana kullanıcıyı ekle
-            table.players.push(Player {
+        // Kullanıcı masaya oturur; kalan koltukları sunucu hemen botlarla doldurur
+        let max_players = salon.max_players as usize;
+        if let Some(table) = salon.tables.iter().find(|t| t.table_id == table_id) {
+            if table.players.len() + 1 >= max_players {
+                return (Status::Conflict, Json(ApiResponse {
+                    message: format!("409: Conflict - Table {} has no free seats for bots", table_id),
+                    result: None,
+                }));
+            }
+            if !matches!(table.game_state, TableState::Waiting) {
+                return (Status::Conflict, Json(ApiResponse {
+                    message: format!("409: Conflict - A game is already starting or running at table {}", table_id),
+                    result: None,
+                }));
+            }
+            let bot_count = max_players - table.players.len() - 1;
+            let player = Player {
                 player_id: telegram_id,
                 has_paid: false,
                 dice_rolls: vec![],
                 is_active: true,
-            });
-
-            // 3 bot oyuncu ekle
-            for _ in 0..3 {
-                let fake_telegram_id = generate_fake_telegram_id();
-                table.players.push(Player {
-                    player_id: fake_telegram_id,
-                    has_paid: false,
-                    dice_rolls: vec![],
-                    is_active: true,
-                });
-            }
-
-            // Güncellenmiş salon verisini kaydet
-            salon_repo.update_salon(updated_salon).await.unwrap();
+            };
 
-            return (Status::Ok, Json(ApiResponse {
-                message: format!("Player {} and 3 bots successfully joined table {}", telegram_id, table_id),
-                result: None,
-            }));
+            // Oyuncu oturduktan sonra en az bir koltuk bota kalmalı
+            return match salon_repo.seat_player(salon_id, table_id, &player, max_players - 1, true).await {
+                Ok(true) => (Status::Ok, Json(ApiResponse {
+                    message: format!("Player {} joined table {}; {} bots will take the remaining seats", telegram_id, table_id, bot_count),
+                    result: None,
+                })),
+                Ok(false) => (Status::Conflict, Json(ApiResponse {
+                    message: format!("409: Conflict - Table {} is no longer open for player {}", table_id, telegram_id),
+                    result: None,
+                })),
+                Err(e) => tx_error_response(TxError::Database(e)),
+            };
         } else {
             return (Status::NotFound, Json(ApiResponse {
                 message: format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id),
@@ -535,545 +837,422 @@ async fn join_table_with_bots(
     }
 }
 
-// Rastgele sahte bir telegram_id üretmek için yardımcı fonksiyon
-fn generate_fake_telegram_id() -> i64 {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(1000000..9999999) // 1,000,000 ile 9,999,999 arasında rastgele sayı
-}
-
-#[post("/salons/<salon_id>/tables/<table_id>/leave", format = "json", data = "<telegram_data>")]
+#[post("/salons/<salon_id>/tables/<table_id>/leave")]
 async fn leave_table(
     salon_id: i32,
     table_id: i32,
-    telegram_data: Json<TelegramId>,
-    salon_repo: &State<SalonRepository>
+    auth: AuthenticatedUser,
+    mongo: &State<Client>,
+    salon_repo: &State<SalonRepository>,
+    user_repo: &State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
 ) -> (Status, Json<ApiResponse<String>>) {
-    let telegram_id = telegram_data.telegram_id;
-
-    // Salonu bulma ve kontrol etme
-    if let Ok(Some(salon)) = salon_repo.find_salon_by_id(salon_id).await {
-        let mut updated_salon = salon;
-
-        // Masayı bulma ve oyuncunun o masada olup olmadığını kontrol etme
-        if let Some(table) = updated_salon.tables.iter_mut().find(|t| t.table_id == table_id) {
-            if let Some(player_index) = table.players.iter().position(|p| p.player_id == telegram_id) {
-                // Oyuncuyu masadan kaldır
-                table.players.remove(player_index);
+    let telegram_id = auth.telegram_id;
 
-                // Salonu güncelle
-                salon_repo.update_salon(updated_salon).await.unwrap();
+    let result = run_transaction(mongo, |mut session| async move {
+        let result = leave_steps(&mut session, salon_repo, user_repo, ledger_repo, (salon_id, table_id), telegram_id, false).await;
+        (session, result)
+    }).await;
 
-                return (Status::Ok, Json(ApiResponse {
-                    message: format!("Player {} successfully left table {}", telegram_id, table_id),
-                    result: None,
-                }));
-            } else {
-                return (Status::NotFound, Json(ApiResponse {
-                    message: format!("404: Not Found - Player {} not found at table {}", telegram_id, table_id),
-                    result: None,
-                }));
-            }
-        } else {
-            return (Status::NotFound, Json(ApiResponse {
-                message: format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id),
-                result: None,
-            }));
-        }
-    } else {
-        return (Status::NotFound, Json(ApiResponse {
-            message: format!("404: Not Found - Salon {} not found", salon_id),
+    match result {
+        Ok(refund) => (Status::Ok, Json(ApiResponse {
+            message: format!("Player {} successfully left table {}{}", telegram_id, table_id, refund_note(refund)),
             result: None,
-        }));
+        })),
+        Err(e) => tx_error_response(e),
     }
 }
 
-#[post("/salons/<salon_id>/tables/<table_id>/leave_bot", format = "json", data = "<telegram_data>")]
+#[post("/salons/<salon_id>/tables/<table_id>/leave_bot")]
 async fn leave_table_bot(
     salon_id: i32,
     table_id: i32,
-    telegram_data: Json<TelegramId>,
-    salon_repo: &State<SalonRepository>
+    auth: AuthenticatedUser,
+    mongo: &State<Client>,
+    salon_repo: &State<SalonRepository>,
+    user_repo: &State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
 ) -> (Status, Json<ApiResponse<String>>) {
-    let telegram_id = telegram_data.telegram_id;
+    let telegram_id = auth.telegram_id;
 
-    // Salonu bulma ve kontrol etme
-    if let Ok(Some(salon)) = salon_repo.find_salon_by_id(salon_id).await {
-        let mut updated_salon = salon;
-
-        // Masayı bulma ve oyuncunun o masada olup olmadığını kontrol etme
-        if let Some(table) = updated_salon.tables.iter_mut().find(|t| t.table_id == table_id) {
-            // Oyuncuyu ve botları masadan kaldır
-            let initial_player_count = table.players.len();
-
-            // Gerçek kullanıcıyı ve botları filtreleyerek sil
-            table.players.retain(|p| {
-                !(p.player_id == telegram_id || is_bot_id(p.player_id))
-            });
-
-            // Eğer oyuncu sayısı değişmediyse, kullanıcı bulunamadı demektir
-            if initial_player_count == table.players.len() {
-                return (Status::NotFound, Json(ApiResponse {
-                    message: format!("404: Not Found - Player {} not found at table {}", telegram_id, table_id),
-                    result: None,
-                }));
-            }
+    let result = run_transaction(mongo, |mut session| async move {
+        let result = leave_steps(&mut session, salon_repo, user_repo, ledger_repo, (salon_id, table_id), telegram_id, true).await;
+        (session, result)
+    }).await;
 
-            // Salonu güncelle
-            salon_repo.update_salon(updated_salon).await.unwrap();
+    match result {
+        Ok(refund) => (Status::Ok, Json(ApiResponse {
+            message: format!("Player {} and associated bots successfully left table {}{}", telegram_id, table_id, refund_note(refund)),
+            result: None,
+        })),
+        Err(e) => tx_error_response(e),
+    }
+}
 
-            return (Status::Ok, Json(ApiResponse {
-                message: format!("Player {} and associated bots successfully left table {}", telegram_id, table_id),
-                result: None,
-            }));
-        } else {
-            return (Status::NotFound, Json(ApiResponse {
-                message: format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id),
-                result: None,
-            }));
-        }
+fn refund_note(refund: i32) -> String {
+    if refund > 0 {
+        format!("; {} game passes refunded", refund)
     } else {
-        return (Status::NotFound, Json(ApiResponse {
-            message: format!("404: Not Found - Salon {} not found", salon_id),
-            result: None,
-        }));
+        String::new()
+    }
+}
+
+// Oyuncuyu (ve istenirse masadaki botları) masadan kaldırır. Oyuncu "ready" olduysa, oyun hiç
+// başlamadığı için salonun `entry_fee`'si iade edilir. Oyun başladıysa masadan kalkılamaz;
+// masayı sunucu oyun bitince boşaltır. İade edilen game pass sayısını döner.
+async fn leave_steps(
+    session: &mut ClientSession,
+    salon_repo: &SalonRepository,
+    user_repo: &UserRepository,
+    ledger_repo: &LedgerRepository,
+    (salon_id, table_id): (i32, i32),
+    telegram_id: i64,
+    with_bots: bool,
+) -> Result<i32, TxError> {
+    let salon = salon_repo.find_salon_with_session(session, salon_id).await?.ok_or_else(|| {
+        TxError::Rejected(Status::NotFound, format!("404: Not Found - Salon {} not found", salon_id))
+    })?;
+    let table = salon.tables.iter().find(|t| t.table_id == table_id).ok_or_else(|| {
+        TxError::Rejected(Status::NotFound, format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id))
+    })?;
+    if matches!(table.game_state, TableState::Started) {
+        return Err(TxError::Rejected(Status::Conflict, format!("409: Conflict - A game is in progress at table {}", table_id)));
+    }
+    let player = table.players.iter().find(|p| p.player_id == telegram_id).ok_or_else(|| {
+        TxError::Rejected(Status::NotFound, format!("404: Not Found - Player {} not found at table {}", telegram_id, table_id))
+    })?;
+    let has_paid = player.has_paid;
+
+    if !salon_repo.remove_players_with_session(session, salon_id, table_id, telegram_id, with_bots).await? {
+        return Err(TxError::Rejected(Status::Conflict, format!("409: Conflict - A game is in progress at table {}", table_id)));
     }
+
+    let refund = if has_paid { salon.entry_fee } else { 0 };
+    if refund > 0 {
+        user_repo.credit_with_session(session, telegram_id, Asset::GamePass, refund as i64).await?;
+        ledger_repo.record_with_session(session, LedgerTransaction::transfer(
+            Reason::GameRefund,
+            Asset::GamePass,
+            SYSTEM_BETS.to_string(),
+            user_account(telegram_id),
+            refund as i64,
+            Some(format!("salon:{}/table:{}", salon_id, table_id)),
+        )).await?;
+    }
+    Ok(refund)
 }
 
 
-#[post("/salons/<salon_id>/tables/<table_id>/ready_bots", format = "json", data = "<telegram_data>")]
+#[post("/salons/<salon_id>/tables/<table_id>/ready_bots")]
 async fn ready_table_bots(
     salon_id: i32,
     table_id: i32,
-    telegram_data: Json<TelegramId>,
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     salon_repo: &State<SalonRepository>,
-    user_repo: &State<UserRepository> // Add UserRepository to access user's game_pass
+    user_repo: &State<UserRepository>, // Add UserRepository to access user's game_pass
+    ledger_repo: &State<LedgerRepository>,
 ) -> (Status, Json<ApiResponse<String>>) {
-    let telegram_id = telegram_data.telegram_id;
-
-    // Determine required game passes based on salon_id
-    let required_game_passes = match salon_id {
-        1 => 1,
-        2 => 3,
-        3 => 5,
-        4 => 10,
-        5 => 15,
-        6 => 25,
-        _ => 0, // Default value if salon_id does not match any case
-    };
+    idempotency.run(async move {
+        let telegram_id = auth.telegram_id;
 
-    // Fetch user data to check game_pass count
-    if let Ok(Some(mut user)) = user_repo.find_user_by_telegram_id(telegram_id).await {
-        // Handle Option<i32> for game_pass safely
-        if let Some(game_pass) = user.game_pass {
-            if game_pass < required_game_passes {
-                return (Status::BadRequest, Json(ApiResponse {
-                    message: format!("Insufficient game passes. You need {} game passes to join salon {}", required_game_passes, salon_id),
-                    result: None,
-                }));
-            }
+        let result = run_transaction(mongo, |mut session| async move {
+            let result = ready_steps(&mut session, salon_repo, user_repo, ledger_repo, (salon_id, table_id), telegram_id).await;
+            (session, result)
+        }).await;
 
-            // Deduct the required game passes
-            user.game_pass = Some(game_pass - required_game_passes);
-            user_repo.update_user_game_pass(&user).await.unwrap();
-        } else {
-            return (Status::BadRequest, Json(ApiResponse {
-                message: "Game pass information is unavailable.".to_string(),
+        match result {
+            Ok(()) => (Status::Ok, Json(ApiResponse {
+                message: format!("Player {} and associated bots are now ready at table {}", telegram_id, table_id),
                 result: None,
-            }));
+            })),
+            Err(e) => tx_error_response(e),
         }
+    }).await
+}
 
-        // Salon and table retrieval and update logic
-        if let Ok(Some(salon)) = salon_repo.find_salon_by_id(salon_id).await {
-            let mut updated_salon = salon;
-
-            // Find the table and update player's has_paid status
-            if let Some(table) = updated_salon.tables.iter_mut().find(|t| t.table_id == table_id) {
-                // Kullanıcıyı "ready" yap
-                if let Some(player) = table.players.iter_mut().find(|p| p.player_id == telegram_id) {
-                    player.has_paid = true;
-                } else {
-                    return (Status::NotFound, Json(ApiResponse {
-                        message: format!("404: Not Found - Player {} not found at table {}", telegram_id, table_id),
-                        result: None,
-                    }));
-                }
-
-                // Masadaki tüm botları da "ready" yap
-                for bot in table.players.iter_mut().filter(|p| is_bot_id(p.player_id)) {
-                    bot.has_paid = true;
-                }
 
-                // Save the updated salon
-                salon_repo.update_salon(updated_salon).await.unwrap();
+// Oyuncuyu "ready" yapar ve salonun `entry_fee`'si kadar game pass düşer; botlar oturdukları anda hazırdır.
+// Tek bir işlem içinde çalışır: oyuncu masada değilse ya da zaten hazırsa ücret de düşülmez.
+async fn ready_steps(
+    session: &mut ClientSession,
+    salon_repo: &SalonRepository,
+    user_repo: &UserRepository,
+    ledger_repo: &LedgerRepository,
+    (salon_id, table_id): (i32, i32),
+    telegram_id: i64,
+) -> Result<(), TxError> {
+    let user = user_repo.find_user_with_session(session, telegram_id).await?.ok_or_else(|| {
+        TxError::Rejected(Status::NotFound, format!("404: Not Found - User with telegram_id {} not found", telegram_id))
+    })?;
+    if user.game_pass.is_none() {
+        return Err(TxError::rejected(Status::BadRequest, "Game pass information is unavailable."));
+    }
 
-                return (Status::Ok, Json(ApiResponse {
-                    message: format!("Player {} and associated bots are now ready at table {}", telegram_id, table_id),
-                    result: None,
-                }));
-            } else {
-                return (Status::NotFound, Json(ApiResponse {
-                    message: format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id),
-                    result: None,
-                }));
-            }
-        } else {
-            return (Status::NotFound, Json(ApiResponse {
-                message: format!("404: Not Found - Salon {} not found", salon_id),
-                result: None,
-            }));
+    let salon = salon_repo.find_salon_with_session(session, salon_id).await?.ok_or_else(|| {
+        TxError::Rejected(Status::NotFound, format!("404: Not Found - Salon {} not found", salon_id))
+    })?;
+    let table = salon.tables.iter().find(|t| t.table_id == table_id).ok_or_else(|| {
+        TxError::Rejected(Status::NotFound, format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id))
+    })?;
+    let player = table.players.iter().find(|p| p.player_id == telegram_id).ok_or_else(|| {
+        TxError::Rejected(Status::NotFound, format!("404: Not Found - Player {} not found at table {}", telegram_id, table_id))
+    })?;
+    if player.has_paid {
+        return Err(TxError::Rejected(Status::Conflict, format!("409: Conflict - Player {} is already ready at table {}", telegram_id, table_id)));
+    }
+    let required_game_passes = salon.entry_fee;
+
+    // Deduct the required game passes
+    if required_game_passes > 0 {
+        if !user_repo.debit_with_session(session, telegram_id, Asset::GamePass, required_game_passes as i64).await? {
+            return Err(TxError::Rejected(Status::BadRequest, format!(
+                "Insufficient game passes. You need {} game passes to join salon {}", required_game_passes, salon_id
+            )));
         }
-    } else {
-        return (Status::NotFound, Json(ApiResponse {
-            message: format!("404: Not Found - User with telegram_id {} not found", telegram_id),
-            result: None,
-        }));
+        ledger_repo.record_with_session(session, LedgerTransaction::transfer(
+            Reason::Bet,
+            Asset::GamePass,
+            user_account(telegram_id),
+            SYSTEM_BETS.to_string(),
+            required_game_passes as i64,
+            Some(format!("salon:{}/table:{}", salon_id, table_id)),
+        )).await?;
     }
-}
 
+    if !salon_repo.mark_players_paid_with_session(session, salon_id, table_id, &[telegram_id]).await? {
+        return Err(TxError::Rejected(Status::Conflict, format!("409: Conflict - Player {} is already ready at table {}", telegram_id, table_id)));
+    }
 
-// Bot olup olmadığını kontrol eden fonksiyon
-fn is_bot_id(telegram_id: i64) -> bool {
-    (1000000..9999999).contains(&telegram_id)
+    Ok(())
 }
 
 
-#[post("/salons/<salon_id>/tables/<table_id>/ready", format = "json", data = "<telegram_data>")]
+#[post("/salons/<salon_id>/tables/<table_id>/ready")]
 async fn ready_table(
     salon_id: i32,
     table_id: i32,
-    telegram_data: Json<TelegramId>,
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     salon_repo: &State<SalonRepository>,
-    user_repo: &State<UserRepository> // Add UserRepository to access user's game_pass
+    user_repo: &State<UserRepository>, // Add UserRepository to access user's game_pass
+    ledger_repo: &State<LedgerRepository>,
 ) -> (Status, Json<ApiResponse<String>>) {
-    let telegram_id = telegram_data.telegram_id;
-
-    // Determine required game passes based on salon_id
-    let required_game_passes = match salon_id {
-        1 => 1,
-        2 => 3,
-        3 => 5,
-        4 => 10,
-        5 => 15,
-        6 => 25,
-        _ => 0, // Default value if salon_id does not match any case
-    };
-
-    // Fetch user data to check game_pass count
-    if let Ok(Some(mut user)) = user_repo.find_user_by_telegram_id(telegram_id).await {
-        // Handle Option<i32> for game_pass safely
-        if let Some(game_pass) = user.game_pass {
-            if game_pass < required_game_passes {
-                return (Status::BadRequest, Json(ApiResponse {
-                    message: format!("Insufficient game passes. You need {} game passes to join salon {}", required_game_passes, salon_id),
-                    result: None,
-                }));
-            }
-
-            // Deduct the required game passes
-            user.game_pass = Some(game_pass - required_game_passes);
-            user_repo.update_user_game_pass(&user).await.unwrap(); // Ensure `update_user_hp` is the correct method name
-        } else {
-            return (Status::BadRequest, Json(ApiResponse {
-                message: "Game pass information is unavailable.".to_string(),
-                result: None,
-            }));
-        }
+    idempotency.run(async move {
+        let telegram_id = auth.telegram_id;
 
-        // Salon and table retrieval and update logic
-        if let Ok(Some(salon)) = salon_repo.find_salon_by_id(salon_id).await {
-            let mut updated_salon = salon;
+        let result = run_transaction(mongo, |mut session| async move {
+            let result = ready_steps(&mut session, salon_repo, user_repo, ledger_repo, (salon_id, table_id), telegram_id).await;
+            (session, result)
+        }).await;
 
-            // Find the table and update player's has_paid status
-            if let Some(table) = updated_salon.tables.iter_mut().find(|t| t.table_id == table_id) {
-                if let Some(player) = table.players.iter_mut().find(|p| p.player_id == telegram_id) {
-                    player.has_paid = true;
-
-                    // Save the updated salon
-                    salon_repo.update_salon(updated_salon).await.unwrap();
-
-                    return (Status::Ok, Json(ApiResponse {
-                        message: format!("Player {} is now ready at table {}", telegram_id, table_id),
-                        result: None,
-                    }));
-                } else {
-                    return (Status::NotFound, Json(ApiResponse {
-                        message: format!("404: Not Found - Player {} not found at table {}", telegram_id, table_id),
-                        result: None,
-                    }));
-                }
-            } else {
-                return (Status::NotFound, Json(ApiResponse {
-                    message: format!("404: Not Found - Table {} not found in Salon {}", table_id, salon_id),
-                    result: None,
-                }));
-            }
-        } else {
-            return (Status::NotFound, Json(ApiResponse {
-                message: format!("404: Not Found - Salon {} not found", salon_id),
+        match result {
+            Ok(()) => (Status::Ok, Json(ApiResponse {
+                message: format!("Player {} is now ready at table {}", telegram_id, table_id),
                 result: None,
-            }));
+            })),
+            Err(e) => tx_error_response(e),
         }
-    } else {
-        return (Status::NotFound, Json(ApiResponse {
-            message: format!("404: Not Found - User with telegram_id {} not found", telegram_id),
-            result: None,
-        }));
-    }
+    }).await
 }
 
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ConverRequest {
-    pub telegram_id: i64,
     pub click_score: i32,
 }
 #[post("/convert", format = "json", data = "<conver_data>")]
 async fn convert(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
+    economy: &State<Arc<EconomyService>>,
     conver_data: Json<ConverRequest>,
 ) -> (Status, Json<ApiResponse<User>>) {
-    let telegram_id = conver_data.telegram_id;
-    let click_score_to_reduce = conver_data.click_score;
+    idempotency.with_body(&*conver_data).run(async move {
+        let telegram_id = auth.telegram_id;
+        let click_score_to_reduce = conver_data.click_score;
+        if click_score_to_reduce <= 0 {
+            return tx_error_response(TxError::rejected(Status::BadRequest, "400: Bad Request - click_score must be positive"));
+        }
+        // click_score'u azalt ve hp'yi arttır
+        let hp_increase = click_score_to_reduce / economy.current().rules.click_score_per_hp;
+
+        let result = run_transaction(mongo, |mut session| async move {
+            let result: Result<User, TxError> = async {
+                let user = user_repo.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))?;
+                if user.click_score.is_none() {
+                    return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - User has no click_score set"));
+                }
 
-    // Kullanıcıyı telegram_id ile bulma
-    match user_repo.find_user_by_telegram_id(telegram_id).await {
-        Ok(Some(mut user)) => {
-            if let Some(current_click_score) = user.click_score {
-                if current_click_score >= click_score_to_reduce {
-                    // click_score'u azalt ve hp'yi arttır
-                    let hp_increase = click_score_to_reduce / 100;
-                    user.hp = Some(user.hp.unwrap_or(0) + hp_increase);
-                    user.click_score = Some(current_click_score - click_score_to_reduce);
-
-                    // Kullanıcıyı güncelle
-                    match user_repo.update_user_hp(&user).await {
-                        Ok(_) => (
-                            Status::Ok,
-                            Json(ApiResponse {
-                                message: "User updated successfully".to_string(),
-                                result: Some(user), // Güncellenmiş kullanıcıyı döndürüyoruz
-                            }),
-                        ),
-                        Err(e) => {
-                            eprintln!("Error updating user: {:?}", e);
-                            (
-                                Status::InternalServerError,
-                                Json(ApiResponse {
-                                    message: "500: Internal Server Error - Unable to update user".to_string(),
-                                    result: None,
-                                }),
-                            )
-                        }
-                    }
-                } else {
-                    (
-                        Status::BadRequest,
-                        Json(ApiResponse {
-                            message: "400: Bad Request - Insufficient click_score".to_string(),
-                            result: None,
-                        }),
-                    )
+                if !user_repo.debit_with_session(&mut session, telegram_id, Asset::ClickScore, click_score_to_reduce as i64).await? {
+                    return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - Insufficient click_score"));
                 }
-            } else {
-                (
-                    Status::BadRequest,
-                    Json(ApiResponse {
-                        message: "400: Bad Request - User has no click_score set".to_string(),
-                        result: None,
-                    }),
-                )
-            }
-        }
-        Ok(None) => (
-            Status::NotFound,
-            Json(ApiResponse {
-                message: "404: Not Found - User not found".to_string(),
-                result: None,
-            }),
-        ),
-        Err(e) => {
-            eprintln!("Error finding user: {:?}", e);
-            (
-                Status::InternalServerError,
-                Json(ApiResponse {
-                    message: "500: Internal Server Error".to_string(),
-                    result: None,
-                }),
-            )
+                user_repo.credit_with_session(&mut session, telegram_id, Asset::Hp, hp_increase as i64).await?;
+                ledger_repo.record_with_session(&mut session, LedgerTransaction::exchange(
+                    Reason::Conversion,
+                    user_account(telegram_id),
+                    SYSTEM_EXCHANGE,
+                    (Asset::ClickScore, click_score_to_reduce as i64),
+                    (Asset::Hp, hp_increase as i64),
+                    None,
+                )).await?;
+
+                // Güncellenmiş kullanıcıyı döndürüyoruz
+                user_repo.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))
+            }.await;
+            (session, result)
+        }).await;
+
+        match result {
+            Ok(user) => (Status::Ok, Json(ApiResponse {
+                message: "User updated successfully".to_string(),
+                result: Some(user),
+            })),
+            Err(e) => tx_error_response(e),
         }
-    }
+    }).await
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct GamePassRequest {
-    pub telegram_id: i64,
     pub hp: i32,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct GamePassTonRequest {
+    pub ton_amount: Ton, // nanoTON
+}
+
 #[post("/buy_gamepass", format = "json", data = "<gamepass_data>")]
 async fn buy_gamepass(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &rocket::State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
+    economy: &State<Arc<EconomyService>>,
     gamepass_data: Json<GamePassRequest>,
 ) -> (Status, Json<ApiResponse<User>>) {
-    let telegram_id = gamepass_data.telegram_id;
-    let hp_to_use = gamepass_data.hp;
+    idempotency.with_body(&*gamepass_data).run(async move {
+        let telegram_id = auth.telegram_id;
+        let hp_to_use = gamepass_data.hp;
+        if hp_to_use <= 0 {
+            return tx_error_response(TxError::rejected(Status::BadRequest, "400: Bad Request - HP must be positive"));
+        }
+        let gamepass_to_add = hp_to_use / economy.current().rules.hp_per_game_pass;
+
+        let result = run_transaction(mongo, |mut session| async move {
+            let result: Result<User, TxError> = async {
+                let user = user_repo.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))?;
+                if user.hp.is_none() {
+                    return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - User has no HP set"));
+                }
 
-    // Kullanıcıyı telegram_id ile bul
-    match user_repo.find_user_by_telegram_id(telegram_id).await {
-        Ok(Some(mut user)) => {
-            // Kullanıcının yeterli hp'si var mı kontrol et
-            if let Some(current_hp) = user.hp {
-                if current_hp >= hp_to_use {
-                    // HP'yi düşür ve game_pass ekle
-                    let gamepass_to_add = hp_to_use / 100; // 100 HP = 1 game_pass
-                    user.hp = Some(current_hp - hp_to_use);
-                    user.game_pass = Some(user.game_pass.unwrap_or(0) + gamepass_to_add);
-
-                    // Kullanıcıyı güncelle
-                    match user_repo.update_user_hp_and_gamepass(&user).await {
-                        Ok(_) => (
-                            Status::Ok,
-                            Json(ApiResponse {
-                                message: "Gamepass purchased successfully".to_string(),
-                                result: Some(user), // Güncellenmiş kullanıcı verisi
-                            }),
-                        ),
-                        Err(e) => {
-                            eprintln!("Error updating user: {:?}", e);
-                            (
-                                Status::InternalServerError,
-                                Json(ApiResponse {
-                                    message: "500: Internal Server Error - Unable to update user".to_string(),
-                                    result: None,
-                                }),
-                            )
-                        }
-                    }
-                } else {
-                    (
-                        Status::BadRequest,
-                        Json(ApiResponse {
-                            message: "400: Bad Request - Insufficient HP".to_string(),
-                            result: None,
-                        }),
-                    )
+                // HP'yi düşür ve game_pass ekle
+                if !user_repo.debit_with_session(&mut session, telegram_id, Asset::Hp, hp_to_use as i64).await? {
+                    return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - Insufficient HP"));
                 }
-            } else {
-                (
-                    Status::BadRequest,
-                    Json(ApiResponse {
-                        message: "400: Bad Request - User has no HP set".to_string(),
-                        result: None,
-                    }),
-                )
-            }
-        },
-        Ok(None) => (
-            Status::NotFound,
-            Json(ApiResponse {
-                message: "404: Not Found - User not found".to_string(),
-                result: None,
-            }),
-        ),
-        Err(e) => {
-            eprintln!("Error finding user: {:?}", e);
-            (
-                Status::InternalServerError,
-                Json(ApiResponse {
-                    message: "500: Internal Server Error".to_string(),
-                    result: None,
-                }),
-            )
+                user_repo.credit_with_session(&mut session, telegram_id, Asset::GamePass, gamepass_to_add as i64).await?;
+                ledger_repo.record_with_session(&mut session, LedgerTransaction::exchange(
+                    Reason::Conversion,
+                    user_account(telegram_id),
+                    SYSTEM_EXCHANGE,
+                    (Asset::Hp, hp_to_use as i64),
+                    (Asset::GamePass, gamepass_to_add as i64),
+                    None,
+                )).await?;
+
+                user_repo.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))
+            }.await;
+            (session, result)
+        }).await;
+
+        match result {
+            Ok(user) => (Status::Ok, Json(ApiResponse {
+                message: "Gamepass purchased successfully".to_string(),
+                result: Some(user), // Güncellenmiş kullanıcı verisi
+            })),
+            Err(e) => tx_error_response(e),
         }
-    }
+    }).await
 }
 
 
 #[post("/buy_gamepass_with_ton", format = "json", data = "<gamepass_data>")]
 async fn buy_gamepass_with_ton(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &rocket::State<UserRepository>,
-    gamepass_data: Json<GamePassRequest>,
+    ledger_repo: &State<LedgerRepository>,
+    economy: &State<Arc<EconomyService>>,
+    gamepass_data: Json<GamePassTonRequest>,
 ) -> (Status, Json<ApiResponse<User>>) {
-    let telegram_id = gamepass_data.telegram_id;
-    let price = gamepass_data.hp as f64; // Gönderilen fiyatı `f64` tipine dönüştür
+    idempotency.with_body(&*gamepass_data).run(async move {
+        let telegram_id = auth.telegram_id;
+
+        // Kaç `game_pass` alabileceğini hesapla; yalnızca tam game pass'lerin ücreti düşülür
+        let game_pass_price = economy.current().rules.game_pass_price;
+        let gamepass_to_add = gamepass_data.ton_amount.whole_units_of(game_pass_price);
+        if gamepass_to_add <= 0 || gamepass_to_add > i32::MAX as i64 {
+            return tx_error_response(TxError::rejected(Status::BadRequest, "400: Bad Request - Amount is too low to buy a game pass"));
+        }
+        let ton_to_deduct = match game_pass_price.checked_mul(gamepass_to_add) {
+            Some(amount) => amount,
+            None => return tx_error_response(TxError::rejected(Status::BadRequest, "400: Bad Request - Amount is too large")),
+        };
+
+        let result = run_transaction(mongo, |mut session| async move {
+            let result: Result<User, TxError> = async {
+                let user = user_repo.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))?;
+                if user.ton_amount.is_none() {
+                    return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - User has no TON amount set"));
+                }
 
-    // Kullanıcıyı telegram_id ile bul
-    match user_repo.find_user_by_telegram_id(telegram_id).await {
-        Ok(Some(mut user)) => {
-            // Kullanıcının yeterli `ton_amount` değeri var mı kontrol et
-            if let Some(current_ton) = user.ton_amount {
-                if current_ton >= price {
-                    // Kaç `game_pass` alabileceğini hesapla
-                    let gamepass_to_add = (price / 5.0).floor() as i32; // `i32` olarak `game_pass` sayısını belirle
-                    let ton_to_deduct = gamepass_to_add as f64 * 5.0; // Toplam düşülecek TON miktarını `f64` olarak hesapla
-
-                    // `ton_amount` miktarını güncelle ve `game_pass` ekle
-                    user.ton_amount = Some(current_ton - ton_to_deduct);
-                    user.game_pass = Some(user.game_pass.unwrap_or(0) + gamepass_to_add);
-
-                    // Kullanıcıyı güncelle
-                    match user_repo.update_user_hp_and_gamepasston(&user).await {
-                        Ok(_) => (
-                            Status::Ok,
-                            Json(ApiResponse {
-                                message: "Gamepasses purchased successfully".to_string(),
-                                result: Some(user), // Güncellenmiş kullanıcı verisi
-                            }),
-                        ),
-                        Err(e) => {
-                            eprintln!("Error updating user: {:?}", e);
-                            (
-                                Status::InternalServerError,
-                                Json(ApiResponse {
-                                    message: "500: Internal Server Error - Unable to update user".to_string(),
-                                    result: None,
-                                }),
-                            )
-                        }
-                    }
-                } else {
-                    (
-                        Status::BadRequest,
-                        Json(ApiResponse {
-                            message: "400: Bad Request - Insufficient TON amount".to_string(),
-                            result: None,
-                        }),
-                    )
+                // `ton_amount` miktarını düşür ve `game_pass` ekle
+                if !user_repo.debit_with_session(&mut session, telegram_id, Asset::Ton, ton_to_deduct.nanoton()).await? {
+                    return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - Insufficient TON amount"));
                 }
-            } else {
-                (
-                    Status::BadRequest,
-                    Json(ApiResponse {
-                        message: "400: Bad Request - User has no TON amount set".to_string(),
-                        result: None,
-                    }),
-                )
-            }
-        },
-        Ok(None) => (
-            Status::NotFound,
-            Json(ApiResponse {
-                message: "404: Not Found - User not found".to_string(),
-                result: None,
-            }),
-        ),
-        Err(e) => {
-            eprintln!("Error finding user: {:?}", e);
-            (
-                Status::InternalServerError,
-                Json(ApiResponse {
-                    message: "500: Internal Server Error".to_string(),
-                    result: None,
-                }),
-            )
+                user_repo.credit_with_session(&mut session, telegram_id, Asset::GamePass, gamepass_to_add as i64).await?;
+                ledger_repo.record_with_session(&mut session, LedgerTransaction::exchange(
+                    Reason::Conversion,
+                    user_account(telegram_id),
+                    SYSTEM_EXCHANGE,
+                    (Asset::Ton, ton_to_deduct.nanoton()),
+                    (Asset::GamePass, gamepass_to_add),
+                    None,
+                )).await?;
+
+                user_repo.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))
+            }.await;
+            (session, result)
+        }).await;
+
+        match result {
+            Ok(user) => (Status::Ok, Json(ApiResponse {
+                message: "Gamepasses purchased successfully".to_string(),
+                result: Some(user), // Güncellenmiş kullanıcı verisi
+            })),
+            Err(e) => tx_error_response(e),
         }
-    }
+    }).await
 }
 
 
 
 #[get("/market")]
-async fn get_all_market(item_repo: &State<MarketRepository>) -> Json<ApiResponse<Vec<Market>>> {
+async fn get_all_market(_auth: AuthenticatedUser, item_repo: &State<MarketRepository>) -> Json<ApiResponse<Vec<Market>>> {
     match item_repo.get_all_market().await {
         Ok(markets) => Json(ApiResponse {
             message: "Market items retrieved successfully".to_string(),
@@ -1087,639 +1266,844 @@ async fn get_all_market(item_repo: &State<MarketRepository>) -> Json<ApiResponse
 }
 #[post("/market", format = "json", data = "<new_item>")]
 async fn add_market_item(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     market_repo: &State<MarketRepository>,
     user_repo: &State<UserRepository>,
     new_item: Json<Market>,
-) -> Json<ApiResponse<User>> {
-    let item = new_item.into_inner();
-    let telegram_id = item.seller;
-    let item_id = item.id.clone();
-
-    // Attempt to add the item to the market
-    match market_repo.add_item(item).await {
-        Ok(_) => {
-            // If the item was successfully added to the market, attempt to remove it from the user's inventory
-            match user_repo.remove_item_from_user(telegram_id, item_id.clone()).await {
-                Ok(_) => {
-                    // Fetch the updated user to return in the response
-                    match user_repo.find_user_by_telegram_id(telegram_id).await {
-                        Ok(Some(updated_user)) => Json(ApiResponse {
-                            message: "Item added to market and removed from user inventory successfully".to_string(),
-                            result: Some(updated_user),
-                        }),
-                        Ok(None) => Json(ApiResponse {
-                            message: "User not found after item removal".to_string(),
-                            result: None,
-                        }),
-                        Err(e) => {
-                            eprintln!("Error fetching updated user: {:?}", e);
-                            Json(ApiResponse {
-                                message: "Failed to fetch updated user after item removal".to_string(),
-                                result: None,
-                            })
-                        }
+) -> (Status, Json<ApiResponse<User>>) {
+    idempotency.with_body(&*new_item).run(async move {
+        let mut item = new_item.into_inner();
+        // Satıcı her zaman isteği yapan kullanıcıdır, gövdedeki değer yok sayılır
+        item.seller = auth.telegram_id;
+        let telegram_id = item.seller;
+        if item.price.is_negative() {
+            return tx_error_response(TxError::rejected(Status::BadRequest, "400: Bad Request - Price cannot be negative"));
+        }
+
+        // Item envanterden çıkarılıp market'e tek işlemde eklenir; envanterde olmayan item listelenemez
+        let result = run_transaction(mongo, |mut session| {
+            let item = item.clone();
+            async move {
+                let result: Result<User, TxError> = async {
+                    if !user_repo.remove_item_with_session(&mut session, telegram_id, item.id.clone()).await? {
+                        return Err(TxError::rejected(Status::NotFound, "404: Not Found - Item not found in user inventory"));
                     }
-                }
-                Err(_) => Json(ApiResponse {
-                    message: "Item added to market, but failed to remove from user inventory".to_string(),
-                    result: None,
-                }),
+                    market_repo.add_item_with_session(&mut session, &item).await?;
+
+                    // Fetch the updated user to return in the response
+                    user_repo.find_user_with_session(&mut session, telegram_id).await?
+                        .ok_or_else(|| TxError::rejected(Status::NotFound, "User not found after item removal"))
+                }.await;
+                (session, result)
             }
+        }).await;
+
+        match result {
+            Ok(updated_user) => (Status::Ok, Json(ApiResponse {
+                message: "Item added to market and removed from user inventory successfully".to_string(),
+                result: Some(updated_user),
+            })),
+            Err(e) => tx_error_response(e),
         }
-        Err(_) => Json(ApiResponse {
-            message: "Failed to add item to market".to_string(),
-            result: None,
-        }),
-    }
+    }).await
 }
 
 
-#[derive(Deserialize)]
+// Sistem mağazasından alınacak item; adı, itibar puanı ve fiyatı ekonomi belgesindedir
+#[derive(Deserialize, Serialize)]
 pub struct AddItemRequest {
-    pub item_name: String,
     pub item_slug: String,
-    pub reputation_points: i32,
-    pub telegram_id: i64,
-    pub hp: i32,
 }
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct AddItemRequestTon {
-    pub item_name: String,
     pub item_slug: String,
-    pub reputation_points: i32,
-    pub telegram_id: i64,
-    pub ton_amount: f64, // TON miktarı
 }
 
 
 #[post("/buy_item_sistem", format = "json", data = "<item_data>")]
 async fn buy_item_sistem(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
+    economy: &State<Arc<EconomyService>>,
     item_data: Json<AddItemRequest>,
 ) -> (Status, Json<ApiResponse<User>>) {
-    let telegram_id = item_data.telegram_id;
-    let hp_cost = item_data.hp;
-
-    match user_repo.find_user_by_telegram_id(telegram_id).await {
-        Ok(Some(mut user)) => {
-            if let Some(current_hp) = user.hp {
-                if current_hp >= hp_cost {
-                    // Kullanıcıya eklenmeye çalışılan item bilgisi
-                    let new_item = Item::new(
-                        item_data.item_name.clone(),
-                        item_data.item_slug.clone(),
-                        item_data.reputation_points,
-                    );
+    idempotency.with_body(&*item_data).run(async move {
+        let telegram_id = auth.telegram_id;
+        let rules = economy.current().rules.clone();
+        let Some(price) = rules.item(&item_data.item_slug) else {
+            return tx_error_response(TxError::Rejected(Status::NotFound, format!("404: Not Found - Item {} is not sold", item_data.item_slug)));
+        };
+        let Some(hp_cost) = price.hp_price else {
+            return tx_error_response(TxError::Rejected(Status::BadRequest, format!("400: Bad Request - Item {} is not sold for HP", item_data.item_slug)));
+        };
+
+        let result = run_transaction(mongo, |mut session| {
+            // Kullanıcıya eklenmeye çalışılan item bilgisi
+            let new_item = Item::new(price.item_name.clone(), price.item_slug.clone(), price.reputation_points);
+            async move {
+                let result: Result<User, TxError> = async {
+                    let user = user_repo.find_user_with_session(&mut session, telegram_id).await?
+                        .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))?;
+                    if user.hp.is_none() {
+                        return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - User has no HP set"));
+                    }
 
                     // Kullanıcı envanterinde aynı item var mı diye kontrol et
-                    if user.items.as_ref().unwrap_or(&vec![]).iter().any(|item| item.id == new_item.id) {
-                        return (
-                            Status::Conflict,
-                            Json(ApiResponse {
-                                message: "409: Conflict - Item already exists in inventory".to_string(),
-                                result: None,
-                            }),
-                        );
+                    if user.items.as_ref().unwrap_or(&vec![]).iter().any(|item| item.item_slug == new_item.item_slug) {
+                        return Err(TxError::rejected(Status::Conflict, "409: Conflict - Item already exists in inventory"));
                     }
 
-                    // Kullanıcıya item ekleme ve hp güncelleme
-                    match user_repo.add_item_to_user(telegram_id, new_item, hp_cost).await {
-                        Ok(Some(updated_user)) => (
-                            Status::Ok,
-                            Json(ApiResponse {
-                                message: "Item added successfully".to_string(),
-                                result: Some(updated_user),
-                            }),
-                        ),
-                        Ok(None) => (
-                            Status::NotFound,
-                            Json(ApiResponse {
-                                message: "404: Not Found - User not found after update".to_string(),
-                                result: None,
-                            }),
-                        ),
-                        Err(e) => {
-                            eprintln!("Error while adding item to user: {:?}", e);
-                            (
-                                Status::InternalServerError,
-                                Json(ApiResponse {
-                                    message: "500: Internal Server Error - Failed to add item".to_string(),
-                                    result: None,
-                                }),
-                            )
-                        },
+                    // HP'yi düş ve item'ı ekle
+                    if !user_repo.debit_with_session(&mut session, telegram_id, Asset::Hp, hp_cost as i64).await? {
+                        return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - Insufficient HP"));
                     }
-                } else {
-                    (
-                        Status::BadRequest,
-                        Json(ApiResponse {
-                            message: "400: Bad Request - Insufficient HP".to_string(),
-                            result: None,
-                        }),
-                    )
-                }
-            } else {
-                (
-                    Status::BadRequest,
-                    Json(ApiResponse {
-                        message: "400: Bad Request - User has no HP set".to_string(),
-                        result: None,
-                    }),
-                )
+                    user_repo.push_item_with_session(&mut session, telegram_id, &new_item).await?;
+                    ledger_repo.record_with_session(&mut session, LedgerTransaction::transfer(
+                        Reason::ItemPurchase,
+                        Asset::Hp,
+                        user_account(telegram_id),
+                        SYSTEM_SHOP.to_string(),
+                        hp_cost as i64,
+                        Some(format!("item:{}", new_item.item_slug)),
+                    )).await?;
+
+                    user_repo.find_user_with_session(&mut session, telegram_id).await?
+                        .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found after update"))
+                }.await;
+                (session, result)
             }
+        }).await;
+
+        match result {
+            Ok(updated_user) => (Status::Ok, Json(ApiResponse {
+                message: "Item added successfully".to_string(),
+                result: Some(updated_user),
+            })),
+            Err(e) => tx_error_response(e),
         }
-        Ok(None) => (
-            Status::NotFound,
-            Json(ApiResponse {
-                message: "404: Not Found - User not found".to_string(),
-                result: None,
-            }),
-        ),
-        Err(e) => {
-            eprintln!("Error while finding user: {:?}", e);
-            (
-                Status::InternalServerError,
-                Json(ApiResponse {
-                    message: "500: Internal Server Error - Failed to find user".to_string(),
-                    result: None,
-                }),
-            )
-        },
-    }
+    }).await
 }
 
 #[post("/buy_item_system_ton", format = "json", data = "<item_data>")]
 async fn buy_item_system_ton(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
+    economy: &State<Arc<EconomyService>>,
     item_data: Json<AddItemRequestTon>,
 ) -> (Status, Json<ApiResponse<User>>) {
-    let telegram_id = item_data.telegram_id;
-    let ton_cost = item_data.ton_amount;
-
-    match user_repo.find_user_by_telegram_id(telegram_id).await {
-        Ok(Some(mut user)) => {
-            if let Some(current_ton) = user.ton_amount {
-                // f64 ile karşılaştırma
-                if current_ton >= ton_cost {
-                    // Kullanıcıya eklenmeye çalışılan item bilgisi
-                    let new_item = Item::new(
-                        item_data.item_name.clone(),
-                        item_data.item_slug.clone(),
-                        item_data.reputation_points,
-                    );
+    idempotency.with_body(&*item_data).run(async move {
+        let telegram_id = auth.telegram_id;
+        let rules = economy.current().rules.clone();
+        let Some(price) = rules.item(&item_data.item_slug) else {
+            return tx_error_response(TxError::Rejected(Status::NotFound, format!("404: Not Found - Item {} is not sold", item_data.item_slug)));
+        };
+        let Some(ton_cost) = price.ton_price else {
+            return tx_error_response(TxError::Rejected(Status::BadRequest, format!("400: Bad Request - Item {} is not sold for TON", item_data.item_slug)));
+        };
+
+        let result = run_transaction(mongo, |mut session| {
+            // Kullanıcıya eklenmeye çalışılan item bilgisi
+            let new_item = Item::new(price.item_name.clone(), price.item_slug.clone(), price.reputation_points);
+            async move {
+                let result: Result<User, TxError> = async {
+                    let user = user_repo.find_user_with_session(&mut session, telegram_id).await?
+                        .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))?;
+                    if user.ton_amount.is_none() {
+                        return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - User has no TON amount set"));
+                    }
 
                     // Kullanıcı envanterinde aynı item var mı diye kontrol et
-                    if user.items.as_ref().unwrap_or(&vec![]).iter().any(|item| item.id == new_item.id) {
-                        return (
-                            Status::Conflict,
-                            Json(ApiResponse {
-                                message: "409: Conflict - Item already exists in inventory".to_string(),
-                                result: None,
-                            }),
-                        );
+                    if user.items.as_ref().unwrap_or(&vec![]).iter().any(|item| item.item_slug == new_item.item_slug) {
+                        return Err(TxError::rejected(Status::Conflict, "409: Conflict - Item already exists in inventory"));
                     }
 
-                    // Kullanıcıya item ekleme ve ton_amount'u güncelleme
-                    match user_repo.add_item_to_user_ton(telegram_id, new_item, ton_cost).await {
-                        Ok(Some(updated_user)) => {
-                            // TON miktarını güncelle
-                            user.ton_amount = Some(current_ton - ton_cost);
-                            // Kullanıcı bilgilerini güncelle
-                            user_repo.update_user_ton_amount(&user).await.unwrap();
-
-                            (
-                                Status::Ok,
-                                Json(ApiResponse {
-                                    message: "Item added successfully".to_string(),
-                                    result: Some(updated_user),
-                                }),
-                            )
-                        },
-                        Ok(None) => (
-                            Status::NotFound,
-                            Json(ApiResponse {
-                                message: "404: Not Found - User not found after update".to_string(),
-                                result: None,
-                            }),
-                        ),
-                        Err(e) => {
-                            eprintln!("Error while adding item to user: {:?}", e);
-                            (
-                                Status::InternalServerError,
-                                Json(ApiResponse {
-                                    message: "500: Internal Server Error - Failed to add item".to_string(),
-                                    result: None,
-                                }),
-                            )
-                        },
+                    // TON'u düş ve item'ı ekle
+                    if !user_repo.debit_with_session(&mut session, telegram_id, Asset::Ton, ton_cost.nanoton()).await? {
+                        return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - Insufficient TON amount"));
                     }
-                } else {
-                    (
-                        Status::BadRequest,
-                        Json(ApiResponse {
-                            message: "400: Bad Request - Insufficient TON amount".to_string(),
-                            result: None,
-                        }),
-                    )
-                }
-            } else {
-                (
-                    Status::BadRequest,
-                    Json(ApiResponse {
-                        message: "400: Bad Request - User has no TON amount set".to_string(),
-                        result: None,
-                    }),
-                )
+                    user_repo.push_item_with_session(&mut session, telegram_id, &new_item).await?;
+                    ledger_repo.record_with_session(&mut session, LedgerTransaction::transfer(
+                        Reason::ItemPurchase,
+                        Asset::Ton,
+                        user_account(telegram_id),
+                        SYSTEM_SHOP.to_string(),
+                        ton_cost.nanoton(),
+                        Some(format!("item:{}", new_item.item_slug)),
+                    )).await?;
+
+                    user_repo.find_user_with_session(&mut session, telegram_id).await?
+                        .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found after update"))
+                }.await;
+                (session, result)
             }
+        }).await;
+
+        match result {
+            Ok(updated_user) => (Status::Ok, Json(ApiResponse {
+                message: "Item added successfully".to_string(),
+                result: Some(updated_user),
+            })),
+            Err(e) => tx_error_response(e),
         }
-        Ok(None) => (
-            Status::NotFound,
-            Json(ApiResponse {
-                message: "404: Not Found - User not found".to_string(),
-                result: None,
-            }),
-        ),
-        Err(e) => {
-            eprintln!("Error while finding user: {:?}", e);
-            (
-                Status::InternalServerError,
-                Json(ApiResponse {
-                    message: "500: Internal Server Error - Failed to find user".to_string(),
-                    result: None,
-                }),
-            )
-        },
-    }
+    }).await
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct PurchaseRequest {
-    pub buyer_telegram_id: i64, // Ürünü satın alan kişinin telegram_id'si
     pub item_id: Binary, // Satın alınacak ürünün id'si
 }
 
 #[post("/purchase_item", format = "json", data = "<purchase_data>")]
 async fn purchase_item(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &State<UserRepository>,
     market_repo: &State<MarketRepository>,
+    ledger_repo: &State<LedgerRepository>,
     purchase_data: Json<PurchaseRequest>,
 ) -> (Status, Json<ApiResponse<User>>) {
-    let buyer_telegram_id = purchase_data.buyer_telegram_id;
-    let item_id = purchase_data.item_id.clone();
-
-    // 1. Market veritabanında ürünü bul
-    match market_repo.find_item_by_id(item_id.clone()).await {
-        Ok(Some(item)) => {
-            let item_price = item.price;
-            let seller_telegram_id = item.seller;
-
-            // 2. Alıcının `ton_amount`'unu kontrol et ve düşür
-            match user_repo.find_user_by_telegram_id(buyer_telegram_id).await {
-                Ok(Some(mut buyer)) => {
-                    if let Some(current_ton) = buyer.ton_amount {
-                        if current_ton >= item_price as f64 {
-                            // 3. Alıcının envanterine item'ı ekle
-                            let new_item = Item::new(
-                                item.item_name.clone(),
-                                item.item_slug.clone(),
-                                item.reputation_points,
-                            );
-                            buyer.ton_amount = Some(current_ton - item_price as f64);
-
-                            // 4. Satıcının `ton_amount`'unu güncelle
-                            match user_repo.find_user_by_telegram_id(seller_telegram_id).await {
-                                Ok(Some(mut seller)) => {
-                                    let seller_current_ton = seller.ton_amount.unwrap_or(0.0);
-                                    seller.ton_amount = Some(seller_current_ton + item_price as f64);
-
-                                    // 5. Satıcı ve alıcı güncellemelerini kaydet
-                                    user_repo.update_user_ton_amount(&buyer).await.unwrap();
-                                    user_repo.update_user_ton_amount(&seller).await.unwrap();
-
-                                    // 6. Market veritabanından ürünü kaldır
-                                    market_repo.delete_item_from_market(item_id).await.unwrap();
-
-                                    // 7. Alıcı envanterine item ekleme
-                                    match user_repo.add_item_to_user_market(buyer_telegram_id, new_item).await {
-                                        Ok(Some(updated_buyer)) => (
-                                            Status::Ok,
-                                            Json(ApiResponse {
-                                                message: "Item successfully purchased.".to_string(),
-                                                result: Some(updated_buyer),
-                                            }),
-                                        ),
-                                        _ => (
-                                            Status::InternalServerError,
-                                            Json(ApiResponse {
-                                                message: "Failed to add item to buyer inventory.".to_string(),
-                                                result: None,
-                                            }),
-                                        ),
-                                    }
-                                },
-                                _ => (
-                                    Status::NotFound,
-                                    Json(ApiResponse {
-                                        message: "Seller not found.".to_string(),
-                                        result: None,
-                                    }),
-                                ),
-                            }
-                        } else {
-                            // Yeterli TON miktarı yoksa hata döndür
-                            (
-                                Status::BadRequest,
-                                Json(ApiResponse {
-                                    message: "Insufficient TON amount.".to_string(),
-                                    result: None,
-                                }),
-                            )
-                        }
-                    } else {
-                        // Alıcıda ton_amount ayarlanmamışsa hata döndür
-                        (
-                            Status::BadRequest,
-                            Json(ApiResponse {
-                                message: "Buyer TON amount not set.".to_string(),
-                                result: None,
-                            }),
-                        )
+    idempotency.with_body(&*purchase_data).run(async move {
+        let buyer_telegram_id = auth.telegram_id; // Ürünü satın alan kişi
+        let item_id = purchase_data.item_id.clone();
+
+        // Tüm adımlar tek işlemde çalışır; herhangi biri başarısız olursa ilan ve bakiyeler eski haline döner
+        let result = run_transaction(mongo, |mut session| {
+            let item_id = item_id.clone();
+            async move {
+                let result: Result<User, TxError> = async {
+                    // 1. İlanı market'ten al. Silme koşullu olduğundan aynı ilanı yalnızca bir alıcı alabilir
+                    let item = market_repo.take_item_with_session(&mut session, item_id).await?
+                        .ok_or_else(|| TxError::rejected(Status::NotFound, "Item not found in market."))?;
+                    let item_price = item.price.nanoton();
+                    let seller_telegram_id = item.seller;
+
+                    // 2. Alıcının `ton_amount`'unu kontrol et ve düşür
+                    if user_repo.find_user_with_session(&mut session, buyer_telegram_id).await?.is_none() {
+                        return Err(TxError::rejected(Status::NotFound, "Buyer not found."));
                     }
-                },
-                _ => (
-                    Status::NotFound,
-                    Json(ApiResponse {
-                        message: "Buyer not found.".to_string(),
-                        result: None,
-                    }),
-                ),
+                    if !user_repo.debit_with_session(&mut session, buyer_telegram_id, Asset::Ton, item_price).await? {
+                        return Err(TxError::rejected(Status::BadRequest, "Insufficient TON amount."));
+                    }
+
+                    // 3. Satıcının `ton_amount`'unu güncelle
+                    if !user_repo.credit_with_session(&mut session, seller_telegram_id, Asset::Ton, item_price).await? {
+                        return Err(TxError::rejected(Status::NotFound, "Seller not found."));
+                    }
+
+                    // 4. Alıcı envanterine item ekleme
+                    let new_item = Item::new(
+                        item.item_name.clone(),
+                        item.item_slug.clone(),
+                        item.reputation_points,
+                    );
+                    user_repo.push_item_with_session(&mut session, buyer_telegram_id, &new_item).await?;
+
+                    let market_reference = Uuid::from_slice(&item.id.bytes)
+                        .map(|id| id.to_string())
+                        .unwrap_or_else(|_| item.item_slug.clone());
+                    ledger_repo.record_with_session(&mut session, LedgerTransaction::transfer(
+                        Reason::ItemPurchase,
+                        Asset::Ton,
+                        user_account(buyer_telegram_id),
+                        user_account(seller_telegram_id),
+                        item_price,
+                        Some(format!("market:{}", market_reference)),
+                    )).await?;
+
+                    user_repo.find_user_with_session(&mut session, buyer_telegram_id).await?
+                        .ok_or_else(|| TxError::rejected(Status::InternalServerError, "Failed to add item to buyer inventory."))
+                }.await;
+                (session, result)
             }
-        },
-        _ => (
-            Status::NotFound,
-            Json(ApiResponse {
-                message: "Item not found in market.".to_string(),
-                result: None,
-            }),
-        ),
-    }
+        }).await;
+
+        match result {
+            Ok(updated_buyer) => (Status::Ok, Json(ApiResponse {
+                message: "Item successfully purchased.".to_string(),
+                result: Some(updated_buyer),
+            })),
+            Err(e) => tx_error_response(e),
+        }
+    }).await
 }
 
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct TrackUserRequest {
-    pub telegram_id: i64, // Yeni kullanıcı
     pub referrer_id: i64, // Referans eden kullanıcının ID'si
 }
 
 #[post("/trackUser", format = "json", data = "<track_user_data>")]
 async fn track_user(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &rocket::State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
     track_user_data: Json<TrackUserRequest>,
 ) -> (Status, Json<ApiResponse<String>>) {
-    let new_user_id = track_user_data.telegram_id;
-    let referrer_id = track_user_data.referrer_id;
-
-    // Referans eden kullanıcıyı `referrer_id` ile bul
-    match user_repo.find_user_by_telegram_id(referrer_id).await {
-        Ok(Some(mut referrer)) => {
-            // Referans seviyesini güncelle
-            let references = referrer.references.get_or_insert_with(|| References {
-                level1: ReferenceLevel {
-                    total_reference_required: 5,
-                    is_started: true,
-                    is_finished: false,
-                    current_reference: 0,
-                },
-                level2: ReferenceLevel {
-                    total_reference_required: 100,
-                    is_started: false,
-                    is_finished: false,
-                    current_reference: 0,
-                },
-                level3: ReferenceLevel {
-                    total_reference_required: 500,
-                    is_started: false,
-                    is_finished: false,
-                    current_reference: 0,
-                },
-                level4: ReferenceLevel {
-                    total_reference_required: 0, // Gerekli değil
-                    is_started: false,
-                    is_finished: false,
-                    current_reference: 0,
-                },
-            });
-
-          // Referans seviyesini güncelleme ve ödül kontrolü
-            if references.level1.is_started && !references.level1.is_finished {
-                references.level1.current_reference += 1;
-                println!("Level 1: Current references = {}", references.level1.current_reference);
-                
-                if references.level1.current_reference >= references.level1.total_reference_required {
-                    references.level1.is_finished = true;
-                    references.level2.is_started = true;
-                    referrer.game_pass = Some(referrer.game_pass.unwrap_or(0) + 1);
-                    println!("Level 1 completed. Game Pass updated to {}", referrer.game_pass.unwrap_or(0));
-                }
-            } else if references.level2.is_started && !references.level2.is_finished {
-                references.level2.current_reference += 1;
-                if references.level2.current_reference >= references.level2.total_reference_required {
-                    references.level2.is_finished = true;
-                    references.level3.is_started = true;
-                    referrer.ton_amount = Some(referrer.ton_amount.unwrap_or(0.0) + 5.0); // f64 güncellemesi
+    idempotency.with_body(&*track_user_data).run(async move {
+        let new_user_id = auth.telegram_id; // Yeni kullanıcı
+        let referrer_id = track_user_data.referrer_id;
+
+        if referrer_id == new_user_id {
+            return (Status::BadRequest, Json(ApiResponse {
+                message: "400: Bad Request - Users cannot refer themselves".to_string(),
+                result: None,
+            }));
+        }
+
+        // Sayaç güncellemesi ve ödül aynı işlemde yazılır; eşzamanlı istekler birbirinin artışını ezemez
+        let result = run_transaction(mongo, |mut session| async move {
+            let result: Result<bool, TxError> = async {
+                // Referans eden kullanıcıyı `referrer_id` ile bul
+                let mut referrer = user_repo.find_user_with_session(&mut session, referrer_id).await?.ok_or_else(|| {
+                    TxError::Rejected(Status::NotFound, format!("404: Not Found - Referrer user not found for id {}", referrer_id))
+                })?;
+
+                // Yeni kullanıcı friends listesine eklenemediyse daha önce sayılmıştır; sayaç ve ödül değişmez
+                if !user_repo.add_friend_with_session(&mut session, referrer_id, new_user_id).await? {
+                    return Ok(false);
                 }
-            } else if references.level3.is_started && !references.level3.is_finished {
-                references.level3.current_reference += 1;
-                if references.level3.current_reference >= references.level3.total_reference_required {
-                    references.level3.is_finished = true;
-                    referrer.ton_amount = Some(referrer.ton_amount.unwrap_or(0.0) + 15.0); // f64 güncellemesi
+
+                // Referans seviyesini güncelle
+                let references = referrer.references.get_or_insert_with(|| References {
+                    level1: ReferenceLevel {
+                        total_reference_required: 5,
+                        is_started: true,
+                        is_finished: false,
+                        current_reference: 0,
+                    },
+                    level2: ReferenceLevel {
+                        total_reference_required: 100,
+                        is_started: false,
+                        is_finished: false,
+                        current_reference: 0,
+                    },
+                    level3: ReferenceLevel {
+                        total_reference_required: 500,
+                        is_started: false,
+                        is_finished: false,
+                        current_reference: 0,
+                    },
+                    level4: ReferenceLevel {
+                        total_reference_required: 0, // Gerekli değil
+                        is_started: false,
+                        is_finished: false,
+                        current_reference: 0,
+                    },
+                });
+
+                // Referans seviyesini güncelleme ve ödül kontrolü
+                let mut reward: Option<(Asset, i64)> = None;
+                if references.level1.is_started && !references.level1.is_finished {
+                    references.level1.current_reference += 1;
+                    println!("Level 1: Current references = {}", references.level1.current_reference);
+
+                    if references.level1.current_reference >= references.level1.total_reference_required {
+                        references.level1.is_finished = true;
+                        references.level2.is_started = true;
+                        reward = Some((Asset::GamePass, 1));
+                        println!("Level 1 completed. Game Pass reward granted to {}", referrer_id);
+                    }
+                } else if references.level2.is_started && !references.level2.is_finished {
+                    references.level2.current_reference += 1;
+                    if references.level2.current_reference >= references.level2.total_reference_required {
+                        references.level2.is_finished = true;
+                        references.level3.is_started = true;
+                        reward = Some((Asset::Ton, Ton::from_ton(5).nanoton()));
+                    }
+                } else if references.level3.is_started && !references.level3.is_finished {
+                    references.level3.current_reference += 1;
+                    if references.level3.current_reference >= references.level3.total_reference_required {
+                        references.level3.is_finished = true;
+                        reward = Some((Asset::Ton, Ton::from_ton(15).nanoton()));
+                    }
                 }
-            }
-          // Yeni kullanıcıyı friends listesine eklemeden önce kontrol et
-          let friends = referrer.friends.get_or_insert(vec![]);
-          if !friends.contains(&new_user_id) { // Eğer yeni kullanıcı arkadaşlar listesinde yoksa
-              friends.push(new_user_id);
-          } else {
-              println!("Kullanıcı zaten friends listesinde: {}", new_user_id);
-          }
-
-            // Güncellenmiş referans veren kullanıcıyı kaydet
-            match user_repo.update_user_references_and_friends(&referrer).await {
-                Ok(_) => (
-                    Status::Ok,
-                    Json(ApiResponse {
-                        message: "User tracked and referrer updated successfully".to_string(),
-                        result: None,
-                    }),
-                ),
-                Err(e) => {
-                    eprintln!("Error updating referrer: {:?}", e);
-                    (
-                        Status::InternalServerError,
-                        Json(ApiResponse {
-                            message: "500: Internal Server Error - Unable to update referrer".to_string(),
-                            result: None,
-                        }),
-                    )
+
+                // Güncellenmiş referans seviyelerini kaydet
+                user_repo.set_references_with_session(&mut session, &referrer).await?;
+
+                if let Some((asset, amount)) = reward {
+                    user_repo.credit_with_session(&mut session, referrer_id, asset, amount).await?;
+                    ledger_repo.record_with_session(&mut session, LedgerTransaction::transfer(
+                        Reason::ReferralReward,
+                        asset,
+                        SYSTEM_REWARDS.to_string(),
+                        user_account(referrer_id),
+                        amount,
+                        Some(format!("referral:{}", new_user_id)),
+                    )).await?;
                 }
-            }
-        },
-        Ok(None) => (
-            Status::NotFound,
-            Json(ApiResponse {
-                message: format!("404: Not Found - Referrer user not found for id {}", referrer_id),
+                Ok(true)
+            }.await;
+            (session, result)
+        }).await;
+
+        match result {
+            Ok(true) => (Status::Ok, Json(ApiResponse {
+                message: "User tracked and referrer updated successfully".to_string(),
                 result: None,
-            }),
-        ),
-        Err(e) => {
-            eprintln!("Error finding referrer user: {:?}", e);
-            (
-                Status::InternalServerError,
-                Json(ApiResponse {
-                    message: "500: Internal Server Error - Unable to find referrer".to_string(),
-                    result: None,
-                }),
-            )
+            })),
+            Ok(false) => (Status::Ok, Json(ApiResponse {
+                message: format!("User {} was already counted for referrer {}", new_user_id, referrer_id),
+                result: None,
+            })),
+            Err(e) => tx_error_response(e),
         }
-    }
+    }).await
 }
 
 
 
+// Yatırım adresi ve kullanıcıya özel memo; TON bu memo transfer yorumu olarak yazılıp gönderilmelidir
+#[get("/deposits/address")]
+async fn get_deposit_address(auth: AuthenticatedUser, config: &State<AppConfig>) -> Json<ApiResponse<DepositAddress>> {
+    Json(ApiResponse {
+        message: "Send TON to this address with the memo as the transfer comment".to_string(),
+        result: Some(DepositAddress {
+            wallet_address: config.deposit_wallet_address.clone(),
+            memo: deposit_memo(auth.telegram_id),
+        }),
+    })
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct DepositTonRequest {
-    pub telegram_id: i64,
-    pub ton_amount: f64, // Artık f64 olarak tamamen işlem yapıyoruz
+    pub tx_hash: String,
 }
 
-// Bu fonksiyon f64 olarak çalışır
+// İstemci gönderdiği transferi bildirir ve yatırım "pending" olarak kaydedilir.
+// Bakiye, deposit izleyici transferi zincirde gördüğünde eklenir.
 #[post("/deposit_ton", format = "json", data = "<deposit_data>")]
 async fn deposit_ton(
-    user_repo: &State<UserRepository>,
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    deposit_repo: &State<DepositRepository>,
     deposit_data: Json<DepositTonRequest>,
-) -> (Status, Json<ApiResponse<User>>) {
-    let telegram_id = deposit_data.telegram_id;
-    let additional_ton = deposit_data.ton_amount; // Artık doğrudan f64
+) -> (Status, Json<ApiResponse<Deposit>>) {
+    idempotency.with_body(&*deposit_data).run(async move {
+        let telegram_id = auth.telegram_id;
+        let tx_hash = deposit_data.tx_hash.trim();
+        if tx_hash.is_empty() || tx_hash.len() > 128 {
+            return tx_error_response(TxError::rejected(Status::BadRequest, "400: Bad Request - Invalid tx_hash"));
+        }
 
-    match user_repo.find_user_by_telegram_id(telegram_id).await {
-        Ok(Some(mut user)) => {
-            user.ton_amount = Some(user.ton_amount.unwrap_or(0.0) + additional_ton);
-
-            match user_repo.update_user_ton_amount(&user).await {
-                Ok(_) => {
-                    match user_repo.find_user_by_telegram_id(telegram_id).await {
-                        Ok(Some(updated_user)) => (
-                            Status::Ok,
-                            Json(ApiResponse {
-                                message: "Deposit successful".to_string(),
-                                result: Some(updated_user),
-                            }),
-                        ),
-                        Ok(None) => (
-                            Status::NotFound,
-                            Json(ApiResponse {
-                                message: "User not found after update".to_string(),
-                                result: None,
-                            }),
-                        ),
-                        Err(e) => {
-                            eprintln!("Error fetching updated user: {:?}", e);
-                            (
-                                Status::InternalServerError,
-                                Json(ApiResponse {
-                                    message: "500: Internal Server Error - Failed to fetch updated user".to_string(),
-                                    result: None,
-                                }),
-                            )
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error updating user: {:?}", e);
-                    (
-                        Status::InternalServerError,
-                        Json(ApiResponse {
-                            message: "500: Internal Server Error - Unable to update user".to_string(),
-                            result: None,
-                        }),
-                    )
-                }
-            }
+        let deposit = Deposit {
+            tx_hash: tx_hash.to_string(),
+            telegram_id: Some(telegram_id),
+            amount: None,
+            memo: Some(deposit_memo(telegram_id)),
+            sender: None,
+            status: DepositStatus::Pending,
+            failure_reason: None,
+            created_at: get_current_unix_timestamp(),
+            confirmed_at: None,
+        };
+
+        match deposit_repo.register_pending(&deposit).await {
+            Ok(true) => (Status::Accepted, Json(ApiResponse {
+                message: "Deposit is pending on-chain confirmation".to_string(),
+                result: Some(deposit),
+            })),
+            // Aynı işlem daha önce bildirilmiş ya da izleyici tarafından zaten işlenmiş
+            Ok(false) => match deposit_repo.find_by_tx_hash(tx_hash).await {
+                Ok(Some(existing)) if existing.telegram_id == Some(telegram_id) => (Status::Ok, Json(ApiResponse {
+                    message: "Deposit is already registered".to_string(),
+                    result: Some(existing),
+                })),
+                Ok(_) => tx_error_response(TxError::rejected(Status::Conflict, "409: Conflict - Transaction is registered to another user")),
+                Err(e) => tx_error_response(TxError::Database(e)),
+            },
+            Err(e) => tx_error_response(TxError::Database(e)),
         }
-        Ok(None) => (
-            Status::NotFound,
+    }).await
+}
+
+#[get("/users/<telegram_id>/deposits?<limit>")]
+async fn get_user_deposits(
+    auth: AuthenticatedUser,
+    deposit_repo: &State<DepositRepository>,
+    telegram_id: i64,
+    limit: Option<i64>,
+) -> (Status, Json<ApiResponse<Vec<Deposit>>>) {
+    if auth.telegram_id != telegram_id {
+        return (
+            Status::Forbidden,
             Json(ApiResponse {
-                message: "404: Not Found - User not found".to_string(),
+                message: "403: Forbidden - Cannot view another user's deposits".to_string(),
                 result: None,
+            }),
+        );
+    }
+
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    match deposit_repo.find_for_user(telegram_id, limit).await {
+        Ok(deposits) => (
+            Status::Ok,
+            Json(ApiResponse {
+                message: "200: Success".to_string(),
+                result: Some(deposits),
+            }),
+        ),
+        Err(e) => tx_error_response(TxError::Database(e)),
+    }
+}
+
+// Yalnızca `ton_indexer = "mock"` iken: sahte indexer'a zincirde görülmüş gibi bir transfer ekler
+#[post("/admin/mock_deposits", format = "json", data = "<transfer>")]
+async fn push_mock_deposit(
+    _admin: AdminUser,
+    mock_indexer: &State<Option<Arc<MockIndexer>>>,
+    transfer: Json<IncomingTransfer>,
+) -> (Status, Json<ApiResponse<IncomingTransfer>>) {
+    match mock_indexer.inner() {
+        Some(indexer) => {
+            indexer.push(transfer.0.clone());
+            (Status::Accepted, Json(ApiResponse {
+                message: "Transfer will be picked up by the deposit watcher".to_string(),
+                result: Some(transfer.0),
+            }))
+        }
+        None => tx_error_response(TxError::rejected(Status::NotFound, "404: Not Found - Mock indexer is not enabled")),
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct WithdrawalRequest {
+    pub amount: Ton, // nanoTON
+}
+
+// Kullanıcının `wallet_address`'ine çekim talebi; tutar onaya kadar bakiyeden kilitlenir
+#[post("/withdrawals", format = "json", data = "<withdrawal_data>")]
+async fn request_withdrawal(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    withdrawal_service: &State<WithdrawalService>,
+    withdrawal_data: Json<WithdrawalRequest>,
+) -> (Status, Json<ApiResponse<Withdrawal>>) {
+    idempotency.with_body(&*withdrawal_data).run(async move {
+        match withdrawal_service.request(auth.telegram_id, withdrawal_data.amount).await {
+            Ok(withdrawal) => (Status::Created, Json(ApiResponse {
+                message: "Withdrawal requested and awaiting approval".to_string(),
+                result: Some(withdrawal),
             })),
-        Err(e) => {
-            eprintln!("Error finding user: {:?}", e);
-            (
-                Status::InternalServerError,
-                Json(ApiResponse {
-                    message: "500: Internal Server Error".to_string(),
-                    result: None,
-                }),
-            )
+            Err(e) => tx_error_response(e),
         }
+    }).await
+}
+
+#[get("/users/<telegram_id>/withdrawals?<limit>")]
+async fn get_user_withdrawals(
+    auth: AuthenticatedUser,
+    withdrawal_service: &State<WithdrawalService>,
+    telegram_id: i64,
+    limit: Option<i64>,
+) -> (Status, Json<ApiResponse<Vec<Withdrawal>>>) {
+    if auth.telegram_id != telegram_id {
+        return (
+            Status::Forbidden,
+            Json(ApiResponse {
+                message: "403: Forbidden - Cannot view another user's withdrawals".to_string(),
+                result: None,
+            }),
+        );
+    }
+
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    match withdrawal_service.repository().find_for_user(telegram_id, limit).await {
+        Ok(withdrawals) => (Status::Ok, Json(ApiResponse {
+            message: "200: Success".to_string(),
+            result: Some(withdrawals),
+        })),
+        Err(e) => tx_error_response(TxError::Database(e)),
+    }
+}
+
+// Yönetici kuyruğu: varsayılan olarak onay bekleyen talepler, eskiden yeniye
+#[get("/admin/withdrawals?<status>&<limit>")]
+async fn admin_list_withdrawals(
+    _admin: AdminUser,
+    withdrawal_service: &State<WithdrawalService>,
+    status: Option<&str>,
+    limit: Option<i64>,
+) -> (Status, Json<ApiResponse<Vec<Withdrawal>>>) {
+    let statuses = [
+        WithdrawalStatus::Requested,
+        WithdrawalStatus::Approved,
+        WithdrawalStatus::Sent,
+        WithdrawalStatus::Failed,
+        WithdrawalStatus::Refunded,
+        WithdrawalStatus::Unknown,
+    ];
+    let status = match status {
+        None => WithdrawalStatus::Requested,
+        Some(status) => match statuses.into_iter().find(|s| s.as_str() == status) {
+            Some(status) => status,
+            None => return tx_error_response(TxError::rejected(Status::BadRequest, "400: Bad Request - Unknown withdrawal status")),
+        },
+    };
+
+    let limit = limit.unwrap_or(50).clamp(1, 200);
+    match withdrawal_service.repository().find_by_status(status, limit).await {
+        Ok(withdrawals) => (Status::Ok, Json(ApiResponse {
+            message: "200: Success".to_string(),
+            result: Some(withdrawals),
+        })),
+        Err(e) => tx_error_response(TxError::Database(e)),
+    }
+}
+
+#[post("/admin/withdrawals/<withdrawal_id>/approve")]
+async fn admin_approve_withdrawal(
+    admin: AdminUser,
+    withdrawal_service: &State<WithdrawalService>,
+    withdrawal_id: &str,
+) -> (Status, Json<ApiResponse<Withdrawal>>) {
+    withdrawal_response(withdrawal_service.approve(withdrawal_id, admin.telegram_id).await)
+}
+
+#[post("/admin/withdrawals/<withdrawal_id>/retry")]
+async fn admin_retry_withdrawal(
+    admin: AdminUser,
+    withdrawal_service: &State<WithdrawalService>,
+    withdrawal_id: &str,
+) -> (Status, Json<ApiResponse<Withdrawal>>) {
+    withdrawal_response(withdrawal_service.retry(withdrawal_id, admin.telegram_id).await)
+}
+
+// "approved" ya da "unknown" durumdaki gönderimin sonucunu cüzdan servisinden alır
+#[post("/admin/withdrawals/<withdrawal_id>/reconcile")]
+async fn admin_reconcile_withdrawal(
+    admin: AdminUser,
+    withdrawal_service: &State<WithdrawalService>,
+    withdrawal_id: &str,
+) -> (Status, Json<ApiResponse<Withdrawal>>) {
+    withdrawal_response(withdrawal_service.reconcile(withdrawal_id, admin.telegram_id).await)
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RejectWithdrawalRequest {
+    pub reason: Option<String>,
+}
+
+// Onay bekleyen ya da gönderimi başarısız olan talebi kapatır ve tutarı iade eder
+#[post("/admin/withdrawals/<withdrawal_id>/reject", format = "json", data = "<reject_data>")]
+async fn admin_reject_withdrawal(
+    admin: AdminUser,
+    withdrawal_service: &State<WithdrawalService>,
+    withdrawal_id: &str,
+    reject_data: Json<RejectWithdrawalRequest>,
+) -> (Status, Json<ApiResponse<Withdrawal>>) {
+    let reason = reject_data.into_inner().reason;
+    withdrawal_response(withdrawal_service.refund(withdrawal_id, admin.telegram_id, reason).await)
+}
+
+fn withdrawal_response(result: Result<Withdrawal, TxError>) -> (Status, Json<ApiResponse<Withdrawal>>) {
+    match result {
+        Ok(withdrawal) => (Status::Ok, Json(ApiResponse {
+            message: format!("Withdrawal is {}", withdrawal.status.as_str()),
+            result: Some(withdrawal),
+        })),
+        Err(e) => tx_error_response(e),
+    }
+}
+
+// Yürürlükteki ekonomi: dönüşüm oranları, game pass fiyatı, salon ücretleri ve ödüller
+#[get("/economy")]
+async fn get_economy(economy: &State<Arc<EconomyService>>) -> (Status, Json<ApiResponse<EconomyConfig>>) {
+    (Status::Ok, Json(ApiResponse {
+        message: "200: Success".to_string(),
+        result: Some((*economy.current()).clone()),
+    }))
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EconomyUpdateRequest {
+    pub expected_version: i64, // Değiştirilen sürüm; arada başka bir değişiklik olduysa 409
+    pub rules: EconomyRules,
+    pub note: Option<String>,
+}
+
+// Oranları yeniden dağıtım gerekmeden değiştirir; her API ve oyun sunucusu örneği yeni sürümü
+// `economy_reload_interval_secs` içinde alır
+#[put("/admin/economy", format = "json", data = "<update>")]
+async fn admin_update_economy(
+    admin: AdminUser,
+    economy: &State<Arc<EconomyService>>,
+    update: Json<EconomyUpdateRequest>,
+) -> (Status, Json<ApiResponse<EconomyConfig>>) {
+    let update = update.into_inner();
+    match economy.update(update.rules, update.expected_version, admin.telegram_id, update.note).await {
+        Ok(config) => (Status::Ok, Json(ApiResponse {
+            message: format!("Economy config updated to version {}", config.version),
+            result: Some(config),
+        })),
+        Err(e) => tx_error_response(e),
+    }
+}
+
+#[get("/admin/economy/history?<limit>")]
+async fn admin_economy_history(
+    _admin: AdminUser,
+    economy: &State<Arc<EconomyService>>,
+    limit: Option<i64>,
+) -> (Status, Json<ApiResponse<Vec<EconomyConfig>>>) {
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    match economy.repository().history(limit).await {
+        Ok(history) => (Status::Ok, Json(ApiResponse {
+            message: "200: Success".to_string(),
+            result: Some(history),
+        })),
+        Err(e) => tx_error_response(TxError::Database(e)),
     }
 }
 
 #[derive(Deserialize)]
+pub struct VoidGameRequest {
+    note: Option<String>,
+}
+
+// Süren bir canlı oyunu iptal ettirir. Oyun sunucusu isteği birkaç saniye içinde uygular:
+// oyunculara giriş ücretlerini iade eder, onlara `game_voided` gönderir ve oyunu kaydeder.
+#[post("/admin/games/<game_id>/void", format = "json", data = "<request>")]
+async fn admin_void_game(
+    game_id: &str,
+    admin: AdminUser,
+    live_game_repo: &State<LiveGameRepository>,
+    request: Json<VoidGameRequest>,
+) -> (Status, Json<ApiResponse<VoidRequest>>) {
+    match live_game_repo.find_state(game_id).await {
+        Ok(Some(state)) if state == "Started" => {}
+        Ok(Some(state)) => return (Status::Conflict, Json(ApiResponse {
+            message: format!("409: Conflict - Game {} is {} and can no longer be voided", game_id, state),
+            result: None,
+        })),
+        Ok(None) => return (Status::NotFound, Json(ApiResponse {
+            message: format!("404: Not Found - No live game {}", game_id),
+            result: None,
+        })),
+        Err(e) => return tx_error_response(TxError::Database(e)),
+    }
+
+    let void_request = VoidRequest {
+        game_id: game_id.to_string(),
+        admin_id: admin.telegram_id,
+        note: request.into_inner().note,
+        requested_at: get_current_unix_timestamp(),
+    };
+    match live_game_repo.request_void(&void_request).await {
+        Ok(true) => (Status::Accepted, Json(ApiResponse {
+            message: format!("Void requested for game {}", game_id),
+            result: Some(void_request),
+        })),
+        Ok(false) => (Status::Conflict, Json(ApiResponse {
+            message: format!("409: Conflict - Game {} already has a pending void request", game_id),
+            result: None,
+        })),
+        Err(e) => tx_error_response(TxError::Database(e)),
+    }
+}
+
+#[get("/admin/games/voided?<limit>")]
+async fn admin_voided_games(
+    _admin: AdminUser,
+    live_game_repo: &State<LiveGameRepository>,
+    limit: Option<i64>,
+) -> (Status, Json<ApiResponse<Vec<VoidedGame>>>) {
+    let limit = limit.unwrap_or(20).clamp(1, 100);
+    match live_game_repo.voided_games(limit).await {
+        Ok(games) => (Status::Ok, Json(ApiResponse {
+            message: "200: Success".to_string(),
+            result: Some(games),
+        })),
+        Err(e) => tx_error_response(TxError::Database(e)),
+    }
+}
+
+// Boost'un fiyatı ekonomi belgesinden seviyeye göre okunur
+#[derive(Deserialize, Serialize)]
 pub struct BoostRequest {
-    telegram_id: i64,
     requested_level: i32,
     currency_type: String, // "hp" veya "ton" olmalı
-    amount: f64,           // HP için tam sayı olarak değerlendirilir, TON için float
 }
 
 #[post("/apply_boost", format = "json", data = "<boost_data>")]
 async fn apply_boost(
+    auth: AuthenticatedUser,
+    idempotency: Idempotency,
+    mongo: &State<Client>,
     user_repo: &State<UserRepository>,
+    ledger_repo: &State<LedgerRepository>,
+    economy: &State<Arc<EconomyService>>,
     boost_data: Json<BoostRequest>,
 ) -> (Status, String) {
-    // İstek verilerini alıyoruz
-    let telegram_id = boost_data.telegram_id;
-    let requested_level = boost_data.requested_level;
-    let currency_type = &boost_data.currency_type;
-    let amount = boost_data.amount;
-
-    // Kullanıcı verilerini al
-    match user_repo.find_user_by_telegram_id(telegram_id).await {
-        Ok(Some(user)) => {
-            // HP veya TON miktarını kontrol et
-            match currency_type.as_str() {
-                "hp" => {
-                    if let Some(current_hp) = user.hp {
-                        if current_hp < amount as i32 {
-                            return (Status::BadRequest, "Not enough HP amount.".to_string());
-                        }
-                    } else {
-                        return (Status::BadRequest, "HP information not found.".to_string());
-                    }
+    idempotency.with_body(&*boost_data).run(async move {
+        // İstek verilerini alıyoruz
+        let telegram_id = auth.telegram_id;
+        let requested_level = boost_data.requested_level;
+        let Some(price) = economy.current().rules.boost(requested_level).cloned() else {
+            return (Status::BadRequest, "Boost level is not sold.".to_string());
+        };
+
+        // HP veya TON ile ödenir
+        let (asset, amount) = match boost_data.currency_type.as_str() {
+            "hp" => (Asset::Hp, price.hp_price),
+            "ton" => (Asset::Ton, price.ton_price.nanoton()),
+            _ => return (Status::BadRequest, "Geçersiz para birimi.".to_string()),
+        };
+
+        let result = run_transaction(mongo, |mut session| async move {
+            let result: Result<(), TxError> = async {
+                // Kullanıcı verilerini al
+                let user = user_repo.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "Kullanıcı bulunamadı."))?;
+                match asset {
+                    Asset::Hp if user.hp.is_none() => return Err(TxError::rejected(Status::BadRequest, "HP information not found.")),
+                    Asset::Ton if user.ton_amount.is_none() => return Err(TxError::rejected(Status::BadRequest, "TON information not found.")),
+                    _ => {}
                 }
-                "ton" => {
-                    if let Some(current_ton) = user.ton_amount {
-                        if current_ton < amount {
-                            return (Status::BadRequest, "Not enough TON amount.".to_string());
-                        }
-                    } else {
-                        return (Status::BadRequest, "TON information not found.".to_string());
-                    }
-                }
-                _ => return (Status::BadRequest, "Geçersiz para birimi.".to_string()),
-            }
 
-            // Boost uygulama işlemini başlat
-            match user_repo.apply_boost(telegram_id, requested_level, currency_type, amount).await {
-                Ok(_) => (Status::Ok, "Boost implemented successfully.".to_string()),
-                Err(_) => (Status::InternalServerError, "Boost application failed.".to_string()),
+                let boost = Boost::upgrade_to(user.boost.as_ref(), requested_level, get_current_unix_timestamp())
+                    .ok_or_else(|| TxError::rejected(Status::Conflict, "Boost cannot be applied at the requested level."))?;
+
+                // HP veya TON miktarını kontrol et ve düş
+                if !user_repo.debit_with_session(&mut session, telegram_id, asset, amount).await? {
+                    let message = if asset == Asset::Hp { "Not enough HP amount." } else { "Not enough TON amount." };
+                    return Err(TxError::rejected(Status::BadRequest, message));
+                }
+                user_repo.set_boost_with_session(&mut session, telegram_id, &boost).await?;
+                ledger_repo.record_with_session(&mut session, LedgerTransaction::transfer(
+                    Reason::Boost,
+                    asset,
+                    user_account(telegram_id),
+                    SYSTEM_SHOP.to_string(),
+                    amount,
+                    Some(format!("boost:{}", requested_level)),
+                )).await?;
+                Ok(())
+            }.await;
+            (session, result)
+        }).await;
+
+        match result {
+            Ok(()) => (Status::Ok, "Boost implemented successfully.".to_string()),
+            Err(TxError::Rejected(status, message)) => (status, message),
+            Err(TxError::Database(e)) => {
+                eprintln!("Boost application failed: {:?}", e);
+                (Status::InternalServerError, "Boost application failed.".to_string())
             }
         }
-        Ok(None) => (Status::NotFound, "Kullanıcı bulunamadı.".to_string()),
-        Err(_) => (Status::InternalServerError, "Kullanıcı verisi alınamadı.".to_string()),
-    }
+    }).await
 }
 
 
@@ -1732,27 +2116,117 @@ fn not_found(req: &Request) -> Json<ApiResponse<String>> {
     })
 }
 
+// 401 Yakalama (geçersiz veya eksik JWT)
+#[catch(401)]
+fn unauthorized(req: &Request) -> Json<ApiResponse<String>> {
+    Json(ApiResponse {
+        message: format!("401: Unauthorized - '{}' requires a valid bearer token", req.uri()),
+        result: None,
+    })
+}
+
 #[launch]
 async fn rocket() -> _ {
-    let client = Client::with_uri_str("mongodb://localhost:27017").await.unwrap();
+    // Rocket.toml + ROCKET_ ortam değişkenleri; geçersiz yapılandırmayla başlamıyoruz
+    let config = match AppConfig::load(&rocket::Config::figment()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let client = Client::with_uri_str(&config.mongo_uri).await.unwrap();
+
+    // Eski f64/i32 TON tutarlarını nanoTON'a çevir; yarım kalmış verilerle başlamıyoruz
+    match MoneyMigration::new(&client).run().await {
+        Ok(report) => println!(
+            "TON migration: {} users, {} market items, {} ledger transactions converted",
+            report.users, report.market_items, report.ledger_transactions
+        ),
+        Err(e) => {
+            eprintln!("TON migration failed: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Kodda ve ekonomi belgesinde tutulan salon ücret/ödül tablolarını salon belgelerine taşı
+    match SalonMigration::new(&client).run().await {
+        Ok(0) => {}
+        Ok(salons) => println!("Salon migration: {} salons now carry their own rules", salons),
+        Err(e) => {
+            eprintln!("Salon migration failed: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+
     let user_repo = UserRepository::new(&client);
-    let bot_token = "7066575107:AAExt9HNG6kR7_vYy4O8CovqAyLIJsD3E3o";
     // Salon ve Table repository'lerini burada oluşturup yönetin
     let salon_repo = SalonRepository::new(&client);
     let table_repo = TableRepository::new(&client);
     let market_repo = MarketRepository::new(&client);
     let game_repo = GameRepository::new(&client);
-    let redis_conn = setup_redis().await.unwrap(); // Redis bağlantısını kur
+    let ledger_repo = LedgerRepository::new(&client);
+    let deposit_repo = DepositRepository::new(&client);
+    if let Err(e) = deposit_repo.ensure_indexes().await {
+        eprintln!("Deposit indexes could not be created: {:?}", e);
+        std::process::exit(1);
+    }
+    if let Err(e) = WithdrawalRepository::new(&client).ensure_indexes().await {
+        eprintln!("Withdrawal indexes could not be created: {:?}", e);
+        std::process::exit(1);
+    }
+    // Ekonomi oranları Mongo'dan okunur ve yeniden başlatmadan güncellenebilir
+    let economy = match EconomyService::load(&client).await {
+        Ok(economy) => Arc::new(economy),
+        Err(e) => {
+            eprintln!("Economy config could not be loaded: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+    economy.clone().spawn_reloader(Duration::from_secs(config.economy_reload_interval_secs));
+    let mongo = client.clone(); // Çok belgeli işlemler (transaction) için oturum açmakta kullanılır
+    let redis_conn = setup_redis(&config.redis_url).await.unwrap(); // Redis bağlantısını kur
+    let idempotency_store = IdempotencyStore::new(redis_conn.connection, config.idempotency_ttl_secs);
+
+    // Cüzdana gelen TON transferlerini izleyip onaylanan yatırımları bakiyeye ekler
+    let mock_indexer = (config.ton_indexer == "mock").then(|| Arc::new(MockIndexer::default()));
+    let indexer: Arc<dyn TonIndexer> = match &mock_indexer {
+        Some(mock) => mock.clone(),
+        None => Arc::new(TonCenterIndexer::new(&config.ton_indexer_url, config.ton_indexer_api_key.clone())),
+    };
+    DepositWatcher::new(indexer, &client, &config.deposit_wallet_address, config.deposit_batch_size, config.deposit_pending_timeout_secs)
+        .spawn(Duration::from_secs(config.deposit_poll_interval_secs));
+
+    // Çekimler: gerçek ağda ayrı cüzdan servisi, yerelde sahte gönderici
+    let payouts: Arc<dyn TonTransfer> = match config.ton_wallet_service_url.as_deref() {
+        Some(url) if config.ton_payouts == "wallet_service" => Arc::new(WalletServiceTransfer::new(url, config.ton_wallet_service_token.clone())),
+        _ => Arc::new(FakeTonTransfer::default()),
+    };
+    let withdrawal_service = WithdrawalService::new(&client, payouts, config.min_withdrawal);
+
+    let telegram_auth = TelegramAuthFairing::new(&config.telegram_bot_token, config.telegram_auth_max_age_secs);
     rocket::build()
+        .manage(config)
+        .manage(mongo)
         .manage(user_repo)
         .manage(salon_repo)  // SalonRepository'yi yönetin
         .manage(table_repo)  // TableRepository'yi yönetin
         .manage(market_repo)  // TableRepository'yi yönetin
         .manage(game_repo) // GameRepository'yi yönetin
+        .manage(ledger_repo) // Bakiye defteri
+        .manage(idempotency_store) // İşlenmiş Idempotency-Key yanıtları
+        .manage(deposit_repo) // TON yatırımları
+        .manage(mock_indexer) // Yalnızca ton_indexer = "mock" iken Some
+        .manage(withdrawal_service) // TON çekim talepleri
+        .manage(economy) // Ekonomi oranları
+        .manage(LiveGameRepository::new(&client)) // Oyun sunucusunun süren oyunları ve iptal istekleri
         .attach(CORS) // CORS fairing ekleniyor
+        .attach(telegram_auth) // initData doğrulayıcısı
         .mount("/", routes![
             get_all_users,
             create_user,
+            auth_telegram,
             get_user_by_telegram_id,
             all_options, // CORS için OPTIONS route
             add_salon,
@@ -1769,6 +2243,20 @@ async fn rocket() -> _ {
             buy_gamepass_with_ton,
             track_user,
             deposit_ton,
+            get_deposit_address,
+            get_user_deposits,
+            push_mock_deposit,
+            request_withdrawal,
+            get_user_withdrawals,
+            admin_list_withdrawals,
+            admin_approve_withdrawal,
+            admin_retry_withdrawal, admin_reconcile_withdrawal,
+            admin_reject_withdrawal,
+            get_economy,
+            admin_update_economy,
+            admin_economy_history,
+            admin_void_game,
+            admin_voided_games,
             apply_boost,
             buy_item_system_ton,
             purchase_item,
@@ -1776,7 +2264,10 @@ async fn rocket() -> _ {
             ready_table_bots,
             leave_table_bot,
             get_leaderboard,
-            update_user_profile
+            update_user_profile,
+            verify_game_rolls,
+            get_user_transactions,
+            reconcile_user_balances
         ])
-        .register("/", catchers![not_found]) // 404 yakalayıcı
+        .register("/", catchers![not_found, unauthorized]) // 404 yakalayıcı
 }
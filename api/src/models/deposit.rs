@@ -0,0 +1,57 @@
+// models/deposit.rs
+
+use serde::{Deserialize, Serialize};
+use crate::models::money::Ton;
+
+const MEMO_PREFIX: &str = "deposit:";
+
+// Kullanıcının yatırım işlemine yazması gereken yorum (memo); gelen transfer bu memo ile eşleştirilir
+pub fn deposit_memo(telegram_id: i64) -> String {
+    format!("{}{}", MEMO_PREFIX, telegram_id)
+}
+
+// Memo'dan telegram_id'yi çözer; biçim uymuyorsa None
+pub fn telegram_id_from_memo(memo: &str) -> Option<i64> {
+    memo.trim()
+        .strip_prefix(MEMO_PREFIX)
+        .and_then(|id| id.parse::<i64>().ok())
+        .filter(|id| *id > 0)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositStatus {
+    Pending,   // İstemci bildirdi, zincirde henüz görülmedi
+    Expired,   // Süresinde zincirde görülmedi; transfer sonradan görülürse yine onaylanır
+    Confirmed, // Zincirde görüldü ve memo'nun sahibinin bakiyesine eklendi
+    Failed,    // Zincirde başarısız, memo bir kullanıcıya ait değil ya da kullanıcı yok
+}
+
+// Cüzdanımıza gelen bir TON transferinin kaydı; `tx_hash` benzersizdir,
+// böylece aynı işlem en fazla bir kez bakiyeye eklenir.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Deposit {
+    pub tx_hash: String,
+    pub telegram_id: Option<i64>, // Memo çözülemezse None
+    pub amount: Option<Ton>,      // Zincirde görülene kadar None
+    pub memo: Option<String>,
+    pub sender: Option<String>,
+    pub status: DepositStatus,
+    pub failure_reason: Option<String>,
+    pub created_at: i64,           // Unix timestamp
+    pub confirmed_at: Option<i64>, // Unix timestamp
+}
+
+impl Deposit {
+    // Onaylanmış ya da başarısız kayıtlar bir daha değişmez
+    pub fn is_settled(&self) -> bool {
+        matches!(self.status, DepositStatus::Confirmed | DepositStatus::Failed)
+    }
+}
+
+// Kullanıcıya gösterilen yatırım adresi ve memo
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DepositAddress {
+    pub wallet_address: String,
+    pub memo: String,
+}
@@ -0,0 +1,119 @@
+// models/economy.rs
+
+use serde::{Deserialize, Serialize};
+use crate::models::money::Ton;
+
+// Mongo'daki tek ekonomi belgesinin `_id`'si (economy.config)
+pub const ECONOMY_CONFIG_ID: &str = "economy";
+
+// Yöneticinin değiştirebildiği oranlar. Salona özgü ücret ve ödüller salon belgesindedir.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EconomyRules {
+    pub click_score_per_hp: i32, // 1 HP için harcanan click_score
+    pub hp_per_game_pass: i32,   // 1 game pass için harcanan HP
+    pub game_pass_price: Ton,    // 1 game pass'in TON fiyatı (nanoTON)
+    // Oyun sonunda sıralamaya göre item itibar puanı artışı (yüzde): [1., 2., 3., ...]
+    pub reputation_rewards_pct: Vec<i32>,
+    pub reputation_reward_rest_pct: i32, // Listenin dışında kalan sıralar için
+    // Sistem mağazasının item'ları; fiyat ve itibar puanı istemciden değil buradan okunur
+    #[serde(default)]
+    pub items: Vec<ItemPrice>,
+    #[serde(default = "default_boost_prices")]
+    pub boosts: Vec<BoostPrice>,
+}
+
+// Bir item'ın itibar puanı tavanı; pasif gelir itibar puanından hesaplanır
+pub const MAX_ITEM_REPUTATION_POINTS: i32 = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ItemPrice {
+    pub item_slug: String,
+    pub item_name: String,
+    pub reputation_points: i32,
+    pub hp_price: Option<i32>,   // None: HP ile satılmaz
+    pub ton_price: Option<Ton>,  // None: TON ile satılmaz (nanoTON)
+}
+
+// Boost seviyesinin fiyatı; süre ve çarpan seviyeden gelir
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BoostPrice {
+    pub level: i32,
+    pub hp_price: i64,
+    pub ton_price: Ton, // nanoTON
+}
+
+fn default_boost_prices() -> Vec<BoostPrice> {
+    vec![
+        BoostPrice { level: 1, hp_price: 1_000, ton_price: Ton::from_nanoton(500_000_000) },
+        BoostPrice { level: 2, hp_price: 2_500, ton_price: Ton::from_ton(1) },
+        BoostPrice { level: 3, hp_price: 7_000, ton_price: Ton::from_nanoton(2_500_000_000) },
+    ]
+}
+
+// Yürürlükteki ekonomi; her değişiklik sürümü bir artırır ve geçmişe de yazılır
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EconomyConfig {
+    pub version: i64,
+    #[serde(flatten)]
+    pub rules: EconomyRules,
+    pub updated_at: i64,            // Unix timestamp
+    pub updated_by: Option<String>, // "admin:<telegram_id>"; ilk kurulumda None
+    pub note: Option<String>,
+}
+
+impl Default for EconomyRules {
+    // Ekonomi belgesi henüz yokken kullanılan (ve veritabanına yazılan) başlangıç değerleri
+    fn default() -> Self {
+        EconomyRules {
+            click_score_per_hp: 100,
+            hp_per_game_pass: 100,
+            game_pass_price: Ton::from_ton(5),
+            reputation_rewards_pct: vec![20, 10, 5],
+            reputation_reward_rest_pct: 1,
+            items: vec![],
+            boosts: default_boost_prices(),
+        }
+    }
+}
+
+impl EconomyRules {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.click_score_per_hp <= 0 || self.hp_per_game_pass <= 0 {
+            return Err("click_score_per_hp and hp_per_game_pass must be positive".to_string());
+        }
+        if self.game_pass_price <= Ton::ZERO {
+            return Err("game_pass_price must be positive".to_string());
+        }
+        if self.reputation_rewards_pct.iter().any(|pct| *pct < 0) || self.reputation_reward_rest_pct < 0 {
+            return Err("Reputation rewards cannot be negative".to_string());
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            if item.item_slug.trim().is_empty() || self.items[..i].iter().any(|other| other.item_slug == item.item_slug) {
+                return Err("Item slugs must be non-empty and unique".to_string());
+            }
+            if !(0..=MAX_ITEM_REPUTATION_POINTS).contains(&item.reputation_points) {
+                return Err(format!("Item {}: reputation_points must be between 0 and {}", item.item_slug, MAX_ITEM_REPUTATION_POINTS));
+            }
+            if item.hp_price.is_some_and(|hp| hp <= 0) || item.ton_price.is_some_and(|ton| ton <= Ton::ZERO) {
+                return Err(format!("Item {}: prices must be positive", item.item_slug));
+            }
+        }
+        for (i, boost) in self.boosts.iter().enumerate() {
+            if !(1..=3).contains(&boost.level) || self.boosts[..i].iter().any(|other| other.level == boost.level) {
+                return Err("Boost levels must be unique and between 1 and 3".to_string());
+            }
+            if boost.hp_price <= 0 || boost.ton_price <= Ton::ZERO {
+                return Err(format!("Boost level {}: prices must be positive", boost.level));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn item(&self, item_slug: &str) -> Option<&ItemPrice> {
+        self.items.iter().find(|item| item.item_slug == item_slug)
+    }
+
+    pub fn boost(&self, level: i32) -> Option<&BoostPrice> {
+        self.boosts.iter().find(|boost| boost.level == level)
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use mongodb::bson::oid::ObjectId;
+use mongodb::bson::Bson;
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GameResult {
@@ -15,3 +17,49 @@ pub struct Player {
     pub player_id: String,
     pub rolls: Vec<i32>,
 }
+
+// live_games.void_requests: yöneticinin iptal isteği; oyun sunucusu isteği alıp oyunu iptal eder
+// ve giriş ücretlerini iade eder (server/src/models/livegame.rs ile aynı biçim)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VoidRequest {
+    pub game_id: String,
+    pub admin_id: i64,
+    pub note: Option<String>,
+    pub requested_at: i64,
+}
+
+// Oyun sunucusunun iptal ettiği oyunlar (games.voided_games)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VoidedGame {
+    pub game_id: String,
+    pub salon_id: String,
+    pub table_id: String,
+    pub reason: Bson, // {"kind": "server_restart" | "abandoned" | "disconnected" | "admin", ...}
+    pub refunded_players: Vec<i64>,
+    pub refund_per_player: i32,
+    pub voided_at: i64,
+}
+
+// Oyun sonunda açıklanan provably-fair seed'leri
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Fairness {
+    pub server_seed: String,
+    pub server_seed_hash: String,
+    pub client_seeds: HashMap<String, String>, // player_id -> client_seed
+}
+
+// Doğrulama için gereken alanlar; player_id sayıdır, eski bot masası kayıtlarında string tutulur
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FairnessRecord {
+    pub game_id: String,
+    pub players: Vec<RollRecord>,
+    pub fairness: Option<Fairness>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollRecord {
+    pub player_id: Bson,
+    pub rolls: Vec<i32>,
+    #[serde(default)]
+    pub tiebreak_rolls: Vec<i32>, // roll_off turlarının zarları; nonce'ları `rolls`'un ardından gelir
+}
@@ -0,0 +1,162 @@
+// models/ledger.rs
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use mongodb::bson::Bson;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::models::money::Ton;
+
+// Defterde tutulan varlık türleri
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    Hp,
+    Ton,
+    GamePass,
+    ClickScore,
+}
+
+impl Asset {
+    // Kullanıcı belgesinde bakiyenin tutulduğu alan
+    pub fn user_field(self) -> &'static str {
+        match self {
+            Asset::Hp => "hp",
+            Asset::Ton => "ton_amount",
+            Asset::GamePass => "game_pass",
+            Asset::ClickScore => "click_score",
+        }
+    }
+
+    // Kullanıcı belgesindeki saklama biçimi: TON nanoTON olarak Int64, diğerleri Int32
+    pub fn to_bson_amount(self, amount: i64) -> Bson {
+        match self {
+            Asset::Ton => Ton::from_nanoton(amount).into(),
+            _ => Bson::Int32(amount as i32),
+        }
+    }
+}
+
+// Bakiye değişikliğinin nedeni
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Deposit,
+    GameWin,
+    Bet,
+    ItemPurchase,
+    ReferralReward,
+    Boost,
+    Conversion,
+    Tap,
+    Withdrawal,
+    WithdrawalRefund,
+    PassiveIncome,
+    GameRefund, // İptal edilen oyunun giriş ücreti (sunucu yazar)
+}
+
+// Sistem hesapları: kullanıcı dışındaki karşı taraflar
+pub const SYSTEM_DEPOSITS: &str = "system:deposits";     // Zincir dışı/zincir üstü yatırımlar
+pub const SYSTEM_EXCHANGE: &str = "system:exchange";     // Varlıklar arası dönüşümler
+pub const SYSTEM_SHOP: &str = "system:shop";             // Sistem item'ları ve boost satışları
+pub const SYSTEM_REWARDS: &str = "system:rewards";       // Referans ödülleri
+pub const SYSTEM_BETS: &str = "system:bets";             // Masalara yatırılan game pass'ler
+pub const SYSTEM_GAME_PRIZES: &str = "system:game_prizes"; // Oyun ödülleri
+pub const SYSTEM_TAPS: &str = "system:taps";             // Tıklamalardan kazanılan click_score
+pub const SYSTEM_PASSIVE_INCOME: &str = "system:passive_income"; // Çevrimdışıyken biriken click_score (sunucu yazar)
+pub const SYSTEM_WITHDRAWALS_PENDING: &str = "system:withdrawals_pending"; // Onay bekleyen çekimlerde kilitli TON
+pub const SYSTEM_WITHDRAWALS: &str = "system:withdrawals"; // Zincire gönderilmiş çekimler
+
+pub fn user_account(telegram_id: i64) -> String {
+    format!("user:{}", telegram_id)
+}
+
+// Tek bir hesap hareketi: pozitif tutar alacak (credit), negatif tutar borç (debit).
+// Tutar varlığın en küçük biriminde tam sayıdır: TON için nanoTON, diğerleri için adet/puan.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedgerEntry {
+    pub account: String,
+    pub asset: Asset,
+    pub amount: i64,
+}
+
+// Çift taraflı kayıt: her varlık için hareketlerin toplamı sıfırdır
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedgerTransaction {
+    pub tx_id: String,
+    pub reason: Reason,
+    pub entries: Vec<LedgerEntry>,
+    pub reference: Option<String>, // Örn. game_id, market item id, salon/masa
+    pub created_at: i64,           // Unix timestamp
+}
+
+impl LedgerTransaction {
+    pub fn new(reason: Reason, entries: Vec<LedgerEntry>, reference: Option<String>) -> Self {
+        LedgerTransaction {
+            tx_id: Uuid::new_v4().to_string(),
+            reason,
+            entries,
+            reference,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs() as i64,
+        }
+    }
+
+    // `from` hesabından `to` hesabına tek varlık transferi
+    pub fn transfer(reason: Reason, asset: Asset, from: String, to: String, amount: i64, reference: Option<String>) -> Self {
+        Self::new(reason, vec![
+            LedgerEntry { account: from, asset, amount: -amount },
+            LedgerEntry { account: to, asset, amount },
+        ], reference)
+    }
+
+    // Kullanıcının bir varlığı verip karşı taraftan başka bir varlık aldığı işlem
+    pub fn exchange(
+        reason: Reason,
+        user: String,
+        counterparty: &str,
+        (give_asset, give_amount): (Asset, i64),
+        (get_asset, get_amount): (Asset, i64),
+        reference: Option<String>,
+    ) -> Self {
+        Self::new(reason, vec![
+            LedgerEntry { account: user.clone(), asset: give_asset, amount: -give_amount },
+            LedgerEntry { account: counterparty.to_string(), asset: give_asset, amount: give_amount },
+            LedgerEntry { account: counterparty.to_string(), asset: get_asset, amount: -get_amount },
+            LedgerEntry { account: user, asset: get_asset, amount: get_amount },
+        ], reference)
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        let mut totals: HashMap<Asset, i64> = HashMap::new();
+        for entry in &self.entries {
+            *totals.entry(entry.asset).or_insert(0) += entry.amount;
+        }
+        totals.values().all(|total| *total == 0)
+    }
+}
+
+// Kullanıcı belgesindeki bakiye ile defterden türetilen bakiyenin karşılaştırması
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BalanceReconciliation {
+    pub asset: Asset,
+    pub stored: i64,
+    pub ledger: i64,
+    pub difference: i64, // stored - ledger
+    pub matches: bool,
+}
+
+impl BalanceReconciliation {
+    pub fn new(asset: Asset, stored: i64, ledger: i64) -> Self {
+        let difference = stored - ledger;
+        BalanceReconciliation {
+            asset,
+            stored,
+            ledger,
+            difference,
+            matches: difference == 0,
+        }
+    }
+}
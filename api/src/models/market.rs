@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use mongodb::bson::{doc, Binary, Bson};
 use mongodb::bson::oid::ObjectId;
 use uuid::Uuid;
+use crate::models::money::Ton;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Market {
@@ -9,6 +10,6 @@ pub struct Market {
     pub item_name: String,
     pub item_slug: String,
     pub reputation_points: i32,
-    pub price: i32,
+    pub price: Ton, // nanoTON
     pub seller: i64, // telegram ID as seller identifier
 }
\ No newline at end of file
@@ -2,4 +2,9 @@ pub mod user;
 pub mod salon;
 pub mod table;
 pub mod market;
-pub mod game;
\ No newline at end of file
+pub mod game;
+pub mod ledger;
+pub mod money;
+pub mod deposit;
+pub mod withdrawal;
+pub mod economy;
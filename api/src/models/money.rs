@@ -0,0 +1,67 @@
+// models/money.rs
+
+use std::fmt;
+use mongodb::bson::Bson;
+use serde::{Deserialize, Serialize};
+
+pub const NANOTON_PER_TON: i64 = 1_000_000_000;
+
+// TON tutarı, nanoTON (1 TON = 1_000_000_000 nanoTON) cinsinden tam sayı.
+// BSON'a Int64, JSON'a tam sayı olarak yazılır; kayan nokta yuvarlama hatası oluşmaz.
+// İstemciler de tutarları nanoTON olarak gönderir (örn. 1.5 TON = 1500000000).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct Ton(i64);
+
+impl Ton {
+    pub const ZERO: Ton = Ton(0);
+
+    pub const fn from_nanoton(nanoton: i64) -> Self {
+        Ton(nanoton)
+    }
+
+    pub const fn from_ton(ton: i64) -> Self {
+        Ton(ton * NANOTON_PER_TON)
+    }
+
+    pub const fn nanoton(self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn checked_mul(self, factor: i64) -> Option<Ton> {
+        self.0.checked_mul(factor).map(Ton)
+    }
+
+    // Bu tutarın içinde kaç tane `unit` olduğu (aşağı yuvarlanır)
+    pub fn whole_units_of(self, unit: Ton) -> i64 {
+        if unit.0 <= 0 {
+            return 0;
+        }
+        self.0.div_euclid(unit.0)
+    }
+}
+
+impl fmt::Display for Ton {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let whole = abs / NANOTON_PER_TON as u64;
+        let fraction = abs % NANOTON_PER_TON as u64;
+        if fraction == 0 {
+            write!(f, "{}{} TON", sign, whole)
+        } else {
+            let fraction = format!("{:09}", fraction);
+            write!(f, "{}{}.{} TON", sign, whole, fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+impl From<Ton> for Bson {
+    fn from(amount: Ton) -> Self {
+        Bson::Int64(amount.0)
+    }
+}
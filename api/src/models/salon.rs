@@ -1,14 +1,55 @@
-
 // models/salon.rs
 
 use serde::{Deserialize, Serialize};
 use crate::models::table::Table;
 
+// Salonun ekonomisi belgenin kendisindedir: `add_salon` ile oluşturulan salon giriş ücretini,
+// kesintiyi, masa kapasitesini ve ödül dağılımını tam olarak tanımlar. Ödül havuzu masanın
+// `bet_amount`'udur. Oyun sunucusu da ödülleri bu alanlardan hesaplar.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Salon {
     pub salon_id: i32,
     pub name: String,        // Örnek: "Bronz", "Altın"
-    pub entry_fee: i32,      // Örnek: 1000 TL
+    pub entry_fee: i32,      // Masada "ready" olmak için düşülen game pass
+    pub rake_bps: i32,       // Ödül havuzundan kesilen pay, baz puan (1/10_000)
+    pub max_players: i32,    // Masa başına en fazla oyuncu
+    pub payout_split_bps: Vec<i32>, // Kesinti sonrası havuzun sıralamaya göre dağılımı [1., 2., ...]; toplamı 10_000
+    #[serde(default)]
+    pub tie_rule: TieRule,   // Zar toplamları eşit oyuncuların sırası nasıl belirlenir
     pub tables: Vec<Table>,  // Salon içindeki masalar
     pub created_at: i64,     // Salona giriş zamanı
 }
+
+// Eşit zar toplamlarının çözümü (server/src/services/tie_break.rs)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TieRule {
+    RollOff,    // Eşit oyuncular, eşitlik bozulana kadar birer zar daha atar
+    #[default]
+    SplitPot,   // Eşit oyuncular paylaştıkları sıraların ödüllerini eşit bölüşür
+    HighestDie, // Tek zarda en yüksek değeri atan öne geçer (sonra ikinci en yüksek, ...)
+}
+
+impl Salon {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.entry_fee < 0 {
+            return Err("entry_fee cannot be negative".to_string());
+        }
+        if !(0..=10_000).contains(&self.rake_bps) {
+            return Err("rake_bps must be between 0 and 10000".to_string());
+        }
+        if self.max_players < 2 {
+            return Err("max_players must be at least 2".to_string());
+        }
+        if self.payout_split_bps.is_empty() || self.payout_split_bps.len() > self.max_players as usize {
+            return Err("payout_split_bps needs between 1 and max_players entries".to_string());
+        }
+        if self.payout_split_bps.iter().any(|bps| *bps < 0) || self.payout_split_bps.iter().sum::<i32>() != 10_000 {
+            return Err("payout_split_bps entries must be non-negative and add up to 10000".to_string());
+        }
+        for table in &self.tables {
+            table.validate()?;
+        }
+        Ok(())
+    }
+}
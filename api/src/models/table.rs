@@ -6,13 +6,27 @@ use serde::{Deserialize, Serialize};
 pub struct Table {
     pub table_id: i32,
     pub players: Vec<Player>, // Masadaki oyuncular
-    pub bet_amount: i32,      // Bahis miktarı
+    pub bet_amount: i32,      // Ödül havuzu (HP); kesinti ve dağılım salondan gelir
     pub game_state: GameState, // Oyunun durumu
+    #[serde(default)]
+    pub fill_with_bots: bool,  // join_with_bots: sunucu boş koltukları hemen botlarla doldurur
+}
+
+// Masanın ödül havuzu sistemden ödenir; yanlış girilen bir havuz sınırsız HP basmasın
+pub const MAX_BET_AMOUNT: i32 = 100_000;
+
+impl Table {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0..=MAX_BET_AMOUNT).contains(&self.bet_amount) {
+            return Err(format!("Table {}: bet_amount must be between 0 and {}", self.table_id, MAX_BET_AMOUNT));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Player {
-    pub player_id: i64,        // Oyuncunun ID'si (telegram_id)
+    pub player_id: i64,        // Oyuncunun ID'si (telegram_id); botların id'leri negatiftir
     pub has_paid: bool,        // Ödeme yaptı mı?
     pub dice_rolls: Vec<i32>,  // Oyuncunun attığı zarlar
     pub is_active: bool,       // Oyuncu aktif mi?
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use mongodb::bson::{doc, spec::BinarySubtype, Binary, Bson, Document};
 use uuid::Uuid;
+use crate::models::money::Ton;
 use std::convert::From;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -10,6 +11,39 @@ pub struct Boost {
     pub duration_days: i32, // Boost kaç gün sürecek
 }
 
+impl Boost {
+    // Boost'un bittiği an (Unix timestamp)
+    pub fn ends_at(&self) -> i64 {
+        self.start_time + self.duration_days as i64 * 24 * 60 * 60
+    }
+
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.ends_at()
+    }
+
+    // İstenen seviyeye geçilebiliyorsa yeni boost'u döner. Etkin boost yoksa her seviye,
+    // varsa yalnızca 1→2 ve 1/2→3 yükseltmeleri geçerlidir.
+    pub fn upgrade_to(existing: Option<&Boost>, requested_level: i32, start_time: i64) -> Option<Boost> {
+        let duration_days = match requested_level {
+            1 => 1,
+            2 => 3,
+            3 => 10,
+            _ => return None, // Geçersiz level
+        };
+
+        let allowed = match existing.filter(|boost| boost.is_active(start_time)) {
+            None => true,
+            Some(boost) => (requested_level == 2 && boost.level == 1) || (requested_level == 3 && boost.level <= 2),
+        };
+
+        if allowed {
+            Some(Boost { level: requested_level, start_time, duration_days })
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReferenceLevel {
     pub total_reference_required: i32, // Level tamamlamak için gereken toplam referans
@@ -57,7 +91,7 @@ pub struct User {
     pub photo_url: Option<String>,
     pub language_code: Option<String>,
     pub hp: Option<i32>, // Tab Tab puanlarından kazanılan HP
-    pub ton_amount: Option<f64>, // Kullanıcının TON miktarı
+    pub ton_amount: Option<Ton>, // Kullanıcının TON miktarı (nanoTON)
     pub wallet_address: Option<String>, // Kullanıcının Ton Wallet adresi
 
     pub click_score: Option<i32>, // Toplam tıklama puanı
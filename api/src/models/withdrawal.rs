@@ -0,0 +1,94 @@
+// models/withdrawal.rs
+
+use serde::{Deserialize, Serialize};
+use crate::models::money::Ton;
+
+// Çekim talebinin durumu.
+//   requested -> approved -> sent
+//   requested -> refunded            (yönetici reddi)
+//   approved  -> failed -> approved  (yeniden deneme)
+//   approved  -> unknown             (gönderim yanıtsız kaldı; transfer yapılmış olabilir)
+//   unknown   -> sent | failed       (mutabakat, cüzdan servisine sorularak)
+//   failed    -> refunded
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalStatus {
+    Requested,
+    Approved,
+    Sent,
+    Failed,
+    Refunded,
+    Unknown,
+}
+
+impl WithdrawalStatus {
+    pub fn can_transition_to(self, next: WithdrawalStatus) -> bool {
+        use WithdrawalStatus::*;
+        matches!(
+            (self, next),
+            (Requested, Approved) | (Requested, Refunded) | (Approved, Sent) | (Approved, Failed) | (Approved, Unknown)
+                | (Unknown, Sent) | (Unknown, Failed) | (Failed, Approved) | (Failed, Refunded)
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WithdrawalStatus::Requested => "requested",
+            WithdrawalStatus::Approved => "approved",
+            WithdrawalStatus::Sent => "sent",
+            WithdrawalStatus::Failed => "failed",
+            WithdrawalStatus::Refunded => "refunded",
+            WithdrawalStatus::Unknown => "unknown",
+        }
+    }
+}
+
+// TON adresi biçim kontrolü: ham ("0:<64 hex>") ya da kullanıcı dostu (48 karakter base64/base64url)
+pub fn is_valid_ton_address(address: &str) -> bool {
+    match address.split_once(':') {
+        Some((workchain, hash)) => {
+            workchain.parse::<i32>().is_ok() && hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => address.len() == 48 && address.chars().all(|c| c.is_ascii_alphanumeric() || "+/-_".contains(c)),
+    }
+}
+
+// Talebin geçtiği her adım: kim, ne zaman, hangi duruma taşıdı
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WithdrawalEvent {
+    pub status: WithdrawalStatus,
+    pub actor: String,        // Örn. "user:<id>", "admin:<id>", "system"
+    pub note: Option<String>, // Red nedeni, hata mesajı, tx hash ...
+    pub at: i64,              // Unix timestamp
+}
+
+// Kullanıcının `wallet_address`'ine TON çekim talebi. Tutar talep anında bakiyeden düşülüp
+// bekleyen çekimler hesabında kilitlenir; gönderilince çıkar, reddedilirse geri iade edilir.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Withdrawal {
+    pub withdrawal_id: String,
+    pub telegram_id: i64,
+    pub amount: Ton, // nanoTON
+    pub destination: String,
+    pub status: WithdrawalStatus,
+    pub tx_hash: Option<String>,
+    pub history: Vec<WithdrawalEvent>,
+    pub created_at: i64, // Unix timestamp
+    pub updated_at: i64, // Unix timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_payout_is_only_settled_by_reconciliation() {
+        use WithdrawalStatus::*;
+        assert!(Approved.can_transition_to(Unknown));
+        assert!(Unknown.can_transition_to(Sent));
+        assert!(Unknown.can_transition_to(Failed));
+        // Gönderilmiş olabilecek bir çekim yeniden gönderilemez ve iade edilemez
+        assert!(!Unknown.can_transition_to(Approved));
+        assert!(!Unknown.can_transition_to(Refunded));
+    }
+}
@@ -0,0 +1,105 @@
+// src/repository/deposit_repository.rs
+use mongodb::{Client, ClientSession, Collection, IndexModel};
+use mongodb::bson::{doc, to_document, Document};
+use mongodb::error::{ErrorKind, Result, WriteFailure};
+use mongodb::options::{FindOptions, IndexOptions, UpdateOptions};
+use futures::stream::TryStreamExt;
+use crate::models::deposit::Deposit;
+
+// MongoDB'nin tekil indeks ihlali kodu
+const DUPLICATE_KEY: i32 = 11000;
+
+pub struct DepositRepository {
+    collection: Collection<Deposit>,
+    watcher_state: Collection<Document>, // Cüzdan başına izleyicinin son işlediği lt
+}
+
+impl DepositRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("deposits");
+        let collection = db.collection::<Deposit>("deposits");
+        let watcher_state = db.collection::<Document>("watcher_state");
+        DepositRepository { collection, watcher_state }
+    }
+
+    // `tx_hash` tekil indeksi, aynı işlemin iki kez kaydedilmesini (ve iki kez bakiyeye eklenmesini) engeller
+    pub async fn ensure_indexes(&self) -> Result<()> {
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "tx_hash": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "telegram_id": 1, "created_at": -1 })
+                .build(),
+        ];
+        self.collection.create_indexes(indexes, None).await.map(|_| ())
+    }
+
+    // İstemcinin bildirdiği yatırımı "pending" olarak kaydeder; `tx_hash` zaten varsa false döner
+    pub async fn register_pending(&self, deposit: &Deposit) -> Result<bool> {
+        match self.collection.insert_one(deposit, None).await {
+            Ok(_) => Ok(true),
+            Err(e) => match *e.kind {
+                ErrorKind::Write(WriteFailure::WriteError(ref write_error)) if write_error.code == DUPLICATE_KEY => Ok(false),
+                _ => Err(e),
+            },
+        }
+    }
+
+    pub async fn find_by_tx_hash(&self, tx_hash: &str) -> Result<Option<Deposit>> {
+        self.collection.find_one(doc! { "tx_hash": tx_hash }, None).await
+    }
+
+    pub async fn find_by_tx_hash_with_session(&self, session: &mut ClientSession, tx_hash: &str) -> Result<Option<Deposit>> {
+        self.collection.find_one_with_session(doc! { "tx_hash": tx_hash }, None, session).await
+    }
+
+    pub async fn insert_with_session(&self, session: &mut ClientSession, deposit: &Deposit) -> Result<()> {
+        self.collection.insert_one_with_session(deposit, None, session).await.map(|_| ())
+    }
+
+    // Bekleyen ya da süresi dolmuş kaydı sonuçlandırır; kayıt zaten sonuçlandıysa false döner
+    pub async fn settle_open_with_session(&self, session: &mut ClientSession, deposit: &Deposit) -> Result<bool> {
+        let filter = doc! { "tx_hash": &deposit.tx_hash, "status": { "$in": ["pending", "expired"] } };
+        let update = doc! { "$set": to_document(deposit)? };
+        let result = self.collection.update_one_with_session(filter, update, None, session).await?;
+        Ok(result.modified_count == 1)
+    }
+
+    // `cutoff`'tan önce bildirilip hâlâ zincirde görülmemiş yatırımları "expired" yapar; transfer
+    // sonradan görülürse kayıt yine onaylanır
+    pub async fn expire_stale_pending(&self, cutoff: i64, reason: &str) -> Result<u64> {
+        let filter = doc! { "status": "pending", "created_at": { "$lt": cutoff } };
+        let update = doc! { "$set": { "status": "expired", "failure_reason": reason } };
+        let result = self.collection.update_many(filter, update, None).await?;
+        Ok(result.modified_count)
+    }
+
+    // İzleyicinin cüzdanda sonuçlandırdığı son işlemin lt'si; henüz yoksa 0
+    pub async fn last_seen_lt(&self, wallet: &str) -> Result<u64> {
+        let state = self.watcher_state.find_one(doc! { "_id": wallet }, None).await?;
+        Ok(state.and_then(|s| s.get_i64("last_lt").ok()).unwrap_or(0) as u64)
+    }
+
+    pub async fn save_last_seen_lt(&self, wallet: &str, lt: u64) -> Result<()> {
+        let options = UpdateOptions::builder().upsert(true).build();
+        let update = doc! { "$max": { "last_lt": lt as i64 } };
+        self.watcher_state.update_one(doc! { "_id": wallet }, update, options).await.map(|_| ())
+    }
+
+    // Kullanıcının yatırımları, yeniden eskiye
+    pub async fn find_for_user(&self, telegram_id: i64, limit: i64) -> Result<Vec<Deposit>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .limit(limit)
+            .build();
+
+        let mut cursor = self.collection.find(doc! { "telegram_id": telegram_id }, options).await?;
+        let mut deposits = Vec::new();
+        while let Some(deposit) = cursor.try_next().await? {
+            deposits.push(deposit);
+        }
+        Ok(deposits)
+    }
+}
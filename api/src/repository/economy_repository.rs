@@ -0,0 +1,63 @@
+// src/repository/economy_repository.rs
+use mongodb::{Client, ClientSession, Collection, IndexModel};
+use mongodb::bson::{doc, to_document};
+use mongodb::error::Result;
+use mongodb::options::{FindOptions, IndexOptions, UpdateOptions};
+use futures::stream::TryStreamExt;
+use crate::models::economy::{EconomyConfig, ECONOMY_CONFIG_ID};
+
+pub struct EconomyRepository {
+    config: Collection<EconomyConfig>,
+    history: Collection<EconomyConfig>, // Yazılan her sürümün kopyası
+}
+
+impl EconomyRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("economy");
+        EconomyRepository {
+            config: db.collection::<EconomyConfig>("config"),
+            history: db.collection::<EconomyConfig>("history"),
+        }
+    }
+
+    pub async fn ensure_indexes(&self) -> Result<()> {
+        let index = IndexModel::builder()
+            .keys(doc! { "version": -1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.history.create_index(index, None).await.map(|_| ())
+    }
+
+    pub async fn load(&self) -> Result<Option<EconomyConfig>> {
+        self.config.find_one(doc! { "_id": ECONOMY_CONFIG_ID }, None).await
+    }
+
+    // Ekonomi belgesi yoksa `initial`'ı yazar; varsa dokunmaz. Yazıldıysa true döner.
+    pub async fn seed(&self, initial: &EconomyConfig) -> Result<bool> {
+        let update = doc! { "$setOnInsert": to_document(initial)? };
+        let options = UpdateOptions::builder().upsert(true).build();
+        let result = self.config.update_one(doc! { "_id": ECONOMY_CONFIG_ID }, update, options).await?;
+        if result.upserted_id.is_some() {
+            self.history.insert_one(initial, None).await?;
+        }
+        Ok(result.upserted_id.is_some())
+    }
+
+    // Belgeyi yalnızca hâlâ `expected_version` sürümündeyse değiştirir ve yeni sürümü geçmişe ekler.
+    // İki yöneticinin aynı sürümü aynı anda değiştirmesini bu karşılaştırma engeller.
+    pub async fn replace_with_session(&self, session: &mut ClientSession, expected_version: i64, next: &EconomyConfig) -> Result<bool> {
+        let filter = doc! { "_id": ECONOMY_CONFIG_ID, "version": expected_version };
+        let result = self.config.replace_one_with_session(filter, next, None, session).await?;
+        if result.modified_count != 1 {
+            return Ok(false);
+        }
+        self.history.insert_one_with_session(next, None, session).await?;
+        Ok(true)
+    }
+
+    // En yeni sürüm önce
+    pub async fn history(&self, limit: i64) -> Result<Vec<EconomyConfig>> {
+        let options = FindOptions::builder().sort(doc! { "version": -1 }).limit(limit).build();
+        self.history.find(doc! {}, options).await?.try_collect().await
+    }
+}
@@ -2,10 +2,11 @@ use mongodb::{Client, Collection};
 use mongodb::bson::{doc, Binary};
 use mongodb::error::Result;
 use futures::stream::TryStreamExt;
-use crate::models::game::GameResult;
+use crate::models::game::{FairnessRecord, GameResult};
 
 pub struct GameRepository {
     collection: Collection<GameResult>,
+    bot_collection: Collection<GameResult>,
 }
 
 
@@ -14,7 +15,8 @@ impl GameRepository {
     pub fn new(client: &Client) -> Self {
         let db = client.database("games"); // Veritabanı adı
         let collection = db.collection::<GameResult>("game_results"); // Koleksiyon adı
-        GameRepository { collection }
+        let bot_collection = db.collection::<GameResult>("game_results_bots");
+        GameRepository { collection, bot_collection }
     }
 
 
@@ -27,4 +29,21 @@ impl GameRepository {
         Ok(users)
     }
 
+    // Oyunun zarlarını ve açıklanan seed'lerini getirir (önce insan, sonra bot masaları)
+    pub async fn find_fairness_record(&self, game_id: &str) -> Result<Option<FairnessRecord>> {
+        let filter = doc! { "game_id": game_id };
+        let record = self.collection
+            .clone_with_type::<FairnessRecord>()
+            .find_one(filter.clone(), None)
+            .await?;
+        if record.is_some() {
+            return Ok(record);
+        }
+
+        self.bot_collection
+            .clone_with_type::<FairnessRecord>()
+            .find_one(filter, None)
+            .await
+    }
+
 }
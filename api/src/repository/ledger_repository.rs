@@ -0,0 +1,72 @@
+// src/repository/ledger_repository.rs
+use std::collections::HashMap;
+use mongodb::{Client, ClientSession, Collection};
+use mongodb::error::{Error, Result};
+use mongodb::bson::{doc, from_bson, Bson, Document};
+use mongodb::options::FindOptions;
+use futures::stream::TryStreamExt;
+use crate::models::ledger::{Asset, LedgerTransaction};
+
+pub struct LedgerRepository {
+    collection: Collection<LedgerTransaction>,
+}
+
+impl LedgerRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("ledger");
+        let collection = db.collection::<LedgerTransaction>("transactions");
+        LedgerRepository { collection }
+    }
+
+    // Kayıt, bakiyeyi değiştiren yazmalarla aynı işlem (transaction) içinde yapılır.
+    // Dengesiz (toplamı sıfır olmayan) işlemler kaydedilmez.
+    pub async fn record_with_session(&self, session: &mut ClientSession, transaction: LedgerTransaction) -> Result<()> {
+        if !transaction.is_balanced() {
+            return Err(Error::custom(format!("Unbalanced ledger transaction {}", transaction.tx_id)));
+        }
+        self.collection.insert_one_with_session(transaction, None, session).await.map(|_| ())
+    }
+
+    // Hesabın işlem geçmişi, yeniden eskiye; `page` 1'den başlar
+    pub async fn find_for_account(&self, account: &str, page: u64, limit: i64) -> Result<Vec<LedgerTransaction>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .skip((page.max(1) - 1) * limit as u64)
+            .limit(limit)
+            .build();
+
+        let mut cursor = self.collection.find(doc! { "entries.account": account }, options).await?;
+        let mut transactions = Vec::new();
+        while let Some(transaction) = cursor.try_next().await? {
+            transactions.push(transaction);
+        }
+        Ok(transactions)
+    }
+
+    // Hesabın defterden türetilen bakiyeleri
+    pub async fn balances_for_account(&self, account: &str) -> Result<HashMap<Asset, i64>> {
+        let pipeline = vec![
+            doc! { "$match": { "entries.account": account } },
+            doc! { "$unwind": "$entries" },
+            doc! { "$match": { "entries.account": account } },
+            doc! { "$group": { "_id": "$entries.asset", "total": { "$sum": "$entries.amount" } } },
+        ];
+
+        let mut cursor = self.collection.aggregate(pipeline, None).await?;
+        let mut balances = HashMap::new();
+        while let Some(row) = cursor.try_next().await? {
+            let row: Document = row;
+            let asset: Asset = match from_bson(row.get("_id").cloned().unwrap_or(Bson::Null)) {
+                Ok(asset) => asset,
+                Err(_) => continue,
+            };
+            let total = match row.get("total") {
+                Some(Bson::Int32(v)) => *v as i64,
+                Some(Bson::Int64(v)) => *v,
+                _ => 0,
+            };
+            balances.insert(asset, total);
+        }
+        Ok(balances)
+    }
+}
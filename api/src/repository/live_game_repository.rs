@@ -0,0 +1,44 @@
+use mongodb::{Client, Collection};
+use mongodb::bson::{doc, to_document, Document};
+use mongodb::error::Result;
+use mongodb::options::{FindOptions, UpdateOptions};
+use futures::stream::TryStreamExt;
+use crate::models::game::{VoidRequest, VoidedGame};
+
+// Oyun sunucusunun süren oyun checkpoint'leri (live_games.live_games) yalnızca okunur;
+// iptal istekleri sunucuya live_games.void_requests üzerinden iletilir
+pub struct LiveGameRepository {
+    games: Collection<Document>,
+    void_requests: Collection<VoidRequest>,
+    voided: Collection<VoidedGame>,
+}
+
+impl LiveGameRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("live_games");
+        LiveGameRepository {
+            games: db.collection("live_games"),
+            void_requests: db.collection("void_requests"),
+            voided: client.database("games").collection("voided_games"),
+        }
+    }
+
+    // Süren oyunun durumu ("Started", "Completed", "Voided"); checkpoint yoksa None
+    pub async fn find_state(&self, game_id: &str) -> Result<Option<String>> {
+        let game = self.games.find_one(doc! { "game_id": game_id }, None).await?;
+        Ok(game.and_then(|g| g.get_str("state").ok().map(str::to_string)))
+    }
+
+    // İsteği kaydeder; aynı oyun için bekleyen bir istek varsa hiçbir şey yazmadan false döner
+    pub async fn request_void(&self, request: &VoidRequest) -> Result<bool> {
+        let options = UpdateOptions::builder().upsert(true).build();
+        let update = doc! { "$setOnInsert": to_document(request)? };
+        let result = self.void_requests.update_one(doc! { "game_id": &request.game_id }, update, options).await?;
+        Ok(result.upserted_id.is_some())
+    }
+
+    pub async fn voided_games(&self, limit: i64) -> Result<Vec<VoidedGame>> {
+        let options = FindOptions::builder().sort(doc! { "voided_at": -1 }).limit(limit).build();
+        self.voided.find(None, options).await?.try_collect().await
+    }
+}
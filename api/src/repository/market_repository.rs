@@ -1,4 +1,4 @@
-use mongodb::{Client, Collection};
+use mongodb::{Client, ClientSession, Collection};
 use mongodb::bson::{doc, Binary};
 use mongodb::error::Result;
 use futures::stream::TryStreamExt;
@@ -30,13 +30,13 @@ impl MarketRepository {
     }
 
     // Yeni bir item ekleme
-    pub async fn add_item(&self, item: Market) -> Result<()> {
-        self.collection.insert_one(item, None).await.map(|_| ())
+    pub async fn add_item_with_session(&self, session: &mut ClientSession, item: &Market) -> Result<()> {
+        self.collection.insert_one_with_session(item, None, session).await.map(|_| ())
     }
 
-    // Belirli bir item_id ile item silme
-    pub async fn delete_item_from_market(&self, item_id: Binary) -> Result<()> {
+    // İlanı silerek döndürür; aynı ilanı yalnızca bir işlem alabilir, diğerleri `None` görür
+    pub async fn take_item_with_session(&self, session: &mut ClientSession, item_id: Binary) -> Result<Option<Market>> {
         let filter = doc! { "id": item_id };
-        self.collection.delete_one(filter, None).await.map(|_| ())
+        self.collection.find_one_and_delete_with_session(filter, None, session).await
     }
 }
@@ -3,3 +3,10 @@ pub mod table_repository;
 pub mod salon_repository;
 pub mod market_repository;
 pub mod game_repository;
+pub mod ledger_repository;
+pub mod money_migration;
+pub mod deposit_repository;
+pub mod withdrawal_repository;
+pub mod economy_repository;
+pub mod salon_migration;
+pub mod live_game_repository;
@@ -0,0 +1,76 @@
+// src/repository/money_migration.rs
+use mongodb::{Client, Collection};
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::error::Result;
+use crate::models::money::NANOTON_PER_TON;
+
+// Eski belgelerdeki kayan noktalı / i32 TON tutarlarını nanoTON (Int64) biçimine çevirir.
+// Yalnızca henüz çevrilmemiş (double veya int32 tipindeki) alanlara dokunduğu için
+// her açılışta güvenle tekrar çalıştırılabilir.
+pub struct MoneyMigration {
+    users: Collection<Document>,
+    market: Collection<Document>,
+    ledger: Collection<Document>,
+}
+
+#[derive(Debug, Default)]
+pub struct MoneyMigrationReport {
+    pub users: u64,
+    pub market_items: u64,
+    pub ledger_transactions: u64,
+}
+
+// `value` (TON cinsinden) -> en yakın nanoTON, Int64
+fn to_nanoton(value: Bson) -> Document {
+    doc! { "$toLong": { "$round": [{ "$multiply": [value, NANOTON_PER_TON] }, 0] } }
+}
+
+impl MoneyMigration {
+    pub fn new(client: &Client) -> Self {
+        MoneyMigration {
+            users: client.database("users").collection("users"),
+            market: client.database("market").collection("market"),
+            ledger: client.database("ledger").collection("transactions"),
+        }
+    }
+
+    pub async fn run(&self) -> Result<MoneyMigrationReport> {
+        let legacy_number = doc! { "$type": ["double", "int"] };
+
+        let users = self.users.update_many(
+            doc! { "ton_amount": legacy_number.clone() },
+            vec![doc! { "$set": { "ton_amount": to_nanoton(Bson::from("$ton_amount")) } }],
+            None,
+        ).await?;
+
+        let market = self.market.update_many(
+            doc! { "price": legacy_number },
+            vec![doc! { "$set": { "price": to_nanoton(Bson::from("$price")) } }],
+            None,
+        ).await?;
+
+        // Defterde TON hareketleri nanoTON'a, diğer varlıklar tam sayıya çevrilir
+        let entry_amount = doc! {
+            "$cond": [
+                { "$eq": ["$$entry.asset", "ton"] },
+                to_nanoton(Bson::from("$$entry.amount")),
+                { "$toLong": { "$round": ["$$entry.amount", 0] } },
+            ]
+        };
+        let ledger = self.ledger.update_many(
+            doc! { "entries.amount": { "$type": "double" } },
+            vec![doc! { "$set": { "entries": { "$map": {
+                "input": "$entries",
+                "as": "entry",
+                "in": { "$mergeObjects": ["$$entry", { "amount": entry_amount }] },
+            }}}}],
+            None,
+        ).await?;
+
+        Ok(MoneyMigrationReport {
+            users: users.modified_count,
+            market_items: market.modified_count,
+            ledger_transactions: ledger.modified_count,
+        })
+    }
+}
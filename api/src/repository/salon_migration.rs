@@ -0,0 +1,92 @@
+// src/repository/salon_migration.rs
+use mongodb::{Client, Collection};
+use mongodb::bson::{doc, from_document, Document};
+use mongodb::error::Result;
+use futures::stream::TryStreamExt;
+use serde::Deserialize;
+use crate::models::economy::ECONOMY_CONFIG_ID;
+
+// Salon ekonomisi önce kodda (salon_id 1-6 eşleşmeleri), sonra ekonomi belgesinin `salons`
+// listesinde tutuluyordu. Bu geçiş, `rake_bps` alanı olmayan salon belgelerine o değerleri
+// yazar ve ekonomi belgesinden eski listeyi kaldırır. Her açılışta güvenle tekrar çalışır.
+pub struct SalonMigration {
+    salons: Collection<Document>,
+    economy: Collection<Document>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct LegacySalonPricing {
+    salon_id: i32,
+    pass_cost: i32,
+    bet_amount: i32,
+    payout_bps: i32,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyEconomy {
+    salons: Vec<LegacySalonPricing>,
+    default_salon: LegacySalonPricing,
+}
+
+// Eski masaların kapasitesi (1 oyuncu + 3 bot)
+const LEGACY_MAX_PLAYERS: i32 = 4;
+
+impl LegacyEconomy {
+    // Kodda sabit olan eski tablo
+    fn hard_coded() -> Self {
+        let salon = |salon_id, pass_cost, bet_amount, payout_bps| LegacySalonPricing { salon_id, pass_cost, bet_amount, payout_bps };
+        LegacyEconomy {
+            salons: vec![
+                salon(1, 1, 20, 8_000),
+                salon(2, 3, 60, 8_500),
+                salon(3, 5, 100, 9_000),
+                salon(4, 10, 200, 9_500),
+                salon(5, 15, 300, 9_800),
+                salon(6, 25, 500, 9_900),
+            ],
+            default_salon: salon(0, 0, 20, 8_000),
+        }
+    }
+
+    fn pricing(&self, salon_id: i32) -> &LegacySalonPricing {
+        self.salons.iter().find(|s| s.salon_id == salon_id).unwrap_or(&self.default_salon)
+    }
+}
+
+impl SalonMigration {
+    pub fn new(client: &Client) -> Self {
+        SalonMigration {
+            salons: client.database("salons").collection("salons"),
+            economy: client.database("economy").collection("config"),
+        }
+    }
+
+    // Güncellenen salon sayısını döner
+    pub async fn run(&self) -> Result<u64> {
+        // Yöneticinin ekonomi belgesinde değiştirdiği değerler koddaki tablodan önceliklidir
+        let economy_filter = doc! { "_id": ECONOMY_CONFIG_ID, "salons": { "$exists": true } };
+        let legacy = match self.economy.find_one(economy_filter.clone(), None).await? {
+            Some(document) => from_document::<LegacyEconomy>(document).unwrap_or_else(|_| LegacyEconomy::hard_coded()),
+            None => LegacyEconomy::hard_coded(),
+        };
+
+        let mut migrated = 0;
+        let mut cursor = self.salons.find(doc! { "rake_bps": { "$exists": false } }, None).await?;
+        while let Some(salon) = cursor.try_next().await? {
+            let salon_id = salon.get_i32("salon_id").unwrap_or_default();
+            let pricing = legacy.pricing(salon_id);
+            let update = doc! { "$set": {
+                "entry_fee": pricing.pass_cost,
+                "rake_bps": 10_000 - pricing.payout_bps,
+                "max_players": LEGACY_MAX_PLAYERS,
+                "payout_split_bps": [10_000],
+                "tables.$[].bet_amount": pricing.bet_amount,
+            }};
+            self.salons.update_one(doc! { "_id": salon.get("_id").cloned() }, update, None).await?;
+            migrated += 1;
+        }
+
+        self.economy.update_one(economy_filter, doc! { "$unset": { "salons": "", "default_salon": "" } }, None).await?;
+        Ok(migrated)
+    }
+}
@@ -1,9 +1,10 @@
-use mongodb::{Client, Collection};
+use mongodb::{Client, ClientSession, Collection};
+use mongodb::options::UpdateOptions;
 use mongodb::error::Result;
 use mongodb::bson::{doc, to_bson};
 use futures::stream::TryStreamExt;
 use crate::models::salon::Salon;
-use crate::models::table::{Player}; // Gerekli yapıları içe aktar
+use crate::models::table::{GameState, Player}; // Gerekli yapıları içe aktar
 use crate::models::table::Table; // Table yapısını içe aktarın
 
 
@@ -101,5 +102,106 @@ impl SalonRepository {
         self.collection.update_one(filter, update, None).await?;
         Ok(())
     }
-    
+
+    // Oyuncuyu masaya oturtur. Masa beklemedeyse, oyuncu salondaki hiçbir masada değilse ve masada
+    // `max_seated`'den az oyuncu varsa yazar; aksi halde `false` döner. Kontrol ve ekleme tek bir
+    // koşullu güncellemedir, böylece eşzamanlı katılımlar masayı taşıramaz.
+    pub async fn seat_player(
+        &self,
+        salon_id: i32,
+        table_id: i32,
+        player: &Player,
+        max_seated: usize,
+        fill_with_bots: bool,
+    ) -> Result<bool> {
+        let mut table_filter = doc! {
+            "table_id": table_id,
+            "game_state": to_bson(&GameState::Waiting)?,
+        };
+        if max_seated > 0 {
+            table_filter.insert(format!("players.{}", max_seated - 1), doc! { "$exists": false });
+        }
+        let filter = doc! {
+            "salon_id": salon_id,
+            "tables.players.player_id": { "$ne": player.player_id },
+            "tables": { "$elemMatch": table_filter },
+        };
+        let mut update = doc! { "$push": { "tables.$.players": to_bson(player)? } };
+        if fill_with_bots {
+            update.insert("$set", doc! { "tables.$.fill_with_bots": true });
+        }
+
+        let result = self.collection.update_one(filter, update, None).await?;
+        Ok(result.modified_count == 1)
+    }
+
+    pub async fn find_salon_with_session(&self, session: &mut ClientSession, salon_id: i32) -> Result<Option<Salon>> {
+        let filter = doc! { "salon_id": salon_id };
+        self.collection.find_one_with_session(filter, None, session).await
+    }
+
+    // Verilen oyuncuları masada "ödedi" olarak işaretler. Yalnızca henüz ödememiş oyuncular güncellenir;
+    // ilk oyuncu masada değilse ya da zaten ödediyse hiçbir şey yazmadan `false` döner.
+    pub async fn mark_players_paid_with_session(
+        &self,
+        session: &mut ClientSession,
+        salon_id: i32,
+        table_id: i32,
+        player_ids: &[i64],
+    ) -> Result<bool> {
+        let first_player = match player_ids.first() {
+            Some(player_id) => *player_id,
+            None => return Ok(false),
+        };
+
+        let filter = doc! {
+            "salon_id": salon_id,
+            "tables": { "$elemMatch": {
+                "table_id": table_id,
+                "players": { "$elemMatch": { "player_id": first_player, "has_paid": false } }
+            }}
+        };
+        let update = doc! { "$set": { "tables.$[t].players.$[p].has_paid": true } };
+        let options = UpdateOptions::builder()
+            .array_filters(vec![
+                doc! { "t.table_id": table_id },
+                doc! { "p.player_id": { "$in": player_ids }, "p.has_paid": false },
+            ])
+            .build();
+
+        let result = self.collection.update_one_with_session(filter, update, options, session).await?;
+        Ok(result.matched_count == 1)
+    }
+
+    // Oyuncuyu (ve istenirse masadaki botları; bot id'leri negatiftir) masadan kaldırır ve masayı beklemeye
+    // alır; böylece başlamak üzere olan geri sayım iptal olur. Oyun başladıysa ya da oyuncu masada değilse `false` döner.
+    pub async fn remove_players_with_session(
+        &self,
+        session: &mut ClientSession,
+        salon_id: i32,
+        table_id: i32,
+        player_id: i64,
+        with_bots: bool,
+    ) -> Result<bool> {
+        let filter = doc! {
+            "salon_id": salon_id,
+            "tables": { "$elemMatch": {
+                "table_id": table_id,
+                "game_state": { "$in": [to_bson(&GameState::Waiting)?, to_bson(&GameState::Ready)?] },
+                "players.player_id": player_id
+            }}
+        };
+        let leaving = if with_bots {
+            doc! { "$or": [{ "player_id": player_id }, { "player_id": { "$lt": 0_i64 } }] }
+        } else {
+            doc! { "player_id": player_id }
+        };
+        let update = doc! {
+            "$pull": { "tables.$.players": leaving },
+            "$set": { "tables.$.game_state": to_bson(&GameState::Waiting)?, "tables.$.fill_with_bots": false }
+        };
+
+        let result = self.collection.update_one_with_session(filter, update, None, session).await?;
+        Ok(result.matched_count == 1)
+    }
 }
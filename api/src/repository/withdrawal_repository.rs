@@ -0,0 +1,86 @@
+// src/repository/withdrawal_repository.rs
+use mongodb::{Client, ClientSession, Collection, IndexModel};
+use mongodb::bson::{doc, to_bson, Document};
+use mongodb::error::Result;
+use mongodb::options::{FindOptions, IndexOptions};
+use futures::stream::TryStreamExt;
+use crate::models::withdrawal::{Withdrawal, WithdrawalEvent, WithdrawalStatus};
+
+pub struct WithdrawalRepository {
+    collection: Collection<Withdrawal>,
+}
+
+impl WithdrawalRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("withdrawals");
+        let collection = db.collection::<Withdrawal>("withdrawals");
+        WithdrawalRepository { collection }
+    }
+
+    pub async fn ensure_indexes(&self) -> Result<()> {
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! { "withdrawal_id": 1 })
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "telegram_id": 1, "created_at": -1 })
+                .build(),
+            IndexModel::builder()
+                .keys(doc! { "status": 1, "created_at": 1 })
+                .build(),
+        ];
+        self.collection.create_indexes(indexes, None).await.map(|_| ())
+    }
+
+    pub async fn insert_with_session(&self, session: &mut ClientSession, withdrawal: &Withdrawal) -> Result<()> {
+        self.collection.insert_one_with_session(withdrawal, None, session).await.map(|_| ())
+    }
+
+    pub async fn find_by_id(&self, withdrawal_id: &str) -> Result<Option<Withdrawal>> {
+        self.collection.find_one(doc! { "withdrawal_id": withdrawal_id }, None).await
+    }
+
+    // Talebi yalnızca hâlâ `from` durumundaysa `event.status`'a taşır ve adımı geçmişe ekler.
+    // Aynı talebi iki yöneticinin aynı anda işlemesini bu karşılaştırma engeller.
+    pub async fn transition_with_session(
+        &self,
+        session: &mut ClientSession,
+        withdrawal_id: &str,
+        from: WithdrawalStatus,
+        event: &WithdrawalEvent,
+        extra: Document,
+    ) -> Result<bool> {
+        let filter = doc! { "withdrawal_id": withdrawal_id, "status": from.as_str() };
+        let mut set = doc! { "status": event.status.as_str(), "updated_at": event.at };
+        set.extend(extra);
+        let update = doc! { "$set": set, "$push": { "history": to_bson(event)? } };
+
+        let result = self.collection.update_one_with_session(filter, update, None, session).await?;
+        Ok(result.modified_count == 1)
+    }
+
+    // Kullanıcının talepleri, yeniden eskiye
+    pub async fn find_for_user(&self, telegram_id: i64, limit: i64) -> Result<Vec<Withdrawal>> {
+        self.find(doc! { "telegram_id": telegram_id }, -1, limit).await
+    }
+
+    // Belirli durumdaki talepler, eskiden yeniye (yönetici kuyruğu)
+    pub async fn find_by_status(&self, status: WithdrawalStatus, limit: i64) -> Result<Vec<Withdrawal>> {
+        self.find(doc! { "status": status.as_str() }, 1, limit).await
+    }
+
+    async fn find(&self, filter: Document, order: i32, limit: i64) -> Result<Vec<Withdrawal>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": order })
+            .limit(limit)
+            .build();
+
+        let mut cursor = self.collection.find(filter, options).await?;
+        let mut withdrawals = Vec::new();
+        while let Some(withdrawal) = cursor.try_next().await? {
+            withdrawals.push(withdrawal);
+        }
+        Ok(withdrawals)
+    }
+}
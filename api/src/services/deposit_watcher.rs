@@ -0,0 +1,468 @@
+// src/services/deposit_watcher.rs
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use mongodb::Client;
+use rocket::http::Status;
+use serde::{Deserialize, Serialize};
+use crate::models::deposit::{telegram_id_from_memo, Deposit, DepositStatus};
+use crate::models::ledger::{user_account, Asset, LedgerTransaction, Reason, SYSTEM_DEPOSITS};
+use crate::models::money::Ton;
+use crate::repository::{deposit_repository::DepositRepository, ledger_repository::LedgerRepository, user_repository::UserRepository};
+use crate::services::transaction_service::{run_transaction, TxError};
+
+// Cüzdanımıza gelen, zincirde görülmüş bir transfer
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IncomingTransfer {
+    pub tx_hash: String,
+    #[serde(default)]
+    pub lt: u64, // İşlemin logical time'ı; cüzdanın işlemleri arasında sıralıdır
+    pub sender: Option<String>,
+    pub amount: Ton,
+    pub memo: Option<String>,
+    pub success: bool,
+    pub utime: i64, // Unix timestamp
+}
+
+// Cüzdanın işlem geçmişinde bir konum; bir sonraki sayfa bu işlemden daha eski işlemlerle başlar
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageCursor {
+    pub lt: u64,
+    pub hash: String,
+}
+
+// Yeniden eskiye bir sayfa transfer; sayfa doluysa `next` daha eski sayfayı gösterir
+#[derive(Debug, Default)]
+pub struct TransferPage {
+    pub transfers: Vec<IncomingTransfer>,
+    pub next: Option<PageCursor>,
+}
+
+// Zincir verisinin kaynağı. Gerçek ağda bir HTTP indexer, yerelde ve testlerde `MockIndexer` kullanılır.
+#[rocket::async_trait]
+pub trait TonIndexer: Send + Sync {
+    // `wallet` adresinin `before`'dan (verilmezse en yeniden) eski en fazla `limit` işlemindeki gelen transferler
+    async fn incoming_transfers(&self, wallet: &str, limit: u32, before: Option<&PageCursor>) -> Result<TransferPage, String>;
+}
+
+// Bellekte tutulan sahte indexer; transferler `push` ile (admin uç noktasından) eklenir
+#[derive(Default)]
+pub struct MockIndexer {
+    transfers: Mutex<Vec<IncomingTransfer>>,
+}
+
+impl MockIndexer {
+    // `lt` verilmemişse transfere bir öncekinden sonraki lt verilir
+    pub fn push(&self, mut transfer: IncomingTransfer) {
+        let mut transfers = self.transfers.lock().expect("mock indexer lock poisoned");
+        if transfer.lt == 0 {
+            transfer.lt = transfers.last().map_or(1, |last| last.lt + 1);
+        }
+        transfers.push(transfer);
+    }
+}
+
+#[rocket::async_trait]
+impl TonIndexer for MockIndexer {
+    async fn incoming_transfers(&self, _wallet: &str, limit: u32, before: Option<&PageCursor>) -> Result<TransferPage, String> {
+        let transfers = self.transfers.lock().map_err(|e| e.to_string())?;
+        let page: Vec<IncomingTransfer> = transfers.iter().rev()
+            .filter(|t| before.is_none_or(|cursor| t.lt < cursor.lt))
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        let next = page.last()
+            .filter(|_| page.len() == limit as usize)
+            .map(|oldest| PageCursor { lt: oldest.lt, hash: oldest.tx_hash.clone() });
+        Ok(TransferPage { transfers: page, next })
+    }
+}
+
+// toncenter API v2 (`getTransactions`) uyumlu indexer
+pub struct TonCenterIndexer {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TonCenterResponse {
+    ok: bool,
+    result: Option<Vec<TonCenterTransaction>>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TonCenterTransaction {
+    utime: i64,
+    transaction_id: TonCenterTransactionId,
+    in_msg: Option<TonCenterMessage>,
+}
+
+#[derive(Deserialize)]
+struct TonCenterTransactionId {
+    lt: String, // ondalık metin
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct TonCenterMessage {
+    #[serde(default)]
+    source: String,
+    value: String, // nanoTON, ondalık metin
+    message: Option<String>,
+}
+
+impl TonCenterIndexer {
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        TonCenterIndexer {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl TonIndexer for TonCenterIndexer {
+    async fn incoming_transfers(&self, wallet: &str, limit: u32, before: Option<&PageCursor>) -> Result<TransferPage, String> {
+        let mut query = vec![("address", wallet.to_string()), ("limit", limit.to_string()), ("archival", "true".to_string())];
+        if let Some(cursor) = before {
+            query.push(("lt", cursor.lt.to_string()));
+            query.push(("hash", cursor.hash.clone()));
+        }
+        let mut request = self.http.get(format!("{}/getTransactions", self.base_url)).query(&query);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("X-API-Key", api_key);
+        }
+
+        let response: TonCenterResponse = request.send().await
+            .map_err(|e| format!("Indexer request failed: {}", e))?
+            .json().await
+            .map_err(|e| format!("Indexer response could not be read: {}", e))?;
+        if !response.ok {
+            return Err(format!("Indexer error: {}", response.error.unwrap_or_default()));
+        }
+
+        // `lt` ve `hash` verilince sayfa o işlemin kendisiyle başlar; onu atlıyoruz
+        let transactions: Vec<(u64, TonCenterTransaction)> = response.result.unwrap_or_default().into_iter()
+            .filter_map(|tx| Some((tx.transaction_id.lt.parse::<u64>().ok()?, tx)))
+            .filter(|(lt, _)| before.is_none_or(|cursor| *lt < cursor.lt))
+            .collect();
+        let next = transactions.last()
+            .filter(|_| transactions.len() + usize::from(before.is_some()) >= limit as usize)
+            .map(|(lt, tx)| PageCursor { lt: *lt, hash: tx.transaction_id.hash.clone() });
+
+        // Harici mesajla başlayan (kaynağı olmayan) işlemler bizim giden işlemlerimizdir
+        let transfers = transactions.into_iter().filter_map(|(lt, tx)| {
+            let in_msg = tx.in_msg.filter(|msg| !msg.source.is_empty())?;
+            let value = in_msg.value.parse::<i64>().ok()?;
+            Some(IncomingTransfer {
+                tx_hash: tx.transaction_id.hash,
+                lt,
+                sender: Some(in_msg.source),
+                amount: Ton::from_nanoton(value),
+                memo: in_msg.message.filter(|m| !m.is_empty()),
+                success: value > 0,
+                utime: tx.utime,
+            })
+        }).collect();
+        Ok(TransferPage { transfers, next })
+    }
+}
+
+// `after_lt`'den sonraki tüm gelen transferler, eskiden yeniye. Indexer en yeniden geriye doğru,
+// `after_lt`'ye ulaşana kadar sayfa sayfa okunur; böylece yoğun bir cüzdanda da transfer kaçmaz.
+pub async fn transfers_after(indexer: &dyn TonIndexer, wallet: &str, batch_size: u32, after_lt: u64) -> Result<Vec<IncomingTransfer>, String> {
+    let mut transfers = vec![];
+    let mut before: Option<PageCursor> = None;
+    loop {
+        let page = indexer.incoming_transfers(wallet, batch_size, before.as_ref()).await?;
+        transfers.extend(page.transfers.into_iter().filter(|t| t.lt > after_lt));
+        match page.next {
+            Some(next) if next.lt > after_lt => before = Some(next),
+            _ => break,
+        }
+    }
+    transfers.reverse();
+    Ok(transfers)
+}
+
+// Transferin sonucu; veritabanına dokunmadan, transferden ve varsa bildirilen kayıttan hesaplanır
+#[derive(Debug, PartialEq)]
+pub enum Judgement {
+    AlreadySettled,
+    Fail(&'static str),
+    // Memo'nun sahibine eklenir; kaydı başka bir kullanıcı bildirdiyse `reported_by` odur
+    Credit { telegram_id: i64, reported_by: Option<i64> },
+}
+
+pub fn judge(transfer: &IncomingTransfer, existing: Option<&Deposit>) -> Judgement {
+    if existing.is_some_and(Deposit::is_settled) {
+        return Judgement::AlreadySettled;
+    }
+    if !transfer.success {
+        return Judgement::Fail("Transaction failed on chain");
+    }
+    if transfer.amount.nanoton() <= 0 {
+        return Judgement::Fail("Transfer carries no TON");
+    }
+    match transfer.memo.as_deref().and_then(telegram_id_from_memo) {
+        None => Judgement::Fail("Memo does not identify a user"),
+        Some(telegram_id) => Judgement::Credit {
+            telegram_id,
+            reported_by: existing.and_then(|d| d.telegram_id).filter(|reporter| *reporter != telegram_id),
+        },
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DepositPollReport {
+    pub confirmed: u64,
+    pub failed: u64,
+    pub expired: u64,
+}
+
+// Cüzdana gelen transferleri izler, memo ile kullanıcıya eşleştirir ve bakiyeye ekler.
+// Her transfer tek bir işlemde sonuçlandırılır; `tx_hash` tekil olduğundan aynı transfer
+// birden fazla kez görülse (ya da birden fazla izleyici çalışsa) bile yalnızca bir kez eklenir.
+pub struct DepositWatcher {
+    indexer: Arc<dyn TonIndexer>,
+    mongo: Client,
+    deposits: DepositRepository,
+    users: UserRepository,
+    ledger: LedgerRepository,
+    wallet_address: String,
+    batch_size: u32,
+    pending_timeout_secs: i64,
+}
+
+impl DepositWatcher {
+    pub fn new(indexer: Arc<dyn TonIndexer>, mongo: &Client, wallet_address: &str, batch_size: u32, pending_timeout_secs: i64) -> Self {
+        DepositWatcher {
+            indexer,
+            mongo: mongo.clone(),
+            deposits: DepositRepository::new(mongo),
+            users: UserRepository::new(mongo),
+            ledger: LedgerRepository::new(mongo),
+            wallet_address: wallet_address.to_string(),
+            batch_size,
+            pending_timeout_secs,
+        }
+    }
+
+    // İzleyiciyi arka planda `interval` aralıklarla çalıştırır
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.poll_once().await {
+                    Ok(report) if report.confirmed + report.failed + report.expired > 0 => println!(
+                        "Deposits: {} confirmed, {} failed, {} expired",
+                        report.confirmed, report.failed, report.expired
+                    ),
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Deposit watcher: {}", e),
+                }
+            }
+        });
+    }
+
+    pub async fn poll_once(&self) -> Result<DepositPollReport, String> {
+        let mut report = DepositPollReport::default();
+        let last_seen = self.deposits.last_seen_lt(&self.wallet_address).await
+            .map_err(|e| format!("Deposit cursor could not be read: {:?}", e))?;
+        let transfers = transfers_after(self.indexer.as_ref(), &self.wallet_address, self.batch_size, last_seen).await?;
+
+        // İmleç yalnızca sonuçlandırılan transferlerin üzerinden ilerler; veritabanı hatasında
+        // kalan transferler bir sonraki turda yeniden okunur
+        let mut seen = last_seen;
+        for transfer in &transfers {
+            let settled = match self.deposits.find_by_tx_hash(&transfer.tx_hash).await {
+                Ok(existing) => existing.is_some_and(|deposit| deposit.is_settled()),
+                Err(e) => {
+                    eprintln!("Deposit {} lookup failed: {:?}", transfer.tx_hash, e);
+                    break;
+                }
+            };
+            if !settled {
+                match self.settle(transfer).await {
+                    Ok(Some(DepositStatus::Confirmed)) => report.confirmed += 1,
+                    Ok(Some(DepositStatus::Failed)) => report.failed += 1,
+                    Ok(_) => {}
+                    Err(TxError::Rejected(_, message)) => eprintln!("Deposit {} rejected: {}", transfer.tx_hash, message),
+                    Err(TxError::Database(e)) => {
+                        eprintln!("Deposit {} could not be settled: {:?}", transfer.tx_hash, e);
+                        break;
+                    }
+                }
+            }
+            seen = transfer.lt;
+        }
+        if seen > last_seen {
+            self.deposits.save_last_seen_lt(&self.wallet_address, seen).await
+                .map_err(|e| format!("Deposit cursor could not be saved: {:?}", e))?;
+        }
+
+        let cutoff = unix_now() - self.pending_timeout_secs;
+        report.expired = self.deposits.expire_stale_pending(cutoff, "Transaction was not observed on chain in time").await
+            .map_err(|e| format!("Expiring pending deposits failed: {:?}", e))?;
+        Ok(report)
+    }
+
+    // Transferi onaylar (memo'nun sahibinin bakiyesine ekler) ya da başarısız olarak kaydeder;
+    // zaten sonuçlanmışsa None
+    async fn settle(&self, transfer: &IncomingTransfer) -> Result<Option<DepositStatus>, TxError> {
+        let (deposits, users, ledger) = (&self.deposits, &self.users, &self.ledger);
+
+        run_transaction(&self.mongo, |mut session| async move {
+            let result: Result<Option<DepositStatus>, TxError> = async {
+                let existing = deposits.find_by_tx_hash_with_session(&mut session, &transfer.tx_hash).await?;
+                let reporter = existing.as_ref().and_then(|d| d.telegram_id);
+
+                let (owner, failure) = match judge(transfer, existing.as_ref()) {
+                    Judgement::AlreadySettled => return Ok(None),
+                    Judgement::Fail(reason) => (None, Some(reason)),
+                    Judgement::Credit { telegram_id, reported_by } => {
+                        // Hash'ler zincirde herkese açık; başkasının transferini bildiren kullanıcı sonucu değiştiremez
+                        if let Some(reporter) = reported_by {
+                            eprintln!("Deposit {} was reported by user {} but its memo belongs to user {}", transfer.tx_hash, reporter, telegram_id);
+                        }
+                        if users.find_user_with_session(&mut session, telegram_id).await?.is_none() {
+                            (None, Some("User not found"))
+                        } else {
+                            (Some(telegram_id), None)
+                        }
+                    }
+                };
+
+                let now = unix_now();
+                let status = if failure.is_some() { DepositStatus::Failed } else { DepositStatus::Confirmed };
+                let deposit = Deposit {
+                    tx_hash: transfer.tx_hash.clone(),
+                    telegram_id: owner.or(transfer.memo.as_deref().and_then(telegram_id_from_memo)).or(reporter),
+                    amount: Some(transfer.amount),
+                    memo: transfer.memo.clone(),
+                    sender: transfer.sender.clone(),
+                    status,
+                    failure_reason: failure.map(str::to_string),
+                    created_at: existing.as_ref().map_or(now, |d| d.created_at),
+                    confirmed_at: (status == DepositStatus::Confirmed).then_some(now),
+                };
+
+                if existing.is_some() {
+                    if !deposits.settle_open_with_session(&mut session, &deposit).await? {
+                        return Ok(None);
+                    }
+                } else {
+                    deposits.insert_with_session(&mut session, &deposit).await?;
+                }
+
+                if let Some(telegram_id) = owner {
+                    if !users.credit_with_session(&mut session, telegram_id, Asset::Ton, transfer.amount.nanoton()).await? {
+                        return Err(TxError::rejected(Status::NotFound, "User not found"));
+                    }
+                    ledger.record_with_session(&mut session, LedgerTransaction::transfer(
+                        Reason::Deposit,
+                        Asset::Ton,
+                        SYSTEM_DEPOSITS.to_string(),
+                        user_account(telegram_id),
+                        transfer.amount.nanoton(),
+                        Some(format!("tx:{}", transfer.tx_hash)),
+                    )).await?;
+                }
+                Ok(Some(status))
+            }.await;
+            (session, result)
+        }).await
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::deposit::deposit_memo;
+
+    fn transfer(tx_hash: &str, memo: Option<String>) -> IncomingTransfer {
+        IncomingTransfer {
+            tx_hash: tx_hash.to_string(),
+            lt: 0,
+            sender: None,
+            amount: Ton::from_ton(1),
+            memo,
+            success: true,
+            utime: 0,
+        }
+    }
+
+    fn deposit(tx_hash: &str, reporter: i64, status: DepositStatus) -> Deposit {
+        Deposit {
+            tx_hash: tx_hash.to_string(),
+            telegram_id: Some(reporter),
+            amount: None,
+            memo: None,
+            sender: None,
+            status,
+            failure_reason: None,
+            created_at: 0,
+            confirmed_at: None,
+        }
+    }
+
+    #[test]
+    fn transfer_is_credited_once_per_tx_hash() {
+        let incoming = transfer("tx", Some(deposit_memo(7)));
+        assert_eq!(judge(&incoming, None), Judgement::Credit { telegram_id: 7, reported_by: None });
+        for status in [DepositStatus::Confirmed, DepositStatus::Failed] {
+            assert_eq!(judge(&incoming, Some(&deposit("tx", 7, status))), Judgement::AlreadySettled);
+        }
+    }
+
+    #[test]
+    fn memo_owner_is_credited_even_if_another_user_reported_it() {
+        let incoming = transfer("tx", Some(deposit_memo(7)));
+        let reported = deposit("tx", 9, DepositStatus::Pending);
+        assert_eq!(judge(&incoming, Some(&reported)), Judgement::Credit { telegram_id: 7, reported_by: Some(9) });
+    }
+
+    #[test]
+    fn failed_or_unattributable_transfers_are_not_credited() {
+        let failed = IncomingTransfer { success: false, ..transfer("tx", Some(deposit_memo(7))) };
+        assert_eq!(judge(&failed, None), Judgement::Fail("Transaction failed on chain"));
+
+        let empty = IncomingTransfer { amount: Ton::ZERO, ..transfer("tx", Some(deposit_memo(7))) };
+        assert_eq!(judge(&empty, None), Judgement::Fail("Transfer carries no TON"));
+
+        for memo in [None, Some("hello".to_string()), Some("deposit:-5".to_string())] {
+            assert_eq!(judge(&transfer("tx", memo), None), Judgement::Fail("Memo does not identify a user"));
+        }
+    }
+
+    #[test]
+    fn expired_deposit_is_still_confirmed_when_its_transfer_arrives() {
+        let expired = deposit("tx", 7, DepositStatus::Expired);
+        assert!(!expired.is_settled());
+        let incoming = transfer("tx", Some(deposit_memo(7)));
+        assert_eq!(judge(&incoming, Some(&expired)), Judgement::Credit { telegram_id: 7, reported_by: None });
+    }
+
+    #[rocket::async_test]
+    async fn transfers_after_pages_back_to_the_saved_lt() {
+        let indexer = MockIndexer::default();
+        for index in 1..=7 {
+            indexer.push(transfer(&format!("tx{}", index), None));
+        }
+
+        let lts = |transfers: Vec<IncomingTransfer>| transfers.into_iter().map(|t| t.lt).collect::<Vec<_>>();
+        assert_eq!(lts(transfers_after(&indexer, "wallet", 3, 2).await.unwrap()), vec![3, 4, 5, 6, 7]);
+        assert_eq!(lts(transfers_after(&indexer, "wallet", 3, 0).await.unwrap()), (1..=7).collect::<Vec<_>>());
+        assert!(transfers_after(&indexer, "wallet", 3, 7).await.unwrap().is_empty());
+    }
+}
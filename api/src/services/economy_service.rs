@@ -0,0 +1,105 @@
+// src/services/economy_service.rs
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use mongodb::Client;
+use rocket::http::Status;
+use crate::models::economy::{EconomyConfig, EconomyRules};
+use crate::repository::economy_repository::EconomyRepository;
+use crate::services::transaction_service::{run_transaction, TxError};
+
+// Yürürlükteki ekonominin bellekteki kopyası. Handler'lar her istekte `current()` ile okur;
+// kopya belirli aralıklarla Mongo'dan yenilenir, böylece yönetici değişiklikleri (ve başka
+// API örneklerinin değişiklikleri) yeniden başlatmadan geçerli olur.
+pub struct EconomyService {
+    mongo: Client,
+    repository: EconomyRepository,
+    current: RwLock<Arc<EconomyConfig>>,
+}
+
+impl EconomyService {
+    // Ekonomi belgesi yoksa başlangıç değerleriyle oluşturur ve yükler
+    pub async fn load(mongo: &Client) -> mongodb::error::Result<Self> {
+        let repository = EconomyRepository::new(mongo);
+        repository.ensure_indexes().await?;
+        let initial = EconomyConfig {
+            version: 1,
+            rules: EconomyRules::default(),
+            updated_at: unix_now(),
+            updated_by: None,
+            note: Some("initial".to_string()),
+        };
+        if repository.seed(&initial).await? {
+            println!("Economy config initialised with default rates");
+        }
+        let current = repository.load().await?.unwrap_or(initial);
+
+        Ok(EconomyService {
+            mongo: mongo.clone(),
+            repository,
+            current: RwLock::new(Arc::new(current)),
+        })
+    }
+
+    pub fn repository(&self) -> &EconomyRepository {
+        &self.repository
+    }
+
+    pub fn current(&self) -> Arc<EconomyConfig> {
+        self.current.read().expect("economy lock poisoned").clone()
+    }
+
+    // Mongo'daki sürüm daha yeniyse bellekteki kopyayı değiştirir
+    pub async fn reload(&self) -> mongodb::error::Result<()> {
+        if let Some(latest) = self.repository.load().await? {
+            if latest.version > self.current().version {
+                println!("Economy config reloaded (version {})", latest.version);
+                *self.current.write().expect("economy lock poisoned") = Arc::new(latest);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn spawn_reloader(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reload().await {
+                    eprintln!("Economy config reload failed: {:?}", e);
+                }
+            }
+        });
+    }
+
+    // Yeni oranları `expected_version` sürümünün üzerine yazar; arada başka bir değişiklik olduysa 409
+    pub async fn update(&self, rules: EconomyRules, expected_version: i64, admin_id: i64, note: Option<String>) -> Result<EconomyConfig, TxError> {
+        rules.validate().map_err(|e| TxError::Rejected(Status::BadRequest, format!("400: Bad Request - {}", e)))?;
+
+        let next = EconomyConfig {
+            version: expected_version + 1,
+            rules,
+            updated_at: unix_now(),
+            updated_by: Some(format!("admin:{}", admin_id)),
+            note,
+        };
+        let (repository, next_ref) = (&self.repository, &next);
+        run_transaction(&self.mongo, |mut session| async move {
+            let result = match repository.replace_with_session(&mut session, expected_version, next_ref).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(TxError::rejected(Status::Conflict, "409: Conflict - Economy config was changed by another request; reload and retry")),
+                Err(e) => Err(TxError::Database(e)),
+            };
+            (session, result)
+        }).await?;
+
+        *self.current.write().expect("economy lock poisoned") = Arc::new(next.clone());
+        Ok(next)
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64
+}
@@ -0,0 +1,284 @@
+// src/services/idempotency.rs
+use std::future::Future;
+use redis::{RedisResult, aio::MultiplexedConnection};
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::time::{interval_at, Duration, Instant};
+use crate::jwt::auth_guard::AuthenticatedUser;
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+const MAX_KEY_LENGTH: usize = 255;
+// İlk istek sürerken anahtara yazılan işaret (`pending:<istek özeti>`); tamamlanınca saklanan yanıtla
+// değiştirilir. Handler çalıştıkça işaretin süresi `PENDING_REFRESH_SECS`'te bir uzatılır; süreç ölürse
+// işaret kısa sürede düşer ve yarıda kalan istek anahtarı tüm TTL boyunca kilitlemez.
+const PENDING_MARKER: &str = "pending";
+const PENDING_TTL_SECS: u64 = 60;
+const PENDING_REFRESH_SECS: u64 = PENDING_TTL_SECS / 3;
+
+// İşlenmiş `Idempotency-Key` anahtarlarını ve yanıtlarını Redis'te TTL ile saklar
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    connection: MultiplexedConnection,
+    ttl_secs: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredResponse {
+    status: u16,
+    body: String,
+    #[serde(default)]
+    request_hash: Option<String>, // Yanıtı üreten isteğin gövde özeti; eski kayıtlarda yoktur
+}
+
+enum Reservation {
+    Acquired,
+    InProgress(Option<String>), // İşlemdeki isteğin gövde özeti
+    Completed(StoredResponse),
+}
+
+// İstek gövdesinin SHA-256 özeti (hex). Gövde ayrıştırılmış haliyle özetlenir, boşluk ve alan sırası farkı sayılmaz.
+fn request_hash<T: Serialize>(body: &T) -> String {
+    let json = serde_json::to_vec(body).unwrap_or_default();
+    Sha256::digest(&json).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn pending_marker(request_hash: Option<&str>) -> String {
+    match request_hash {
+        Some(hash) => format!("{}:{}", PENDING_MARKER, hash),
+        None => PENDING_MARKER.to_string(),
+    }
+}
+
+// Anahtarda bulunan değeri yorumlar
+fn parse_existing(value: &str) -> Reservation {
+    if value == PENDING_MARKER {
+        return Reservation::InProgress(None);
+    }
+    if let Some(hash) = value.strip_prefix(PENDING_MARKER).and_then(|rest| rest.strip_prefix(':')) {
+        return Reservation::InProgress(Some(hash.to_string()));
+    }
+    // Okunamayan kayıt yeniden çalıştırmaya izin vermez, işlemde sayılır
+    serde_json::from_str(value).map(Reservation::Completed).unwrap_or(Reservation::InProgress(None))
+}
+
+// Anahtar başka bir gövdeyle mi kullanılmış? Özeti bilinmeyen taraf (eski kayıt, gövdesiz istek) karşılaştırılmaz.
+fn is_different_request(recorded: Option<&str>, current: Option<&str>) -> bool {
+    matches!((recorded, current), (Some(recorded), Some(current)) if recorded != current)
+}
+
+impl IdempotencyStore {
+    pub fn new(connection: MultiplexedConnection, ttl_secs: u64) -> Self {
+        IdempotencyStore { connection, ttl_secs }
+    }
+
+    // Anahtarı SET NX ile ayırır; anahtar zaten varsa durumunu döner
+    async fn reserve(&self, key: &str, request_hash: Option<&str>) -> RedisResult<Reservation> {
+        let mut connection = self.connection.clone();
+        // Anahtarın GET ile SET NX arasında süresi dolmuş olabilir; bir kez daha denenir
+        for _ in 0..2 {
+            let acquired: Option<String> = redis::cmd("SET")
+                .arg(key).arg(pending_marker(request_hash)).arg("NX").arg("EX").arg(PENDING_TTL_SECS.min(self.ttl_secs))
+                .query_async(&mut connection).await?;
+            if acquired.is_some() {
+                return Ok(Reservation::Acquired);
+            }
+
+            let existing: Option<String> = redis::cmd("GET").arg(key).query_async(&mut connection).await?;
+            match existing.as_deref() {
+                None => continue,
+                Some(value) => return Ok(parse_existing(value)),
+            }
+        }
+        Ok(Reservation::InProgress(None))
+    }
+
+    // Handler sürerken işaretin süresini uzatır
+    async fn refresh(&self, key: &str) -> RedisResult<()> {
+        let mut connection = self.connection.clone();
+        redis::cmd("EXPIRE").arg(key).arg(PENDING_TTL_SECS.min(self.ttl_secs))
+            .query_async(&mut connection).await
+    }
+
+    async fn complete(&self, key: &str, response: &StoredResponse) -> RedisResult<()> {
+        let mut connection = self.connection.clone();
+        let value = serde_json::to_string(response).unwrap_or_default();
+        redis::cmd("SET").arg(key).arg(value).arg("EX").arg(self.ttl_secs)
+            .query_async(&mut connection).await
+    }
+
+    async fn release(&self, key: &str) -> RedisResult<()> {
+        let mut connection = self.connection.clone();
+        redis::cmd("DEL").arg(key).query_async(&mut connection).await
+    }
+}
+
+// Saklanıp aynen tekrar oynatılabilen handler yanıtları
+pub trait ReplayableResponse: Sized {
+    fn status(&self) -> Status;
+    fn body_to_string(&self) -> Option<String>;
+    fn from_stored(status: Status, body: &str) -> Option<Self>;
+    fn error(status: Status, message: &str) -> Self;
+}
+
+// İsteğin `Idempotency-Key` başlığı. Başlık yoksa istek olduğu gibi çalıştırılır.
+// Anahtar kullanıcıya ve yola göre ayrılır; aynı anahtar başka bir endpoint'te yeniden kullanılabilir.
+// Gövdeli endpoint'ler `with_body` ile gövdeyi bildirir; anahtar farklı bir gövdeyle tekrar gelirse 422 döner.
+pub struct Idempotency {
+    scoped: Option<(IdempotencyStore, String)>,
+    request_hash: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Idempotency {
+    type Error = String;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let key = match request.headers().get_one(IDEMPOTENCY_HEADER) {
+            Some(key) => key.trim(),
+            None => return Outcome::Success(Idempotency { scoped: None, request_hash: None }),
+        };
+        if key.is_empty() || key.len() > MAX_KEY_LENGTH || !key.chars().all(|c| c.is_ascii_graphic()) {
+            return Outcome::Error((Status::BadRequest, format!("Invalid {} header", IDEMPOTENCY_HEADER)));
+        }
+
+        let user = match request.guard::<AuthenticatedUser>().await {
+            Outcome::Success(user) => user,
+            Outcome::Error(e) => return Outcome::Error(e),
+            Outcome::Forward(status) => return Outcome::Forward(status),
+        };
+        let store = match request.rocket().state::<IdempotencyStore>() {
+            Some(store) => store.clone(),
+            None => return Outcome::Error((Status::InternalServerError, "Idempotency store is not loaded".to_string())),
+        };
+
+        let scoped_key = format!("idempotency:{}:{}:{}", user.telegram_id, request.uri().path(), key);
+        Outcome::Success(Idempotency { scoped: Some((store, scoped_key)), request_hash: None })
+    }
+}
+
+impl Idempotency {
+    // Anahtarı isteğin gövdesine bağlar
+    pub fn with_body<T: Serialize>(mut self, body: &T) -> Self {
+        if self.scoped.is_some() {
+            self.request_hash = Some(request_hash(body));
+        }
+        self
+    }
+
+    // `handler`'ı anahtar başına en fazla bir kez çalıştırır; tekrar eden istekler saklanan yanıtı alır.
+    // 5xx yanıtlar saklanmaz (işlem geri alınmıştır), anahtar serbest bırakılır ve istek tekrar denenebilir.
+    pub async fn run<R, Fut>(self, handler: Fut) -> R
+    where
+        R: ReplayableResponse,
+        Fut: Future<Output = R>,
+    {
+        let (store, key) = match self.scoped {
+            Some(scoped) => scoped,
+            None => return handler.await,
+        };
+
+        let request_hash = self.request_hash;
+        match store.reserve(&key, request_hash.as_deref()).await {
+            Ok(Reservation::Acquired) => {}
+            Ok(Reservation::InProgress(recorded)) | Ok(Reservation::Completed(StoredResponse { request_hash: recorded, .. }))
+                if is_different_request(recorded.as_deref(), request_hash.as_deref()) =>
+            {
+                return R::error(Status::UnprocessableEntity, "422: Unprocessable Entity - Idempotency-Key was already used with a different request body");
+            }
+            Ok(Reservation::InProgress(_)) => {
+                return R::error(Status::Conflict, "409: Conflict - A request with this Idempotency-Key is still being processed");
+            }
+            Ok(Reservation::Completed(stored)) => {
+                let status = Status::from_code(stored.status).unwrap_or(Status::Ok);
+                return R::from_stored(status, &stored.body).unwrap_or_else(|| {
+                    R::error(Status::InternalServerError, "500: Internal Server Error - Stored response could not be read")
+                });
+            }
+            Err(e) => {
+                eprintln!("Idempotency store unavailable: {:?}", e);
+                return R::error(Status::ServiceUnavailable, "503: Service Unavailable - Please retry later");
+            }
+        }
+
+        let response = {
+            let refresh_every = Duration::from_secs(PENDING_REFRESH_SECS);
+            let mut heartbeat = interval_at(Instant::now() + refresh_every, refresh_every);
+            tokio::pin!(handler);
+            loop {
+                tokio::select! {
+                    response = &mut handler => break response,
+                    _ = heartbeat.tick() => {
+                        if let Err(e) = store.refresh(&key).await {
+                            eprintln!("Failed to refresh idempotency marker for {}: {:?}", key, e);
+                        }
+                    }
+                }
+            }
+        };
+        let stored = match response.body_to_string() {
+            Some(body) if response.status().code < 500 => Some(StoredResponse { status: response.status().code, body, request_hash }),
+            _ => None,
+        };
+        let saved = match stored {
+            Some(stored) => store.complete(&key, &stored).await,
+            None => store.release(&key).await,
+        };
+        if let Err(e) = saved {
+            eprintln!("Failed to store idempotent response for {}: {:?}", key, e);
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Body {
+        amount: i64,
+        currency: &'static str,
+    }
+
+    #[test]
+    fn request_hash_depends_only_on_the_body() {
+        let hash = request_hash(&Body { amount: 5, currency: "ton" });
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, request_hash(&Body { amount: 5, currency: "ton" }));
+        assert_ne!(hash, request_hash(&Body { amount: 6, currency: "ton" }));
+    }
+
+    #[test]
+    fn pending_marker_carries_the_request_hash() {
+        match parse_existing(&pending_marker(Some("abc"))) {
+            Reservation::InProgress(Some(hash)) => assert_eq!(hash, "abc"),
+            _ => panic!("expected pending marker with hash"),
+        }
+        assert!(matches!(parse_existing(PENDING_MARKER), Reservation::InProgress(None)));
+        assert!(matches!(parse_existing("not json"), Reservation::InProgress(None)));
+    }
+
+    #[test]
+    fn stored_response_keeps_the_request_hash() {
+        let stored = StoredResponse { status: 200, body: "{}".to_string(), request_hash: Some("abc".to_string()) };
+        match parse_existing(&serde_json::to_string(&stored).unwrap()) {
+            Reservation::Completed(stored) => assert_eq!(stored.request_hash.as_deref(), Some("abc")),
+            _ => panic!("expected stored response"),
+        }
+        // Gövde özeti eklenmeden önce saklanan yanıtlar da okunur
+        match parse_existing(r#"{"status":201,"body":"ok"}"#) {
+            Reservation::Completed(stored) => assert_eq!((stored.status, stored.request_hash), (201, None)),
+            _ => panic!("expected legacy stored response"),
+        }
+    }
+
+    #[test]
+    fn only_known_differing_hashes_are_rejected() {
+        assert!(is_different_request(Some("a"), Some("b")));
+        assert!(!is_different_request(Some("a"), Some("a")));
+        assert!(!is_different_request(None, Some("a")));
+        assert!(!is_different_request(Some("a"), None));
+    }
+}
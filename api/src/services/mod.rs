@@ -1,2 +1,9 @@
 pub mod redis_service; // Redis servisini modül olarak ekle
 pub mod telegram_auth_fairing;
+pub mod provably_fair;
+pub mod transaction_service;
+pub mod idempotency;
+pub mod deposit_watcher;
+pub mod ton_transfer;
+pub mod withdrawal_service;
+pub mod economy_service;
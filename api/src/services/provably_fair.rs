@@ -0,0 +1,167 @@
+use hmac::{Hmac, Mac};
+use mongodb::bson::Bson;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::models::game::FairnessRecord;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Sunucunun (server crate) zar türetimiyle birebir aynı olmalı:
+// digest = HMAC-SHA256(server_seed, "{client_seed}:{player_id}:{nonce}"),
+// 252'den küçük ilk byte için zar = byte % 6 + 1
+pub fn derive_roll(server_seed: &str, client_seed: &str, player_id: &str, nonce: usize) -> i32 {
+    let mut mac = HmacSha256::new_from_slice(server_seed.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{}:{}:{}", client_seed, player_id, nonce).as_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let byte = digest.iter().copied().find(|b| *b < 252).unwrap_or(digest[0]);
+    (byte % 6) as i32 + 1
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlayerVerification {
+    pub player_id: String,
+    pub client_seed: String,
+    pub recorded_rolls: Vec<i32>,
+    pub computed_rolls: Vec<i32>,
+    pub recorded_tiebreak_rolls: Vec<i32>,
+    pub computed_tiebreak_rolls: Vec<i32>,
+    pub valid: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GameVerification {
+    pub game_id: String,
+    pub server_seed: String,
+    pub server_seed_hash: String,
+    pub commitment_valid: bool, // sha256(server_seed) == server_seed_hash
+    pub players: Vec<PlayerVerification>,
+    pub valid: bool,
+}
+
+// Kaydedilmiş oyunun zarlarını açıklanan seed'lerden yeniden hesaplar.
+// Oyunda seed bilgisi yoksa (eski kayıtlar) `None` döner.
+pub fn verify_game(record: &FairnessRecord) -> Option<GameVerification> {
+    let fairness = record.fairness.as_ref()?;
+
+    let computed_hash: String = Sha256::digest(fairness.server_seed.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let commitment_valid = computed_hash == fairness.server_seed_hash;
+
+    let players: Vec<PlayerVerification> = record.players.iter().map(|player| {
+        let player_id = match &player.player_id {
+            Bson::String(id) => id.clone(),
+            Bson::Int64(id) => id.to_string(),
+            Bson::Int32(id) => id.to_string(),
+            other => other.to_string(),
+        };
+        let client_seed = fairness.client_seeds.get(&player_id).cloned().unwrap_or_default();
+
+        // Bağlantısı kopan oyuncuların 0 ile doldurulan zarları türetilmiş sayılmaz
+        let compute = |nonce: usize, roll: i32| if roll == 0 { 0 } else { derive_roll(&fairness.server_seed, &client_seed, &player_id, nonce) };
+        let computed_rolls: Vec<i32> = player.rolls.iter().enumerate().map(|(nonce, roll)| compute(nonce, *roll)).collect();
+        // Roll-off zarlarının nonce'ları oyunun zarlarının ardından gelir
+        let computed_tiebreak_rolls: Vec<i32> = player.tiebreak_rolls.iter().enumerate()
+            .map(|(i, roll)| compute(player.rolls.len() + i, *roll))
+            .collect();
+        let valid = computed_rolls == player.rolls && computed_tiebreak_rolls == player.tiebreak_rolls;
+
+        PlayerVerification {
+            player_id,
+            client_seed,
+            recorded_rolls: player.rolls.clone(),
+            computed_rolls,
+            recorded_tiebreak_rolls: player.tiebreak_rolls.clone(),
+            computed_tiebreak_rolls,
+            valid,
+        }
+    }).collect();
+
+    let valid = commitment_valid && players.iter().all(|p| p.valid);
+
+    Some(GameVerification {
+        game_id: record.game_id.clone(),
+        server_seed: fairness.server_seed.clone(),
+        server_seed_hash: fairness.server_seed_hash.clone(),
+        commitment_valid,
+        players,
+        valid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::game::{Fairness, RollRecord};
+
+    // Bilinen cevaplar; server/src/services/provably_fair.rs ve api/src/services/provably_fair.rs
+    // aynı vektörleri kullanır. Değerler türetimden bağımsız (Python hmac/hashlib) hesaplanmıştır.
+    #[test]
+    fn derive_roll_matches_known_answers() {
+        let rolls: Vec<i32> = (0..10).map(|nonce| derive_roll("server-seed", "client-seed", "42", nonce)).collect();
+        assert_eq!(rolls, vec![4, 5, 6, 2, 2, 5, 4, 6, 3, 1]);
+
+        let without_client_seed: Vec<i32> = (0..5).map(|nonce| derive_roll("server-seed", "", "42", nonce)).collect();
+        assert_eq!(without_client_seed, vec![6, 4, 5, 6, 4]);
+    }
+
+    // Digest'in ilk byte'ı 254'tür (>= 252) ve atlanır; zar sonraki byte'tan (102) gelir
+    #[test]
+    fn derive_roll_skips_biased_bytes() {
+        assert_eq!(derive_roll("server-seed", "client-seed", "42", 52), 1);
+    }
+
+    fn rolls(client_seed: &str, player_id: &str, nonces: std::ops::Range<usize>) -> Vec<i32> {
+        nonces.map(|nonce| derive_roll("server-seed", client_seed, player_id, nonce)).collect()
+    }
+
+    // İki oyuncu zar toplamında eşit kalır ve birer roll-off zarı atar
+    fn tied_game() -> FairnessRecord {
+        let first = rolls("", "42", 0..5);
+        let total: i32 = first.iter().sum();
+        let client_seed = (0..)
+            .map(|i| format!("seed-{}", i))
+            .find(|seed| rolls(seed, "7", 0..5).iter().sum::<i32>() == total)
+            .unwrap();
+
+        let players = vec![
+            RollRecord { player_id: Bson::Int64(42), rolls: first, tiebreak_rolls: rolls("", "42", 5..6) },
+            RollRecord { player_id: Bson::Int64(7), rolls: rolls(&client_seed, "7", 0..5), tiebreak_rolls: rolls(&client_seed, "7", 5..6) },
+        ];
+        FairnessRecord {
+            game_id: "game".to_string(),
+            players,
+            fairness: Some(Fairness {
+                server_seed: "server-seed".to_string(),
+                server_seed_hash: "91024ec49c5bec0b689e42892526320fce08337205c91de94c7a588c20d08eeb".to_string(),
+                client_seeds: [("7".to_string(), client_seed)].into_iter().collect(),
+            }),
+        }
+    }
+
+    #[test]
+    fn roll_off_rolls_are_verified() {
+        let verification = verify_game(&tied_game()).unwrap();
+        assert!(verification.commitment_valid);
+        assert!(verification.valid);
+        assert_eq!(verification.players[0].computed_tiebreak_rolls, verification.players[0].recorded_tiebreak_rolls);
+
+        let mut tampered = tied_game();
+        let roll = &mut tampered.players[1].tiebreak_rolls[0];
+        *roll = *roll % 6 + 1;
+        let verification = verify_game(&tampered).unwrap();
+        assert!(!verification.valid);
+        assert!(verification.players[0].valid);
+        assert!(!verification.players[1].valid);
+    }
+
+    #[test]
+    fn forfeited_roll_off_roll_counts_as_zero() {
+        let mut game = tied_game();
+        game.players[1].tiebreak_rolls[0] = 0;
+        assert!(verify_game(&game).unwrap().valid);
+    }
+}
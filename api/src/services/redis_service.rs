@@ -1,12 +1,13 @@
-use redis::{AsyncCommands, Client, aio::Connection};
+use redis::{Client, aio::MultiplexedConnection};
 
+// Çoklanmış (multiplexed) bağlantı ucuzca klonlanır; istekler aynı bağlantıyı paylaşır
 pub struct RedisConnection {
-    pub connection: Connection,
+    pub connection: MultiplexedConnection,
 }
 
-pub async fn setup_redis() -> Result<RedisConnection, redis::RedisError> {
-    let client = Client::open("redis://127.0.0.1/")?;
-    let connection = client.get_async_connection().await?;
+pub async fn setup_redis(redis_url: &str) -> Result<RedisConnection, redis::RedisError> {
+    let client = Client::open(redis_url)?;
+    let connection = client.get_multiplexed_tokio_connection().await?;
 
     // Redis'e başarılı bir şekilde bağlandığında log yazdırıyoruz
     println!("Connected to Redis successfully!");
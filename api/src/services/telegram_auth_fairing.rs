@@ -1,29 +1,188 @@
+use std::fmt;
 use std::io::Cursor;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Deserialize;
 use serde_json::json;
-use rocket::{fairing::{Fairing, Info, Kind}, Request, Data, Response};
-use rocket::http::Status;
+use rocket::{fairing::{self, Fairing, Info, Kind}, Build, Rocket, Request, Data, Response};
+use rocket::http::{RawStr, Status};
 use hmac::{Hmac, Mac};
-use sha2::{Sha256, Digest};
+use sha2::Sha256;
+use crate::models::user::User;
 
 type HmacSha256 = Hmac<Sha256>;
 
-pub struct TelegramAuthFairing {
-    pub bot_token: String,
+// Sunucu ile Telegram arasındaki saat farkı için `auth_date`'in ileri olabileceği en fazla süre
+const MAX_AUTH_DATE_SKEW_SECS: i64 = 60;
+
+// initData içindeki `user` alanı
+#[derive(Debug, Clone, Deserialize)]
+pub struct TelegramWebAppUser {
+    pub id: i64,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub username: Option<String>,
+    pub photo_url: Option<String>,
+    pub language_code: Option<String>,
 }
 
-impl TelegramAuthFairing {
-    pub fn new(bot_token: &str) -> Self {
-        TelegramAuthFairing {
+impl TelegramWebAppUser {
+    // Telegram verisinden yeni bir `User` oluşturur; bakiye alanları repository'de varsayılanlanır
+    pub fn into_user(self) -> User {
+        User {
+            telegram_id: self.id,
+            first_name: self.first_name,
+            last_name: self.last_name,
+            username: self.username,
+            photo_url: self.photo_url,
+            language_code: self.language_code,
+            hp: None,
+            ton_amount: None,
+            wallet_address: None,
+            click_score: None,
+            click_power: None,
+            boost: None,
+            references: None,
+            game_pass: None,
+            reputation_points: None,
+            items: None,
+            friends: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidatedInitData {
+    pub user: TelegramWebAppUser,
+    pub auth_date: i64,
+    pub start_param: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitDataError {
+    Malformed,
+    MissingHash,
+    InvalidSignature,
+    MissingAuthDate,
+    Expired,
+    FromFuture,
+    MissingUser,
+    InvalidUser,
+}
+
+impl fmt::Display for InitDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            InitDataError::Malformed => "initData could not be parsed",
+            InitDataError::MissingHash => "initData has no hash",
+            InitDataError::InvalidSignature => "initData signature is invalid",
+            InitDataError::MissingAuthDate => "initData has no valid auth_date",
+            InitDataError::Expired => "initData is too old",
+            InitDataError::FromFuture => "initData auth_date is in the future",
+            InitDataError::MissingUser => "initData has no user",
+            InitDataError::InvalidUser => "initData user could not be parsed",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+// Telegram WebApp initData doğrulayıcısı.
+// https://core.telegram.org/bots/webapps#validating-data-received-via-the-mini-app
+//   secret_key = HMAC_SHA256(key = "WebAppData", msg = bot_token)
+//   hash       = hex(HMAC_SHA256(key = secret_key, msg = data_check_string))
+// data_check_string: `hash` hariç tüm alanlar, anahtara göre sıralı, "key=value" ve '\n' ile birleştirilmiş.
+#[derive(Clone)]
+pub struct TelegramInitDataValidator {
+    bot_token: String,
+    max_auth_age_secs: i64,
+}
+
+impl TelegramInitDataValidator {
+    pub fn new(bot_token: &str, max_auth_age_secs: i64) -> Self {
+        TelegramInitDataValidator {
             bot_token: bot_token.to_string(),
+            max_auth_age_secs,
+        }
+    }
+
+    pub fn validate(&self, init_data: &str) -> Result<ValidatedInitData, InitDataError> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards").as_secs() as i64;
+        self.validate_at(init_data, now)
+    }
+
+    // `now` (Unix saniye) anına göre doğrular
+    fn validate_at(&self, init_data: &str, now: i64) -> Result<ValidatedInitData, InitDataError> {
+        let mut fields: Vec<(String, String)> = Vec::new();
+        for pair in init_data.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or(InitDataError::Malformed)?;
+            let key = RawStr::new(key).url_decode().map_err(|_| InitDataError::Malformed)?;
+            let value = RawStr::new(value).url_decode().map_err(|_| InitDataError::Malformed)?;
+            fields.push((key.into_owned(), value.into_owned()));
         }
+
+        let hash_index = fields.iter().position(|(k, _)| k == "hash").ok_or(InitDataError::MissingHash)?;
+        let (_, hash) = fields.remove(hash_index);
+        let expected_hash = decode_hex(&hash).ok_or(InitDataError::InvalidSignature)?;
+
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        let data_check_string = fields
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut secret_mac = HmacSha256::new_from_slice(b"WebAppData").expect("HMAC can take key of any size");
+        secret_mac.update(self.bot_token.as_bytes());
+        let secret_key = secret_mac.finalize().into_bytes();
+
+        let mut mac = HmacSha256::new_from_slice(&secret_key).expect("HMAC can take key of any size");
+        mac.update(data_check_string.as_bytes());
+        // Sabit zamanlı karşılaştırma
+        mac.verify_slice(&expected_hash).map_err(|_| InitDataError::InvalidSignature)?;
+
+        let field = |name: &str| fields.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+
+        let auth_date = field("auth_date")
+            .and_then(|v| v.parse::<i64>().ok())
+            .ok_or(InitDataError::MissingAuthDate)?;
+        if now - auth_date > self.max_auth_age_secs {
+            return Err(InitDataError::Expired);
+        }
+        if auth_date - now > MAX_AUTH_DATE_SKEW_SECS {
+            return Err(InitDataError::FromFuture);
+        }
+
+        let user_json = field("user").ok_or(InitDataError::MissingUser)?;
+        let user = serde_json::from_str::<TelegramWebAppUser>(&user_json).map_err(|_| InitDataError::InvalidUser)?;
+
+        Ok(ValidatedInitData {
+            user,
+            auth_date,
+            start_param: field("start_param"),
+        })
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
     }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
 
-    fn verify_telegram_signature(&self, init_data: &str, hash: &str) -> bool {
-        let secret_key = Sha256::digest(self.bot_token.as_bytes());
-        let mut hmac = HmacSha256::new_from_slice(&secret_key).expect("HMAC can take key of any size");
-        hmac.update(init_data.as_bytes());
+// Doğrulayıcıyı Rocket state'ine ekler ve `X-Init-Data` başlığı gönderilen isteklerde
+// imzayı kontrol eder; geçersiz initData içeren istekler 401 ile sonuçlanır.
+pub struct TelegramAuthFairing {
+    pub validator: TelegramInitDataValidator,
+}
 
-        hmac.verify_slice(hash.as_bytes()).is_ok()
+impl TelegramAuthFairing {
+    pub fn new(bot_token: &str, max_auth_age_secs: i64) -> Self {
+        TelegramAuthFairing {
+            validator: TelegramInitDataValidator::new(bot_token, max_auth_age_secs),
+        }
     }
 }
 
@@ -32,15 +191,22 @@ impl Fairing for TelegramAuthFairing {
     fn info(&self) -> Info {
         Info {
             name: "Telegram Authentication Fairing",
-            kind: Kind::Request | Kind::Response,
+            kind: Kind::Ignite | Kind::Request | Kind::Response,
         }
     }
 
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        Ok(rocket.manage(self.validator.clone()))
+    }
+
     async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
-        let init_data = request.headers().get_one("X-Init-Data").unwrap_or_default();
-        let hash = request.headers().get_one("X-Hash").unwrap_or_default();
+        let init_data = match request.headers().get_one("X-Init-Data") {
+            Some(init_data) => init_data.to_string(),
+            None => return,
+        };
 
-        if !self.verify_telegram_signature(init_data, hash) {
+        if let Err(e) = self.validator.validate(&init_data) {
+            eprintln!("Telegram initData doğrulanamadı: {}", e);
             // Unauthorized durumunu `Option<Status>` içinde cache'e ekliyoruz
             request.local_cache::<Option<Status>, _>(|| Some(Status::Unauthorized));
         }
@@ -62,3 +228,66 @@ impl Fairing for TelegramAuthFairing {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOT_TOKEN: &str = "12345:test-token";
+    const NOW: i64 = 1_700_000_000;
+    const USER: &str = r#"{"id":42,"first_name":"Ada"}"#;
+
+    fn validator() -> TelegramInitDataValidator {
+        TelegramInitDataValidator::new(BOT_TOKEN, 3600)
+    }
+
+    // Alanları Telegram'ın yaptığı gibi imzalar ve initData sorgu dizesini döner
+    fn signed(fields: &[(&str, String)]) -> String {
+        let mut sorted = fields.to_vec();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        let data_check_string = sorted.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("\n");
+
+        let mut secret_mac = HmacSha256::new_from_slice(b"WebAppData").unwrap();
+        secret_mac.update(BOT_TOKEN.as_bytes());
+        let mut mac = HmacSha256::new_from_slice(&secret_mac.finalize().into_bytes()).unwrap();
+        mac.update(data_check_string.as_bytes());
+        let hash: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+
+        fields.iter()
+            .map(|(k, v)| format!("{}={}", k, RawStr::new(v).percent_encode()))
+            .chain(std::iter::once(format!("hash={}", hash)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    fn init_data(auth_date: i64) -> String {
+        signed(&[("auth_date", auth_date.to_string()), ("query_id", "AAE".to_string()), ("user", USER.to_string())])
+    }
+
+    #[test]
+    fn valid_signature_is_accepted() {
+        let validated = validator().validate_at(&init_data(NOW - 10), NOW).unwrap();
+        assert_eq!(validated.user.id, 42);
+        assert_eq!(validated.auth_date, NOW - 10);
+    }
+
+    #[test]
+    fn tampered_field_is_rejected() {
+        let tampered = init_data(NOW).replace("query_id=AAE", "query_id=AAF");
+        assert_eq!(validator().validate_at(&tampered, NOW).unwrap_err(), InitDataError::InvalidSignature);
+    }
+
+    #[test]
+    fn expired_auth_date_is_rejected() {
+        assert_eq!(validator().validate_at(&init_data(NOW - 3601), NOW).unwrap_err(), InitDataError::Expired);
+    }
+
+    #[test]
+    fn future_auth_date_is_rejected_beyond_the_skew() {
+        assert!(validator().validate_at(&init_data(NOW + MAX_AUTH_DATE_SKEW_SECS), NOW).is_ok());
+        assert_eq!(
+            validator().validate_at(&init_data(NOW + MAX_AUTH_DATE_SKEW_SECS + 1), NOW).unwrap_err(),
+            InitDataError::FromFuture
+        );
+    }
+}
@@ -0,0 +1,191 @@
+// src/services/ton_transfer.rs
+use std::collections::HashMap;
+use std::sync::Mutex;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::models::money::Ton;
+
+// Gönderim hatası. `Rejected` transferin gönderilmediği kesin olan hatalardır (bağlantı kurulamadı,
+// 4xx); `Unknown` ise zaman aşımı ya da 5xx gibi transferin gönderilmiş olabileceği durumlardır.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferError {
+    Rejected(String),
+    Unknown(String),
+}
+
+impl TransferError {
+    pub fn message(&self) -> &str {
+        match self {
+            TransferError::Rejected(message) | TransferError::Unknown(message) => message,
+        }
+    }
+}
+
+// Bir idempotency anahtarıyla yapılmış transferin gönderici tarafındaki durumu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferStatus {
+    Sent(String), // tx hash
+    Pending,
+    NotSent,
+}
+
+// Çekimleri zincire gönderen taraf. Özel anahtarlar API'de tutulmaz: gerçek ağda imzalama
+// ayrı bir cüzdan servisinde yapılır, yerelde ve testlerde `FakeTonTransfer` kullanılır.
+// Aynı `idempotency_key` ile tekrarlanan gönderim ikinci bir transfer yapmaz, ilkinin sonucunu döner.
+#[rocket::async_trait]
+pub trait TonTransfer: Send + Sync {
+    // Başarılıysa gönderilen işlemin hash'ini döner
+    async fn send(&self, destination: &str, amount: Ton, comment: &str, idempotency_key: &str) -> Result<String, TransferError>;
+
+    // `idempotency_key` ile yapılmış transferin durumunu sorar
+    async fn status(&self, idempotency_key: &str) -> Result<TransferStatus, String>;
+}
+
+// Zincire hiçbir şey göndermeyen sahte gönderici
+#[derive(Default)]
+pub struct FakeTonTransfer {
+    sent: Mutex<HashMap<String, String>>, // idempotency anahtarı -> tx hash
+}
+
+#[rocket::async_trait]
+impl TonTransfer for FakeTonTransfer {
+    async fn send(&self, destination: &str, amount: Ton, comment: &str, idempotency_key: &str) -> Result<String, TransferError> {
+        let mut sent = self.sent.lock().unwrap();
+        if let Some(tx_hash) = sent.get(idempotency_key) {
+            return Ok(tx_hash.clone());
+        }
+        println!("Fake TON transfer: {} to {} ({})", amount, destination, comment);
+        let tx_hash = format!("fake:{}", Uuid::new_v4());
+        sent.insert(idempotency_key.to_string(), tx_hash.clone());
+        Ok(tx_hash)
+    }
+
+    async fn status(&self, idempotency_key: &str) -> Result<TransferStatus, String> {
+        Ok(match self.sent.lock().unwrap().get(idempotency_key) {
+            Some(tx_hash) => TransferStatus::Sent(tx_hash.clone()),
+            None => TransferStatus::NotSent,
+        })
+    }
+}
+
+// Cüzdan servisine `POST <url>/transfers` isteği gönderen gönderici. Anahtar hem gövdede hem
+// `Idempotency-Key` başlığında gider; durum `GET <url>/transfers/<anahtar>` ile sorulur.
+pub struct WalletServiceTransfer {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TransferRequest<'a> {
+    destination: &'a str,
+    amount: Ton, // nanoTON
+    comment: &'a str,
+    idempotency_key: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TransferResponse {
+    tx_hash: String,
+}
+
+#[derive(Deserialize)]
+struct TransferStatusResponse {
+    status: String, // "sent", "pending" ya da "failed"
+    tx_hash: Option<String>,
+}
+
+impl WalletServiceTransfer {
+    pub fn new(base_url: &str, token: Option<String>) -> Self {
+        WalletServiceTransfer {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+}
+
+impl WalletServiceTransfer {
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+// Yanıt kodu 5xx ise transfer yapılmış olabilir; 4xx istek reddedildi demektir
+fn classify_status(status: StatusCode, body: String) -> TransferError {
+    let message = format!("Wallet service returned {}: {}", status, body);
+    if status.is_server_error() {
+        TransferError::Unknown(message)
+    } else {
+        TransferError::Rejected(message)
+    }
+}
+
+#[rocket::async_trait]
+impl TonTransfer for WalletServiceTransfer {
+    async fn send(&self, destination: &str, amount: Ton, comment: &str, idempotency_key: &str) -> Result<String, TransferError> {
+        let request = self.authorized(self.http
+            .post(format!("{}/transfers", self.base_url))
+            .header("Idempotency-Key", idempotency_key)
+            .json(&TransferRequest { destination, amount, comment, idempotency_key }));
+
+        let response = request.send().await.map_err(|e| {
+            let message = format!("Wallet service request failed: {}", e);
+            // Bağlantı hiç kurulamadıysa istek servise ulaşmamıştır
+            if e.is_connect() { TransferError::Rejected(message) } else { TransferError::Unknown(message) }
+        })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(classify_status(status, body));
+        }
+        let transfer: TransferResponse = response.json().await
+            .map_err(|e| TransferError::Unknown(format!("Wallet service response could not be read: {}", e)))?;
+        Ok(transfer.tx_hash)
+    }
+
+    async fn status(&self, idempotency_key: &str) -> Result<TransferStatus, String> {
+        let request = self.authorized(self.http.get(format!("{}/transfers/{}", self.base_url, idempotency_key)));
+        let response = request.send().await.map_err(|e| format!("Wallet service request failed: {}", e))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(TransferStatus::NotSent);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Wallet service returned {}", response.status()));
+        }
+        let transfer: TransferStatusResponse = response.json().await
+            .map_err(|e| format!("Wallet service response could not be read: {}", e))?;
+        match (transfer.status.as_str(), transfer.tx_hash) {
+            ("sent", Some(tx_hash)) => Ok(TransferStatus::Sent(tx_hash)),
+            ("pending", _) => Ok(TransferStatus::Pending),
+            ("failed", _) => Ok(TransferStatus::NotSent),
+            (other, _) => Err(format!("Wallet service returned unknown transfer status: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rocket::async_test]
+    async fn fake_transfer_sends_once_per_idempotency_key() {
+        let transfer = FakeTonTransfer::default();
+        assert_eq!(transfer.status("w-1").await, Ok(TransferStatus::NotSent));
+
+        let first = transfer.send("0:abc", Ton::from_ton(1), "withdrawal:w-1", "w-1").await.unwrap();
+        let again = transfer.send("0:abc", Ton::from_ton(1), "withdrawal:w-1", "w-1").await.unwrap();
+        assert_eq!(first, again);
+        assert_eq!(transfer.status("w-1").await, Ok(TransferStatus::Sent(first)));
+    }
+
+    #[test]
+    fn server_errors_leave_the_transfer_unknown() {
+        assert!(matches!(classify_status(StatusCode::BAD_GATEWAY, String::new()), TransferError::Unknown(_)));
+        assert!(matches!(classify_status(StatusCode::BAD_REQUEST, String::new()), TransferError::Rejected(_)));
+    }
+}
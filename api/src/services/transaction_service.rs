@@ -0,0 +1,76 @@
+// src/services/transaction_service.rs
+use std::future::Future;
+use mongodb::{Client, ClientSession};
+use mongodb::error::{Error, TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT};
+use rocket::http::Status;
+
+// Geçici hatalarda (write conflict, primary değişimi) işlemin kaç kez yeniden deneneceği
+const MAX_TRANSACTION_ATTEMPTS: usize = 5;
+
+// İşlem içindeki adımların sonucu: iş kuralı reddi (işlem geri alınır ve istemciye döner)
+// ya da veritabanı hatası
+#[derive(Debug)]
+pub enum TxError {
+    Rejected(Status, String),
+    Database(Error),
+}
+
+impl TxError {
+    pub fn rejected(status: Status, message: &str) -> Self {
+        TxError::Rejected(status, message.to_string())
+    }
+}
+
+impl From<Error> for TxError {
+    fn from(e: Error) -> Self {
+        TxError::Database(e)
+    }
+}
+
+// `body` adımlarını tek bir MongoDB işlemi (transaction) içinde çalıştırır.
+// `body` Ok dönerse işlem onaylanır, Err dönerse tüm yazmalar geri alınır.
+// Oturum `body`'ye verilip geri alınır; böylece adımlar `&mut ClientSession` ile yazılabilir.
+// Not: MongoDB işlemleri replica set (tek üyeli de olabilir) gerektirir.
+pub async fn run_transaction<T, F, Fut>(client: &Client, mut body: F) -> Result<T, TxError>
+where
+    F: FnMut(ClientSession) -> Fut,
+    Fut: Future<Output = (ClientSession, Result<T, TxError>)>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mut session = client.start_session(None).await?;
+        session.start_transaction(None).await?;
+
+        let (mut session, result) = body(session).await;
+        let error = match result {
+            Ok(value) => match commit(&mut session).await {
+                Ok(()) => return Ok(value),
+                Err(e) => e,
+            },
+            Err(TxError::Database(e)) => {
+                let _ = session.abort_transaction().await;
+                e
+            }
+            Err(rejected) => {
+                let _ = session.abort_transaction().await;
+                return Err(rejected);
+            }
+        };
+
+        if !error.contains_label(TRANSIENT_TRANSACTION_ERROR) || attempt >= MAX_TRANSACTION_ATTEMPTS {
+            return Err(TxError::Database(error));
+        }
+    }
+}
+
+async fn commit(session: &mut ClientSession) -> Result<(), Error> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match session.commit_transaction().await {
+            Err(e) if e.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) && attempt < MAX_TRANSACTION_ATTEMPTS => continue,
+            other => return other,
+        }
+    }
+}
@@ -0,0 +1,250 @@
+// src/services/withdrawal_service.rs
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use mongodb::Client;
+use mongodb::bson::{doc, Document};
+use rocket::http::Status;
+use uuid::Uuid;
+use crate::models::ledger::{user_account, Asset, LedgerTransaction, Reason, SYSTEM_WITHDRAWALS, SYSTEM_WITHDRAWALS_PENDING};
+use crate::models::money::Ton;
+use crate::models::withdrawal::{is_valid_ton_address, Withdrawal, WithdrawalEvent, WithdrawalStatus};
+use crate::repository::{ledger_repository::LedgerRepository, user_repository::UserRepository, withdrawal_repository::WithdrawalRepository};
+use crate::services::ton_transfer::{TonTransfer, TransferError, TransferStatus};
+use crate::services::transaction_service::{run_transaction, TxError};
+
+// Çekim taleplerinin durum makinesi. Her geçiş tek bir işlemde durum değişikliğini,
+// geçmiş kaydını ve defter hareketini birlikte yazar.
+pub struct WithdrawalService {
+    mongo: Client,
+    withdrawals: WithdrawalRepository,
+    users: UserRepository,
+    ledger: LedgerRepository,
+    transfer: Arc<dyn TonTransfer>,
+    min_amount: Ton,
+}
+
+impl WithdrawalService {
+    pub fn new(mongo: &Client, transfer: Arc<dyn TonTransfer>, min_amount: Ton) -> Self {
+        WithdrawalService {
+            mongo: mongo.clone(),
+            withdrawals: WithdrawalRepository::new(mongo),
+            users: UserRepository::new(mongo),
+            ledger: LedgerRepository::new(mongo),
+            transfer,
+            min_amount,
+        }
+    }
+
+    pub fn repository(&self) -> &WithdrawalRepository {
+        &self.withdrawals
+    }
+
+    // Tutarı bakiyeden düşüp bekleyen çekimler hesabında kilitler
+    pub async fn request(&self, telegram_id: i64, amount: Ton) -> Result<Withdrawal, TxError> {
+        if amount < self.min_amount {
+            return Err(TxError::Rejected(Status::BadRequest, format!("400: Bad Request - Minimum withdrawal is {}", self.min_amount)));
+        }
+        let (withdrawals, users, ledger) = (&self.withdrawals, &self.users, &self.ledger);
+
+        run_transaction(&self.mongo, |mut session| async move {
+            let result: Result<Withdrawal, TxError> = async {
+                let user = users.find_user_with_session(&mut session, telegram_id).await?
+                    .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - User not found"))?;
+                let destination = user.wallet_address
+                    .filter(|address| is_valid_ton_address(address))
+                    .ok_or_else(|| TxError::rejected(Status::BadRequest, "400: Bad Request - Set a valid wallet_address before withdrawing"))?;
+
+                if !users.debit_with_session(&mut session, telegram_id, Asset::Ton, amount.nanoton()).await? {
+                    return Err(TxError::rejected(Status::BadRequest, "400: Bad Request - Insufficient TON balance"));
+                }
+
+                let now = unix_now();
+                let withdrawal = Withdrawal {
+                    withdrawal_id: Uuid::new_v4().to_string(),
+                    telegram_id,
+                    amount,
+                    destination,
+                    status: WithdrawalStatus::Requested,
+                    tx_hash: None,
+                    history: vec![WithdrawalEvent {
+                        status: WithdrawalStatus::Requested,
+                        actor: user_account(telegram_id),
+                        note: None,
+                        at: now,
+                    }],
+                    created_at: now,
+                    updated_at: now,
+                };
+                withdrawals.insert_with_session(&mut session, &withdrawal).await?;
+                ledger.record_with_session(&mut session, LedgerTransaction::transfer(
+                    Reason::Withdrawal,
+                    Asset::Ton,
+                    user_account(telegram_id),
+                    SYSTEM_WITHDRAWALS_PENDING.to_string(),
+                    amount.nanoton(),
+                    Some(format!("withdrawal:{}", withdrawal.withdrawal_id)),
+                )).await?;
+                Ok(withdrawal)
+            }.await;
+            (session, result)
+        }).await
+    }
+
+    // Talebi onaylar ve gönderir
+    pub async fn approve(&self, withdrawal_id: &str, admin_id: i64) -> Result<Withdrawal, TxError> {
+        let withdrawal = self.find(withdrawal_id).await?;
+        if withdrawal.status != WithdrawalStatus::Requested {
+            return Err(invalid_transition(&withdrawal, WithdrawalStatus::Approved));
+        }
+        let approved = self.transition(&withdrawal, WithdrawalStatus::Approved, admin_actor(admin_id), None, doc! {}).await?;
+        self.payout(approved).await
+    }
+
+    // Başarısız gönderimi yeniden dener
+    pub async fn retry(&self, withdrawal_id: &str, admin_id: i64) -> Result<Withdrawal, TxError> {
+        let withdrawal = self.find(withdrawal_id).await?;
+        if withdrawal.status != WithdrawalStatus::Failed {
+            return Err(invalid_transition(&withdrawal, WithdrawalStatus::Approved));
+        }
+        let approved = self.transition(&withdrawal, WithdrawalStatus::Approved, admin_actor(admin_id), Some("retry".to_string()), doc! {}).await?;
+        self.payout(approved).await
+    }
+
+    // Talebi reddeder (requested) ya da başarısız gönderimi kapatır (failed); tutar kullanıcıya iade edilir
+    pub async fn refund(&self, withdrawal_id: &str, admin_id: i64, reason: Option<String>) -> Result<Withdrawal, TxError> {
+        let withdrawal = self.find(withdrawal_id).await?;
+        self.transition(&withdrawal, WithdrawalStatus::Refunded, admin_actor(admin_id), reason, doc! {}).await
+    }
+
+    // "approved" durumda takılı kalan ya da sonucu bilinmeyen ("unknown") gönderimi cüzdan servisine
+    // sorar: gönderildiyse "sent", gönderilmediyse yeniden denenebilmesi için "failed" olur.
+    // Servis transferi hâlâ işliyorsa talep olduğu gibi kalır.
+    pub async fn reconcile(&self, withdrawal_id: &str, admin_id: i64) -> Result<Withdrawal, TxError> {
+        let withdrawal = self.find(withdrawal_id).await?;
+        if !matches!(withdrawal.status, WithdrawalStatus::Approved | WithdrawalStatus::Unknown) {
+            return Err(TxError::Rejected(Status::Conflict, format!(
+                "409: Conflict - Withdrawal is {} and needs no reconciliation",
+                withdrawal.status.as_str()
+            )));
+        }
+
+        let status = self.transfer.status(&withdrawal.withdrawal_id).await.map_err(|e| {
+            eprintln!("Withdrawal {} status could not be read: {}", withdrawal.withdrawal_id, e);
+            TxError::rejected(Status::BadGateway, "502: Bad Gateway - Wallet service is unavailable")
+        })?;
+        let actor = admin_actor(admin_id);
+        match status {
+            TransferStatus::Sent(tx_hash) => {
+                let extra = doc! { "tx_hash": &tx_hash };
+                self.transition(&withdrawal, WithdrawalStatus::Sent, actor, Some(tx_hash), extra).await
+            }
+            TransferStatus::NotSent => {
+                self.transition(&withdrawal, WithdrawalStatus::Failed, actor, Some("not sent by wallet service".to_string()), doc! {}).await
+            }
+            TransferStatus::Pending => Ok(withdrawal),
+        }
+    }
+
+    // Onaylı talebi zincire gönderir; sonuç "sent", "failed" ya da gönderilip gönderilmediği
+    // bilinmiyorsa "unknown" olarak kaydedilir. `withdrawal_id` idempotency anahtarıdır: yeniden
+    // deneme, ilk gönderim ulaşmışsa ikinci bir transfer yapmaz.
+    async fn payout(&self, withdrawal: Withdrawal) -> Result<Withdrawal, TxError> {
+        let comment = format!("withdrawal:{}", withdrawal.withdrawal_id);
+        match self.transfer.send(&withdrawal.destination, withdrawal.amount, &comment, &withdrawal.withdrawal_id).await {
+            Ok(tx_hash) => {
+                let extra = doc! { "tx_hash": &tx_hash };
+                self.transition(&withdrawal, WithdrawalStatus::Sent, "system".to_string(), Some(tx_hash), extra).await
+            }
+            Err(e) => {
+                eprintln!("Withdrawal {} payout failed: {}", withdrawal.withdrawal_id, e.message());
+                let next = match e {
+                    TransferError::Rejected(_) => WithdrawalStatus::Failed,
+                    TransferError::Unknown(_) => WithdrawalStatus::Unknown,
+                };
+                self.transition(&withdrawal, next, "system".to_string(), Some(e.message().to_string()), doc! {}).await
+            }
+        }
+    }
+
+    async fn find(&self, withdrawal_id: &str) -> Result<Withdrawal, TxError> {
+        self.withdrawals.find_by_id(withdrawal_id).await?
+            .ok_or_else(|| TxError::rejected(Status::NotFound, "404: Not Found - Withdrawal not found"))
+    }
+
+    async fn transition(
+        &self,
+        withdrawal: &Withdrawal,
+        next: WithdrawalStatus,
+        actor: String,
+        note: Option<String>,
+        extra: Document,
+    ) -> Result<Withdrawal, TxError> {
+        if !withdrawal.status.can_transition_to(next) {
+            return Err(invalid_transition(withdrawal, next));
+        }
+        let event = WithdrawalEvent { status: next, actor, note, at: unix_now() };
+        let (withdrawals, users, ledger) = (&self.withdrawals, &self.users, &self.ledger);
+        let (event, extra) = (&event, &extra);
+
+        run_transaction(&self.mongo, |mut session| async move {
+            let result: Result<(), TxError> = async {
+                if !withdrawals.transition_with_session(&mut session, &withdrawal.withdrawal_id, withdrawal.status, event, extra.clone()).await? {
+                    return Err(TxError::rejected(Status::Conflict, "409: Conflict - Withdrawal was updated by another request"));
+                }
+
+                let reference = Some(format!("withdrawal:{}", withdrawal.withdrawal_id));
+                match next {
+                    // Kilitli tutar zincire çıktı
+                    WithdrawalStatus::Sent => {
+                        ledger.record_with_session(&mut session, LedgerTransaction::transfer(
+                            Reason::Withdrawal,
+                            Asset::Ton,
+                            SYSTEM_WITHDRAWALS_PENDING.to_string(),
+                            SYSTEM_WITHDRAWALS.to_string(),
+                            withdrawal.amount.nanoton(),
+                            reference,
+                        )).await?;
+                    }
+                    // Kilitli tutar kullanıcıya geri döner
+                    WithdrawalStatus::Refunded => {
+                        if !users.credit_with_session(&mut session, withdrawal.telegram_id, Asset::Ton, withdrawal.amount.nanoton()).await? {
+                            return Err(TxError::rejected(Status::NotFound, "404: Not Found - User not found"));
+                        }
+                        ledger.record_with_session(&mut session, LedgerTransaction::transfer(
+                            Reason::WithdrawalRefund,
+                            Asset::Ton,
+                            SYSTEM_WITHDRAWALS_PENDING.to_string(),
+                            user_account(withdrawal.telegram_id),
+                            withdrawal.amount.nanoton(),
+                            reference,
+                        )).await?;
+                    }
+                    _ => {}
+                }
+                Ok(())
+            }.await;
+            (session, result)
+        }).await?;
+
+        self.find(&withdrawal.withdrawal_id).await
+    }
+}
+
+fn admin_actor(admin_id: i64) -> String {
+    format!("admin:{}", admin_id)
+}
+
+fn invalid_transition(withdrawal: &Withdrawal, next: WithdrawalStatus) -> TxError {
+    TxError::Rejected(Status::Conflict, format!(
+        "409: Conflict - Withdrawal is {} and cannot become {}",
+        withdrawal.status.as_str(),
+        next.as_str()
+    ))
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64
+}
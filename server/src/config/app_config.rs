@@ -0,0 +1,198 @@
+use figment::{Figment, Profile, providers::{Env, Format, Toml}};
+use serde::{Deserialize, Serialize};
+use crate::models::bot::BotStrategy;
+use crate::services::game_engine::TurnRules;
+
+// Sunucu yapılandırması.
+// Sıra: Server.toml (profil bazlı: [default], [staging], [production]) ve ardından
+// `SERVER_` önekli ortam değişkenleri (örn. SERVER_MONGO_URI, SERVER_TAP_PORT).
+// Profil `SERVER_PROFILE`, dosya yolu `SERVER_CONFIG` ile seçilir.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AppConfig {
+    pub mongo_uri: String,
+    #[serde(default = "default_mongo_app_name")]
+    pub mongo_app_name: String,
+    pub redis_url: String,
+    pub jwt_secret: String,        // API ile aynı olmalı; canlı oyun soketinde kimlik doğrulama
+    #[serde(default = "default_bind_host")]
+    pub bind_host: String,
+    #[serde(default = "default_tap_port")]
+    pub tap_port: u16,             // Tıklama (tap) WebSocket'i
+    #[serde(default = "default_salon_port")]
+    pub salon_port: u16,           // Salon WebSocket'i
+    #[serde(default = "default_live_game_port")]
+    pub live_game_port: u16,       // Canlı oyun WebSocket'i
+
+    // Tıklama muhasebesi: enerji havuzu ve otomatik tıklayıcı sınırları
+    #[serde(default = "default_tap_energy_max")]
+    pub tap_energy_max: i32,
+    #[serde(default = "default_tap_energy_regen_per_sec")]
+    pub tap_energy_regen_per_sec: i32,
+    #[serde(default = "default_tap_max_clicks_per_sec")]
+    pub tap_max_clicks_per_sec: u32,
+    #[serde(default = "default_tap_max_batch_age_ms")]
+    pub tap_max_batch_age_ms: i64,
+    #[serde(default = "default_tap_max_clock_skew_ms")]
+    pub tap_max_clock_skew_ms: i64,
+
+    // Boost seviyelerinin kazanç çarpanları (yüzde; 1. eleman level 1) ve süpürücü aralığı
+    #[serde(default = "default_boost_multipliers_pct")]
+    pub boost_multipliers_pct: Vec<i64>,
+    #[serde(default = "default_boost_sweep_interval_secs")]
+    pub boost_sweep_interval_secs: u64,
+
+    // Pasif kazanç: itibar puanı başına saatlik click_score ve en fazla kaç saat birikeceği
+    #[serde(default = "default_passive_income_per_reputation_per_hour")]
+    pub passive_income_per_reputation_per_hour: i64,
+    #[serde(default = "default_passive_income_max_hours")]
+    pub passive_income_max_hours: i64,
+    #[serde(default = "default_passive_income_max_per_hour")]
+    pub passive_income_max_per_hour: i64, // Boost'suz saatlik kazancın üst sınırı
+
+    // Ekonomi oranlarının (salon ödülleri, itibar artışları) Mongo'dan yeniden okunma aralığı
+    #[serde(default = "default_economy_reload_interval_secs")]
+    pub economy_reload_interval_secs: u64,
+
+    // Masa dolup herkes ödedikten sonra oyunun başlamasına kadar geçen süre
+    #[serde(default = "default_game_start_countdown_secs")]
+    pub game_start_countdown_secs: u64,
+
+    // Oyuncunun her zar için süresi, süre dolmadan önceki uyarı penceresi ve süre dolunca yapılacak işlem
+    #[serde(default = "default_roll_timeout_secs")]
+    pub roll_timeout_secs: u64,
+    #[serde(default = "default_roll_warning_secs")]
+    pub roll_warning_secs: u64,
+    #[serde(default)]
+    pub roll_timeout_action: RollTimeoutAction,
+
+    // Bağlantısı kopan oyuncunun yeniden bağlanıp oyuna dönebileceği süre; sonra kalan zarları 0 sayılır
+    #[serde(default = "default_reconnect_grace_secs")]
+    pub reconnect_grace_secs: u64,
+
+    // Açılışta checkpoint'i olan oyunlar sürdürülsün mü; false ise hepsi iptal edilip iade edilir
+    #[serde(default = "default_resume_games_on_restart")]
+    pub resume_games_on_restart: bool,
+
+    // Masada oturan bir oyuncu bu kadar saniye bekledikten sonra boş koltuklar botlarla dolar (0: kapalı);
+    // yeni botlara bu stratejilerden biri rastgele verilir
+    #[serde(default = "default_bot_fill_after_secs")]
+    pub bot_fill_after_secs: u64,
+    #[serde(default = "default_bot_strategies")]
+    pub bot_strategies: Vec<BotStrategy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RollTimeoutAction {
+    #[default]
+    AutoRoll, // Sunucu zarı oyuncu adına atar
+    Forfeit,  // Zar hakkı 0 sayılır
+}
+
+fn default_mongo_app_name() -> String { "websocket-app".to_string() }
+fn default_bind_host() -> String { "127.0.0.1".to_string() }
+fn default_tap_port() -> u16 { 9001 }
+fn default_salon_port() -> u16 { 9002 }
+fn default_live_game_port() -> u16 { 9003 }
+fn default_tap_energy_max() -> i32 { 1000 }
+fn default_tap_energy_regen_per_sec() -> i32 { 3 }
+fn default_tap_max_clicks_per_sec() -> u32 { 15 }
+fn default_tap_max_batch_age_ms() -> i64 { 10_000 }
+fn default_tap_max_clock_skew_ms() -> i64 { 2_000 }
+fn default_boost_multipliers_pct() -> Vec<i64> { vec![200, 300, 500] }
+fn default_boost_sweep_interval_secs() -> u64 { 60 }
+fn default_passive_income_per_reputation_per_hour() -> i64 { 1 }
+fn default_passive_income_max_hours() -> i64 { 3 }
+fn default_passive_income_max_per_hour() -> i64 { 5_000 }
+fn default_economy_reload_interval_secs() -> u64 { 10 }
+fn default_game_start_countdown_secs() -> u64 { 5 }
+fn default_roll_timeout_secs() -> u64 { 10 }
+fn default_roll_warning_secs() -> u64 { 5 }
+fn default_reconnect_grace_secs() -> u64 { 60 }
+fn default_resume_games_on_restart() -> bool { true }
+fn default_bot_fill_after_secs() -> u64 { 30 }
+fn default_bot_strategies() -> Vec<BotStrategy> { vec![BotStrategy::Quick, BotStrategy::Steady, BotStrategy::Hesitant] }
+
+impl AppConfig {
+    pub fn figment() -> Figment {
+        let path = std::env::var("SERVER_CONFIG").unwrap_or_else(|_| "Server.toml".to_string());
+        let profile = Profile::from_env_or("SERVER_PROFILE", Profile::Default);
+
+        Figment::new()
+            .merge(Toml::file(path).nested())
+            .merge(Env::prefixed("SERVER_").ignore(&["PROFILE", "CONFIG"]).global())
+            .select(profile)
+    }
+
+    // Yapılandırmayı yükler ve doğrular; hatalı yapılandırmayla sunucu başlamaz
+    pub fn load() -> Result<Self, String> {
+        let config: AppConfig = Self::figment()
+            .extract()
+            .map_err(|e| format!("Yapılandırma okunamadı: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !self.mongo_uri.starts_with("mongodb://") && !self.mongo_uri.starts_with("mongodb+srv://") {
+            return Err(format!("mongo_uri geçersiz: {}", self.mongo_uri));
+        }
+        if !self.redis_url.starts_with("redis://") && !self.redis_url.starts_with("rediss://") {
+            return Err(format!("redis_url geçersiz: {}", self.redis_url));
+        }
+
+        if self.jwt_secret.len() < 32 {
+            return Err("jwt_secret en az 32 karakter olmalı".to_string());
+        }
+
+        let ports = [self.tap_port, self.salon_port, self.live_game_port];
+        if ports.contains(&0) {
+            return Err("WebSocket portları 0 olamaz".to_string());
+        }
+        for (i, port) in ports.iter().enumerate() {
+            if ports[i + 1..].contains(port) {
+                return Err(format!("WebSocket portu {} birden fazla sunucuya atanmış", port));
+            }
+        }
+
+        if self.tap_energy_max <= 0 || self.tap_energy_regen_per_sec <= 0 || self.tap_max_clicks_per_sec == 0 {
+            return Err("tap_energy_max, tap_energy_regen_per_sec ve tap_max_clicks_per_sec pozitif olmalı".to_string());
+        }
+        if self.tap_max_batch_age_ms <= 0 || self.tap_max_clock_skew_ms < 0 {
+            return Err("tap_max_batch_age_ms pozitif, tap_max_clock_skew_ms negatif olmayan bir değer olmalı".to_string());
+        }
+        if self.boost_multipliers_pct.is_empty() || self.boost_multipliers_pct.iter().any(|pct| *pct < 100) {
+            return Err("boost_multipliers_pct boş olamaz ve her çarpan en az 100 olmalı".to_string());
+        }
+        if self.boost_sweep_interval_secs == 0 {
+            return Err("boost_sweep_interval_secs pozitif olmalı".to_string());
+        }
+        if self.economy_reload_interval_secs == 0 {
+            return Err("economy_reload_interval_secs pozitif olmalı".to_string());
+        }
+        if self.roll_timeout_secs == 0 || self.roll_warning_secs > self.roll_timeout_secs {
+            return Err("roll_timeout_secs pozitif olmalı ve roll_warning_secs ondan büyük olamaz".to_string());
+        }
+        if self.passive_income_per_reputation_per_hour < 0 || self.passive_income_max_hours < 0 || self.passive_income_max_per_hour < 0 {
+            return Err("passive_income_per_reputation_per_hour, passive_income_max_hours ve passive_income_max_per_hour negatif olamaz".to_string());
+        }
+        if self.bot_strategies.is_empty() {
+            return Err("bot_strategies boş olamaz".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn address(&self, port: u16) -> String {
+        format!("{}:{}", self.bind_host, port)
+    }
+
+    // Oyun motorunun süre kuralları
+    pub fn turn_rules(&self) -> TurnRules {
+        TurnRules {
+            roll_timeout_secs: self.roll_timeout_secs,
+            roll_warning_secs: self.roll_warning_secs,
+            reconnect_grace_secs: self.reconnect_grace_secs,
+            timeout_action: self.roll_timeout_action,
+        }
+    }
+}
@@ -1 +1,2 @@
 pub mod mongo_config;
+pub mod app_config;
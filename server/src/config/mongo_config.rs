@@ -1,8 +1,8 @@
 use mongodb::{Client, options::ClientOptions};
+use crate::config::app_config::AppConfig;
 
-pub async fn setup_mongo() -> Client {
-    let mongo_uri = "mongodb://localhost:27017";
-    let mut client_options = ClientOptions::parse(mongo_uri).await.unwrap();
-    client_options.app_name = Some("websocket-app".to_string());
+pub async fn setup_mongo(config: &AppConfig) -> Client {
+    let mut client_options = ClientOptions::parse(&config.mongo_uri).await.unwrap();
+    client_options.app_name = Some(config.mongo_app_name.clone());
     Client::with_options(client_options).unwrap()
 }
@@ -3,39 +3,67 @@ mod repository;
 mod models;
 mod config;
 
+use std::sync::Arc;
+use config::app_config::AppConfig;
 use config::mongo_config::setup_mongo;
-use services::live_game_socket_services_bot::run_live_game_websocket_server_bots;
 use services::websocket_service::run_websocket_server;
 use services::salon_websocket_service::run_salon_websocket_server; // Salon için WebSocket fonksiyonu
 use services::redis_service::setup_redis;
+use services::boost_engine::run_boost_sweeper;
+use services::economy_cache::{run_economy_reloader, EconomyCache};
 use crate::services::live_game_socket_services::run_live_game_websocket_server;
 
 
 #[tokio::main]
 async fn main() {
-    let mongo_client = setup_mongo().await;
-    let _redis_conn = setup_redis().await.unwrap(); // Redis bağlantısını kur
+    // Yapılandırma tüm sunucular arasında paylaşılır; geçersizse başlamıyoruz
+    let config = match AppConfig::load() {
+        Ok(config) => Arc::new(config),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mongo_client = setup_mongo(&config).await;
+    let _redis_conn = setup_redis(&config.redis_url).await.unwrap(); // Redis bağlantısını kur
+
+    // Salon ödülleri ve itibar artışları; API'deki yönetici değişiklikleri periyodik olarak okunur
+    let economy = Arc::new(EconomyCache::load(&mongo_client).await);
 
     let mongo_client_clone = mongo_client.clone(); // Clone yapıyoruz
     let mongo_client_clone_clone = mongo_client.clone(); // Clone yapıyoruz
     let mongo_client_clone_clone_clone = mongo_client.clone(); // Clone yapıyoruz
-    let mongo_client_clone_clone_clone_clone = mongo_client.clone(); // Clone yapıyoruz
 
-    let user_socket = tokio::spawn(async move {
-        run_websocket_server(mongo_client_clone).await;
+    let user_socket = tokio::spawn({
+        let config = config.clone();
+        async move {
+            run_websocket_server(mongo_client_clone, config).await;
+        }
     });
 
-    let salon_socket = tokio::spawn(async move {
-        run_salon_websocket_server(mongo_client_clone_clone).await;
+    let salon_socket = tokio::spawn({
+        let config = config.clone();
+        async move {
+            run_salon_websocket_server(mongo_client_clone_clone, config).await;
+        }
     });
-    
-    let live_game_socket = tokio::spawn(async move {
-        run_live_game_websocket_server(&mongo_client_clone_clone_clone).await;
 
+    let live_game_socket = tokio::spawn({
+        let config = config.clone();
+        let economy = economy.clone();
+        async move {
+            run_live_game_websocket_server(&mongo_client_clone_clone_clone, config, economy).await;
+        }
     });
-    let live_game_socket_bot = tokio::spawn(async move {
-        run_live_game_websocket_server_bots(&mongo_client_clone_clone_clone_clone).await;
-
+    // Süresi dolan boost'ları kaldırır
+    let boost_sweeper = tokio::spawn({
+        let config = config.clone();
+        let mongo_client = mongo_client.clone();
+        async move {
+            run_boost_sweeper(mongo_client, config).await;
+        }
     });
-    let _ = tokio::join!(user_socket, salon_socket,live_game_socket,live_game_socket_bot);
-}
\ No newline at end of file
+    let economy_reloader = tokio::spawn(run_economy_reloader(economy, config.economy_reload_interval_secs));
+    let _ = tokio::join!(user_socket, salon_socket,live_game_socket, boost_sweeper, economy_reloader);
+}
@@ -0,0 +1,47 @@
+// models/bot.rs
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Sunucunun yönettiği bot oyuncular (bots.bots). Bot id'leri negatiftir: Telegram kullanıcı id'leri
+// pozitif olduğundan gerçek bir oyuncuyla çakışamaz. Id'ler bots.counters'taki sayaçtan alınır.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bot {
+    pub bot_id: i64,
+    pub name: String,
+    pub strategy: BotStrategy,
+    pub created_at: i64,
+}
+
+pub fn is_bot_id(player_id: i64) -> bool {
+    player_id < 0
+}
+
+// Botun zar atma temposu. Zarlar her durumda provably-fair'dir; strateji yalnızca botun bir önceki
+// zarından (ilk zarda oyunun başlangıcından) sonra ne kadar bekleyeceğini belirler.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BotStrategy {
+    Quick,    // 0,6-1,5 sn
+    #[default]
+    Steady,   // 1,2-3,5 sn; arada bir iki katı kadar düşünür
+    Hesitant, // 2,5 sn'den zar süresinin bitmesine 2 sn kalana kadar
+}
+
+impl BotStrategy {
+    pub fn roll_delay(&self, roll_timeout_secs: u64) -> Duration {
+        let mut rng = rand::thread_rng();
+        let millis = match self {
+            BotStrategy::Quick => rng.gen_range(600..=1_500),
+            BotStrategy::Steady if rng.gen_bool(0.1) => rng.gen_range(2_400..=7_000),
+            BotStrategy::Steady => rng.gen_range(1_200..=3_500),
+            BotStrategy::Hesitant => {
+                let latest = (roll_timeout_secs.saturating_sub(2) * 1_000).max(3_000);
+                rng.gen_range(2_500..=latest)
+            }
+        };
+        // Zar süresi kısa yapılandırıldıysa bot yine de süresi dolmadan atar
+        Duration::from_millis(millis.min((roll_timeout_secs * 1_000).saturating_sub(500).max(500)))
+    }
+}
@@ -0,0 +1,32 @@
+// models/economy.rs
+// api/src/models/economy.rs ile aynı belge biçimi; sunucu yalnızca itibar artışlarını okur.
+// Salon ödülleri salon belgesindedir (models/salon.rs).
+
+use serde::Deserialize;
+
+pub const ECONOMY_CONFIG_ID: &str = "economy";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EconomyConfig {
+    pub version: i64,
+    pub reputation_rewards_pct: Vec<i32>, // Sıralamaya göre: [1., 2., 3., ...]
+    pub reputation_reward_rest_pct: i32,
+}
+
+impl Default for EconomyConfig {
+    // API ekonomi belgesini henüz oluşturmadıysa kullanılan başlangıç değerleri
+    fn default() -> Self {
+        EconomyConfig {
+            version: 0,
+            reputation_rewards_pct: vec![20, 10, 5],
+            reputation_reward_rest_pct: 1,
+        }
+    }
+}
+
+impl EconomyConfig {
+    // `rank` (0 = birinci) sırasındaki oyuncunun item itibar puanı artışı (yüzde)
+    pub fn reputation_reward_pct(&self, rank: usize) -> i32 {
+        self.reputation_rewards_pct.get(rank).copied().unwrap_or(self.reputation_reward_rest_pct)
+    }
+}
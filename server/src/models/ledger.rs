@@ -0,0 +1,67 @@
+// models/ledger.rs
+// api/src/models/ledger.rs ile aynı belge biçimi; sunucu yalnızca oyun ödüllerini ve iadelerini, tıklamaları ve pasif kazancı deftere yazar.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    Hp,
+    GamePass,
+    ClickScore,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    GameWin,
+    Tap,
+    PassiveIncome,
+    GameRefund,
+}
+
+pub const SYSTEM_BETS: &str = "system:bets";             // Masalara yatırılan game pass'ler
+pub const SYSTEM_GAME_PRIZES: &str = "system:game_prizes"; // Oyun ödülleri
+pub const SYSTEM_TAPS: &str = "system:taps";             // Tıklamalardan kazanılan click_score
+pub const SYSTEM_PASSIVE_INCOME: &str = "system:passive_income"; // Çevrimdışıyken biriken click_score
+
+pub fn user_account(telegram_id: i64) -> String {
+    format!("user:{}", telegram_id)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LedgerEntry {
+    pub account: String,
+    pub asset: Asset,
+    pub amount: i64, // Varlığın en küçük biriminde
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LedgerTransaction {
+    pub tx_id: String,
+    pub reason: Reason,
+    pub entries: Vec<LedgerEntry>,
+    pub reference: Option<String>,
+    pub created_at: i64,
+}
+
+impl LedgerTransaction {
+    // `from` hesabından `to` hesabına tek varlık transferi
+    pub fn transfer(reason: Reason, asset: Asset, from: &str, to: String, amount: i64, reference: Option<String>) -> Self {
+        LedgerTransaction {
+            tx_id: Uuid::new_v4().to_string(),
+            reason,
+            entries: vec![
+                LedgerEntry { account: from.to_string(), asset, amount: -amount },
+                LedgerEntry { account: to, asset, amount },
+            ],
+            reference,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs() as i64,
+        }
+    }
+}
@@ -1,24 +1,107 @@
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct LiveGame {
-    pub game_id: String,
-    pub players: Vec<Player>, // Oyuncular
-    pub rolls: Vec<Vec<i32>>, // Her oyuncunun zar atışları
-    pub winner_id: Option<i64>, // Kazananın ID'si
-    pub state: GameState, // Oyun durumu
-}
+// models/livegame.rs
+// Canlı oyunun durumu. Bellekte `active_games`'te tutulur ve her geçişte live_games.live_games'e
+// yazılır (checkpoint); sunucu yeniden başlarsa oyunlar buradan sürdürülür ya da iptal edilir.
+// Oyun kuralları (zar hakları, süreler, roll-off) services/game_engine.rs'tedir.
+
+use serde::{Deserialize, Serialize};
+use crate::models::salon::TieRule;
+use crate::services::game_engine::MAX_ROLLS;
+use crate::services::provably_fair::Fairness;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Player {
     pub player_id: i64,
-    pub rolls: Vec<i32>, // Oyuncunun attığı zarlar
-    pub is_active: bool, // Oyuncu bağlantısı aktif mi
+    #[serde(rename = "dice_rolls")]
+    pub rolls: Vec<i32>,
+    pub is_active: bool,
+    pub last_roll_time: Option<u64>, // Zar atma süresi için Unix timestamp
+    #[serde(default)]
+    pub disconnected_at: Option<u64>, // Bağlantı koptuysa Unix timestamp; yeniden bağlanınca None
+    #[serde(default)]
+    pub tiebreak_rolls: Vec<i32>, // roll_off turlarında atılan zarlar
+    #[serde(default)]
+    pub kind: ParticipantKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantKind {
+    #[default]
+    Human,
+    Bot, // Sunucunun bot oyuncusu (models/bot.rs); bağlantısı ve zar süresi yoktur
 }
 
-// Oyun durumlarını temsil eden enum
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LiveGame {
+    pub game_id: String,
+    pub players: Vec<Player>,
+    pub state: GameState,
+    pub salon_id: String,
+    pub table_id: String,
+    pub fairness: Fairness, // server_seed oyun bitene kadar istemcilere gönderilmez
+    #[serde(default)]
+    pub entry_fee: i32, // Oyuncu başına alınan game pass; oyun iptal edilirse iade edilir
+    #[serde(default)]
+    pub version: i64,   // Her değişiklikte artar; eski bir checkpoint yenisinin üzerine yazılmaz
+    #[serde(default)]
+    pub void_reason: Option<VoidReason>, // Voided ise neden
+    #[serde(default)]
+    pub tie_rule: TieRule,
+    #[serde(default = "default_paid_places")]
+    pub paid_places: usize, // Ödül alan sıra sayısı (salonun payout_split_bps uzunluğu)
+    #[serde(default)]
+    pub roll_off: Vec<i64>, // Süren roll-off turunda zar atması gereken oyuncular
+    #[serde(default)]
+    pub roll_off_round: usize,
+    #[serde(default = "default_max_rolls")]
+    pub max_rolls: usize, // Oyuncu başına zar
+}
+
+fn default_paid_places() -> usize { 1 }
+fn default_max_rolls() -> usize { MAX_ROLLS }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum GameState {
     Waiting,
     Ready,
     Started,
     Completed,
+    Voided, // İptal edildi, ödemeler iade ediliyor
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VoidReason {
+    ServerRestart, // Sunucu yeniden başladı ve oyun sürdürülemedi
+    Abandoned,     // Tüm oyuncuların bağlantısı koptu ve hiçbiri süresi içinde dönmedi
+    Disconnected,  // Eski botlu masa soketinde oyuncunun bağlantısı koptu; yalnızca eski kayıtlarda bulunur
+    Admin { admin_id: i64, note: Option<String> },
+}
+
+// games.voided_games: iptal edilen her oyunun kaydı
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoidedGame {
+    pub game_id: String,
+    pub salon_id: String,
+    pub table_id: String,
+    pub reason: VoidReason,
+    pub refunded_players: Vec<i64>,
+    pub refund_per_player: i32, // game pass
+    pub voided_at: i64,
+}
+
+// live_games.void_requests: yöneticinin API'den istediği iptaller (api/src/models/game.rs ile aynı biçim)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoidRequest {
+    pub game_id: String,
+    pub admin_id: i64,
+    pub note: Option<String>,
+    pub requested_at: i64,
+}
+
+impl LiveGame {
+    // Checkpoint'ten önce çağrılır
+    pub fn touch(&mut self) {
+        self.version += 1;
+    }
 }
@@ -1,3 +1,9 @@
 pub mod user;
 pub mod salon;
 pub mod table;
+pub mod ledger;
+pub mod money;
+pub mod tap_flag;
+pub mod economy;
+pub mod livegame;
+pub mod bot;
@@ -0,0 +1,10 @@
+// models/money.rs
+// api/src/models/money.rs ile aynı saklama biçimi.
+
+use serde::{Deserialize, Serialize};
+
+// TON tutarı, nanoTON (1 TON = 1_000_000_000 nanoTON) cinsinden tam sayı.
+// BSON'a Int64, JSON'a tam sayı olarak yazılır.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct Ton(i64);
@@ -1,14 +1,38 @@
-
 // models/salon.rs
 
 use serde::{Deserialize, Serialize};
 use crate::models::table::Table;
 
+// api/src/models/salon.rs ile aynı belge biçimi; salonun ekonomisi belgenin kendisindedir
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Salon {
     pub salon_id: i32,
     pub name: String,        // Örnek: "Bronz", "Altın"
-    pub entry_fee: i32,      // Örnek: 1000 TL
+    pub entry_fee: i32,      // Masada "ready" olmak için düşülen game pass
+    pub rake_bps: i32,       // Ödül havuzundan kesilen pay, baz puan (1/10_000)
+    pub max_players: i32,    // Masa başına en fazla oyuncu
+    pub payout_split_bps: Vec<i32>, // Kesinti sonrası havuzun sıralamaya göre dağılımı [1., 2., ...]
+    #[serde(default)]
+    pub tie_rule: TieRule,   // Zar toplamları eşit oyuncuların sırası nasıl belirlenir
     pub tables: Vec<Table>,  // Salon içindeki masalar
     pub created_at: i64,     // Salona giriş zamanı
 }
+
+// Eşit zar toplamlarının çözümü (server/src/services/tie_break.rs)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TieRule {
+    RollOff,    // Eşit oyuncular, eşitlik bozulana kadar birer zar daha atar
+    #[default]
+    SplitPot,   // Eşit oyuncular paylaştıkları sıraların ödüllerini eşit bölüşür
+    HighestDie, // Tek zarda en yüksek değeri atan öne geçer (sonra ikinci en yüksek, ...)
+}
+
+impl Salon {
+    // Masanın havuzundan sıralamaya göre ödenecek HP'ler (aşağı yuvarlanır); kesinti sistemde kalır
+    pub fn payouts(&self, table_id: i32) -> Vec<i32> {
+        let bet_amount = self.tables.iter().find(|t| t.table_id == table_id).map_or(0, |t| t.bet_amount) as i64;
+        let pool = bet_amount * (10_000 - self.rake_bps as i64) / 10_000;
+        self.payout_split_bps.iter().map(|bps| (pool * *bps as i64 / 10_000) as i32).collect()
+    }
+}
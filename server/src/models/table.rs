@@ -8,6 +8,8 @@ pub struct Table {
     pub players: Vec<Player>, // Masadaki oyuncular
     pub bet_amount: i32,      // Bahis miktarı
     pub game_state: GameState, // Oyunun durumu
+    #[serde(default)]
+    pub fill_with_bots: bool,  // Oyuncu join_with_bots ile oturdu; boş koltuklar hemen botlarla dolar
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+// İncelemeye alınan şüpheli tıklama davranışı
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TapFlag {
+    pub telegram_id: i64,
+    pub anomaly: String,   // "rate_exceeded", "invalid_timestamp", "uniform_intervals"
+    pub accepted: u32,     // Aynı mesajda kabul edilen tıklama
+    pub rejected: u32,     // Aynı mesajda reddedilen tıklama
+    pub reviewed: bool,
+    pub created_at: i64,   // Unix ms
+}
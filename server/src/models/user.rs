@@ -1,6 +1,6 @@
-use std::time::{SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use mongodb::bson::{doc, spec::BinarySubtype, Binary, Bson, Document};
+use crate::models::money::Ton;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Boost {
@@ -43,11 +43,14 @@ pub struct User {
     pub photo_url: Option<String>,
     pub language_code: Option<String>,
     pub hp: Option<i32>, // Tab Tab puanlarından kazanılan HP
-    pub ton_amount: Option<f64>, // Kullanıcının TON miktarı
+    pub ton_amount: Option<Ton>, // Kullanıcının TON miktarı (nanoTON)
     pub wallet_address: Option<String>, // Kullanıcının Ton Wallet adresi
 
     pub click_score: Option<i32>, // Toplam tıklama puanı
     pub click_power: Option<i32>, // Her tıklamada kazanacağı puan
+    pub energy: Option<i32>, // Tıklama enerjisi; her tıklama 1 harcar, zamanla dolar
+    pub energy_updated_at: Option<i64>, // Enerjinin en son hesaplandığı an (Unix ms)
+    pub last_seen: Option<i64>, // Tıklama soketine en son bağlı olduğu an (Unix saniye); pasif kazanç buradan hesaplanır
 
     pub boost: Option<Boost>, // Kullanıcının Boost bilgisi
     pub references: Option<References>, // Referans sistemi
@@ -58,36 +61,52 @@ pub struct User {
     pub friends: Option<Vec<i64>>, // Kullanıcının referans olduğu Telegram ID'leri
 }
 
-impl User {
-    pub fn update_click_power(&mut self) {
-        if let Some(boost) = &self.boost {
-            // Boost süresi içinde mi kontrol edelim
-            let current_time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("Time went backwards")
-                .as_secs() as i64;
+impl Boost {
+    // Boost'un bittiği an (Unix saniye)
+    pub fn ends_at(&self) -> i64 {
+        self.start_time + self.duration_days as i64 * 86400
+    }
 
-            let boost_end_time = boost.start_time + (boost.duration_days as i64 * 86400);
-            if current_time <= boost_end_time {
-                // Boost seviyesiyle click_power güncelleme
-                let boost_increment = match boost.level {
-                    1 => 2,
-                    2 => 10,
-                    3 => 20,
-                    _ => 0,
-                };
+    pub fn is_active(&self, now: i64) -> bool {
+        now < self.ends_at()
+    }
+}
 
-                // click_power değerini güncelleme
-                if let Some(ref mut power) = self.click_power {
-                    *power += boost_increment;
-                } else {
-                    // Eğer click_power None ise, boost_increment ile başlat
-                    self.click_power = Some(boost_increment);
-                }
-            } else {
-                // Boost süresi dolmuşsa boost'u sıfırla
-                self.boost = None;
-            }
+#[cfg(test)]
+impl User {
+    // Yalnızca zorunlu alanı dolu kullanıcı
+    pub fn for_test(telegram_id: i64) -> Self {
+        User {
+            telegram_id,
+            first_name: None,
+            last_name: None,
+            username: None,
+            photo_url: None,
+            language_code: None,
+            hp: None,
+            ton_amount: None,
+            wallet_address: None,
+            click_score: None,
+            click_power: None,
+            energy: None,
+            energy_updated_at: None,
+            last_seen: None,
+            boost: None,
+            references: None,
+            game_pass: None,
+            reputation_points: None,
+            items: None,
+            friends: None,
         }
     }
+
+    pub fn with_items(mut self, reputation_points: &[i32]) -> Self {
+        self.items = Some(reputation_points.iter().map(|points| Item {
+            id: Binary { subtype: BinarySubtype::Uuid, bytes: vec![0; 16] },
+            item_name: "item".to_string(),
+            item_slug: "item".to_string(),
+            reputation_points: *points,
+        }).collect());
+        self
+    }
 }
@@ -0,0 +1,67 @@
+use mongodb::{Client, Collection, IndexModel};
+use mongodb::bson::{doc, to_bson, Document};
+use mongodb::error::Result;
+use mongodb::options::{FindOneAndUpdateOptions, FindOptions, IndexOptions, ReturnDocument};
+use futures_util::TryStreamExt;
+use crate::models::bot::{Bot, BotStrategy};
+
+// Bot oyuncular ve id sayacı; botlar oyundan oyuna yeniden kullanılır
+#[derive(Clone)]
+pub struct BotRepository {
+    bots: Collection<Bot>,
+    counters: Collection<Document>,
+}
+
+impl BotRepository {
+    pub fn new(client: &Client) -> Self {
+        let bots = client.database("bots").collection("bots");
+        let counters = client.database("bots").collection("counters");
+        Self { bots, counters }
+    }
+
+    pub async fn ensure_indexes(&self) -> Result<()> {
+        let index = IndexModel::builder()
+            .keys(doc! { "bot_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.bots.create_index(index, None).await.map(|_| ())
+    }
+
+    // `seated` dışındaki botlardan en fazla `count` tanesi; yetmezse `strategies` arasından rastgele
+    // stratejiyle yeni botlar oluşturulur
+    pub async fn pick_idle(&self, seated: &[i64], count: usize, strategies: &[BotStrategy], now: i64) -> Result<Vec<Bot>> {
+        let options = FindOptions::builder().limit(count as i64).build();
+        let mut bots: Vec<Bot> = self.bots
+            .find(doc! { "bot_id": { "$nin": seated }, "strategy": { "$in": to_bson(strategies)? } }, options)
+            .await?
+            .try_collect()
+            .await?;
+
+        while bots.len() < count {
+            let bot = self.create(strategies, now).await?;
+            bots.push(bot);
+        }
+        Ok(bots)
+    }
+
+    pub async fn find_by_ids(&self, bot_ids: &[i64]) -> Result<Vec<Bot>> {
+        self.bots.find(doc! { "bot_id": { "$in": bot_ids } }, None).await?.try_collect().await
+    }
+
+    async fn create(&self, strategies: &[BotStrategy], now: i64) -> Result<Bot> {
+        let seq = self.next_seq().await?;
+        let strategy = strategies[rand::random::<usize>() % strategies.len()];
+        let bot = Bot { bot_id: -seq, name: format!("Bot {}", seq), strategy, created_at: now };
+        self.bots.insert_one(&bot, None).await?;
+        Ok(bot)
+    }
+
+    // Sayaç 1'den başlar; bot id'si sayacın negatifidir
+    async fn next_seq(&self) -> Result<i64> {
+        let options = FindOneAndUpdateOptions::builder().upsert(true).return_document(ReturnDocument::After).build();
+        let counter = self.counters
+            .find_one_and_update(doc! { "_id": "bot_id" }, doc! { "$inc": { "seq": 1_i64 } }, options)
+            .await?;
+        Ok(counter.and_then(|c| c.get_i64("seq").ok()).unwrap_or(1))
+    }
+}
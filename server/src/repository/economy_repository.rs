@@ -0,0 +1,22 @@
+use mongodb::{Client, Collection};
+use mongodb::bson::doc;
+use mongodb::error::Result;
+use crate::models::economy::{EconomyConfig, ECONOMY_CONFIG_ID};
+
+// Ekonomi belgesini yalnızca okur; belgeyi API oluşturur ve yönetir
+#[derive(Clone)]
+pub struct EconomyRepository {
+    collection: Collection<EconomyConfig>,
+}
+
+impl EconomyRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("economy");
+        let collection = db.collection::<EconomyConfig>("config");
+        EconomyRepository { collection }
+    }
+
+    pub async fn load(&self) -> Result<Option<EconomyConfig>> {
+        self.collection.find_one(doc! { "_id": ECONOMY_CONFIG_ID }, None).await
+    }
+}
@@ -0,0 +1,31 @@
+use mongodb::{Client, Collection};
+use mongodb::bson::{doc, to_bson};
+use mongodb::error::Result;
+use crate::models::ledger::{LedgerTransaction, Reason};
+
+#[derive(Clone)]
+pub struct LedgerRepository {
+    collection: Collection<LedgerTransaction>,
+}
+
+impl LedgerRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("ledger");
+        let collection = db.collection::<LedgerTransaction>("transactions");
+        LedgerRepository { collection }
+    }
+
+    pub async fn record(&self, transaction: LedgerTransaction) -> Result<()> {
+        self.collection.insert_one(transaction, None).await.map(|_| ())
+    }
+
+    // Hesap için bu nedenle ve referansla bir hareket yazılmış mı (iadeleri tekrarlamamak için)
+    pub async fn exists(&self, reason: Reason, reference: &str, account: &str) -> Result<bool> {
+        let filter = doc! {
+            "reason": to_bson(&reason)?,
+            "reference": reference,
+            "entries.account": account,
+        };
+        Ok(self.collection.count_documents(filter, None).await? > 0)
+    }
+}
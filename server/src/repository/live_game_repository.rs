@@ -1,27 +1,64 @@
-use mongodb::{Client, Collection};
+use mongodb::{Client, Collection, IndexModel};
 use mongodb::bson::doc;
+use mongodb::error::{ErrorKind, Result, WriteFailure};
+use mongodb::options::{IndexOptions, ReplaceOptions};
+use futures_util::TryStreamExt;
+use crate::models::livegame::{LiveGame, VoidRequest, VoidedGame};
 
+// Süren oyunların checkpoint'leri; oyun sonuçlanınca ya da iptal edilince silinir
+// (kalıcı kayıt games.game_results ya da games.voided_games'tedir)
+#[derive(Clone)]
 pub struct LiveGameRepository {
     collection: Collection<LiveGame>,
+    void_requests: Collection<VoidRequest>,
+    voided: Collection<VoidedGame>,
 }
 
 impl LiveGameRepository {
     pub fn new(client: &Client) -> Self {
         let collection = client.database("live_games").collection("live_games");
-        Self { collection }
+        let void_requests = client.database("live_games").collection("void_requests");
+        let voided = client.database("games").collection("voided_games");
+        Self { collection, void_requests, voided }
     }
 
-    pub async fn create_game(&self, game: LiveGame) -> Result<(), Box<dyn std::error::Error>> {
-        self.collection.insert_one(game, None).await?;
-        Ok(())
+    pub async fn ensure_indexes(&self) -> Result<()> {
+        let index = IndexModel::builder()
+            .keys(doc! { "game_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        self.collection.create_index(index, None).await.map(|_| ())
     }
 
-    pub async fn update_game(&self, game_id: &str, rolls: Vec<Vec<i32>>, winner_id: Option<i64>) -> Result<(), Box<dyn std::error::Error>> {
-        self.collection.update_one(
-            doc! { "game_id": game_id },
-            doc! { "$set": { "rolls": rolls, "winner_id": winner_id } },
-            None
-        ).await?;
-        Ok(())
+    // Oyunun son halini yazar. Kayıttaki sürüm daha yeniyse (geç kalan bir yazma) hiçbir şey yazılmaz.
+    pub async fn save(&self, game: &LiveGame) -> Result<()> {
+        let filter = doc! { "game_id": &game.game_id, "version": { "$lt": game.version } };
+        let options = ReplaceOptions::builder().upsert(true).build();
+        match self.collection.replace_one(filter, game, options).await {
+            Ok(_) => Ok(()),
+            // Daha yeni sürüm var: filtre eşleşmedi ve upsert benzersiz game_id'ye takıldı
+            Err(e) if matches!(e.kind.as_ref(), ErrorKind::Write(WriteFailure::WriteError(w)) if w.code == 11000) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<LiveGame>> {
+        self.collection.find(None, None).await?.try_collect().await
+    }
+
+    pub async fn remove(&self, game_id: &str) -> Result<()> {
+        self.collection.delete_one(doc! { "game_id": game_id }, None).await.map(|_| ())
+    }
+
+    pub async fn pending_void_requests(&self) -> Result<Vec<VoidRequest>> {
+        self.void_requests.find(None, None).await?.try_collect().await
+    }
+
+    pub async fn remove_void_request(&self, game_id: &str) -> Result<()> {
+        self.void_requests.delete_one(doc! { "game_id": game_id }, None).await.map(|_| ())
+    }
+
+    pub async fn record_voided(&self, voided: &VoidedGame) -> Result<()> {
+        self.voided.insert_one(voided, None).await.map(|_| ())
     }
 }
@@ -1,3 +1,8 @@
 pub mod user_repository;
 pub mod salon_repository;
-pub mod table_repository;
\ No newline at end of file
+pub mod table_repository;
+pub mod ledger_repository;
+pub mod tap_flag_repository;
+pub mod economy_repository;
+pub mod live_game_repository;
+pub mod bot_repository;
@@ -4,6 +4,7 @@ use mongodb::bson::to_bson;
 use mongodb::error::Result;
 use futures_util::TryStreamExt; // try_collect için gerekli import
 use crate::models::salon::Salon;
+use crate::models::table::{GameState, Player};
 
 #[derive(Clone)] // Clone trait'ini ekliyoruz
 pub struct SalonRepository {
@@ -42,6 +43,75 @@ impl SalonRepository {
         let salons: Vec<Salon> = cursor.try_collect().await?; // Cursor'u Vec<Salon>'a dönüştür
         Ok(salons)
     }
+    // Masanın durumunu yalnızca hâlâ `from` ise `to` yapar; değiştiyse true döner
+    pub async fn set_table_state(&self, salon_id: i32, table_id: i32, from: GameState, to: GameState) -> mongodb::error::Result<bool> {
+        let filter = doc! {
+            "salon_id": salon_id,
+            "tables": { "$elemMatch": { "table_id": table_id, "game_state": from.to_string() } }
+        };
+        let update = doc! { "$set": { "tables.$.game_state": to.to_string() } };
+        let result = self.collection.update_one(filter, update, None).await?;
+        Ok(result.modified_count == 1)
+    }
+
+    // Masayı boşaltır ve yeni oyun için beklemeye alır; masa hâlâ `from` durumundaysa
+    pub async fn reset_table(&self, salon_id: i32, table_id: i32, from: GameState) -> mongodb::error::Result<bool> {
+        let filter = doc! {
+            "salon_id": salon_id,
+            "tables": { "$elemMatch": { "table_id": table_id, "game_state": from.to_string() } }
+        };
+        let update = doc! { "$set": {
+            "tables.$.players": [],
+            "tables.$.game_state": GameState::Waiting.to_string(),
+            "tables.$.fill_with_bots": false
+        } };
+        let result = self.collection.update_one(filter, update, None).await?;
+        Ok(result.modified_count == 1)
+    }
+
+    // Botları beklemedeki masanın boş koltuklarına oturtur; botlar ödeme yapmaz, hazır sayılır.
+    // Masadaki oyuncu sayısı okunduğundan beri değiştiyse (biri oturdu ya da kalktı) hiçbir şey yapmaz.
+    pub async fn seat_bots(&self, salon_id: i32, table_id: i32, seated: usize, bot_ids: &[i64]) -> mongodb::error::Result<bool> {
+        let filter = doc! {
+            "salon_id": salon_id,
+            "tables": { "$elemMatch": {
+                "table_id": table_id,
+                "game_state": GameState::Waiting.to_string(),
+                "players": { "$size": seated as i64 }
+            }}
+        };
+        let bots = bot_ids.iter().map(|bot_id| to_bson(&Player {
+            player_id: *bot_id,
+            is_active: true,
+            has_paid: true,
+            dice_rolls: vec![],
+        })).collect::<std::result::Result<Vec<_>, _>>()?;
+        let update = doc! {
+            "$push": { "tables.$.players": { "$each": bots } },
+            "$set": { "tables.$.fill_with_bots": false }
+        };
+        let result = self.collection.update_one(filter, update, None).await?;
+        Ok(result.modified_count == 1)
+    }
+
+    // Masada insan oyuncu kalmadıysa botları kaldırır ve masayı beklemeye alır
+    pub async fn remove_bots(&self, salon_id: i32, table_id: i32) -> mongodb::error::Result<bool> {
+        let filter = doc! {
+            "salon_id": salon_id,
+            "tables": { "$elemMatch": {
+                "table_id": table_id,
+                "game_state": { "$in": [GameState::Waiting.to_string(), GameState::Ready.to_string()] },
+                "players": { "$not": { "$elemMatch": { "player_id": { "$gt": 0_i64 } } } }
+            }}
+        };
+        let update = doc! {
+            "$pull": { "tables.$.players": { "player_id": { "$lt": 0_i64 } } },
+            "$set": { "tables.$.game_state": GameState::Waiting.to_string(), "tables.$.fill_with_bots": false }
+        };
+        let result = self.collection.update_one(filter, update, None).await?;
+        Ok(result.modified_count == 1)
+    }
+
     pub fn get_collection(&self) -> &Collection<Salon> {
         &self.collection
     }
@@ -0,0 +1,20 @@
+use mongodb::{Client, Collection};
+use mongodb::error::Result;
+use crate::models::tap_flag::TapFlag;
+
+#[derive(Clone)]
+pub struct TapFlagRepository {
+    collection: Collection<TapFlag>,
+}
+
+impl TapFlagRepository {
+    pub fn new(client: &Client) -> Self {
+        let db = client.database("users");
+        let collection = db.collection::<TapFlag>("tap_flags");
+        TapFlagRepository { collection }
+    }
+
+    pub async fn record(&self, flag: TapFlag) -> Result<()> {
+        self.collection.insert_one(flag, None).await.map(|_| ())
+    }
+}
@@ -1,6 +1,8 @@
 use mongodb::{Client, bson::doc};
 use mongodb::error::Result;
+use mongodb::options::{FindOneAndUpdateOptions, ReturnDocument};
 use crate::models::user::User;
+use crate::services::tap_accounting::EnergyPool;
 
 #[derive(Clone)]  // Clone trait'ini ekledik
 pub struct UserRepository {
@@ -37,20 +39,74 @@ impl UserRepository {
     }
     
     
-    pub async fn update_click_score(&self, telegram_id: i64, click_power: i32) -> Result<Option<User>> {
+    // Tıklama sonucunu yazar: enerji durumu ve click_score birlikte güncellenir.
+    // Belgedeki enerji `stored` (energy, energy_updated_at) okunduğundan beri değişmediyse uygulanır; aynı kullanıcının
+    // iki bağlantısı aynı enerjiyi iki kez harcayamaz. Eşleşme yoksa `None` döner ve çağıran yeniden okur.
+    pub async fn apply_taps(
+        &self,
+        telegram_id: i64,
+        stored: (Option<i32>, Option<i64>),
+        energy: EnergyPool,
+        score_delta: i32,
+    ) -> Result<Option<User>> {
+        let filter = doc! { "telegram_id": telegram_id, "energy": stored.0, "energy_updated_at": stored.1 };
+        let update = vec![doc! {
+            "$set": {
+                "energy": energy.energy,
+                "energy_updated_at": energy.updated_at,
+                "click_score": { "$add": [{ "$ifNull": ["$click_score", 0] }, score_delta] },
+            }
+        }];
+        let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+        self.collection.find_one_and_update(filter, update, options).await
+    }
+
+    // Süresi `now` (Unix saniye) itibarıyla dolmuş boost'ları kaldırır; etkilenen kullanıcı sayısını döner.
+    // Boost çevrimdışıyken bittiyse, pasif kazanç boost'lu süreyi sayabilsin diye kullanıcının bir sonraki
+    // birikimine (`last_seen` bitişi geçene) kadar tutulur. Hiç bağlanmamış kullanıcıda beklenecek birikim yoktur.
+    pub async fn expire_boosts(&self, now: i64) -> Result<u64> {
+        let ends_at = doc! { "$add": ["$boost.start_time", { "$multiply": ["$boost.duration_days", 86400] }] };
+        let filter = doc! {
+            "boost": { "$type": "object" },
+            "$expr": { "$and": [
+                { "$lte": [ends_at.clone(), now] },
+                { "$or": [
+                    { "$eq": [{ "$ifNull": ["$last_seen", null] }, null] },
+                    { "$gte": ["$last_seen", ends_at] },
+                ] },
+            ] },
+        };
+        let update = doc! { "$set": { "boost": null } };
+        let result = self.collection.update_many(filter, update, None).await?;
+        Ok(result.modified_count)
+    }
+
+    // Pasif kazancı yatırır ve `last_seen`'i ilerletir. `last_seen` okunduğundan beri değiştiyse
+    // (başka bir bağlantı kazancı zaten yatırdıysa) hiçbir şey yazılmaz ve false döner.
+    pub async fn credit_passive_income(&self, telegram_id: i64, expected_last_seen: Option<i64>, now: i64, earned: i32) -> Result<bool> {
+        let filter = doc! { "telegram_id": telegram_id, "last_seen": expected_last_seen };
+        let update = vec![doc! {
+            "$set": {
+                "last_seen": now,
+                "click_score": { "$add": [{ "$ifNull": ["$click_score", 0] }, earned] },
+            }
+        }];
+        let result = self.collection.update_one(filter, update, None).await?;
+        Ok(result.modified_count == 1)
+    }
+
+    // Game pass ekler; alan eksik ya da null ise sıfırdan başlatır. Kullanıcı yoksa false döner.
+    pub async fn credit_game_pass(&self, telegram_id: i64, amount: i32) -> Result<bool> {
         let filter = doc! { "telegram_id": telegram_id };
-    
-        if let Some(mut user) = self.collection.find_one(filter.clone(), None).await? {
-            // Eğer click_score Some ise değeri güncelle, değilse click_power ile başlat
-            user.click_score = Some(user.click_score.unwrap_or(0) + click_power);
-    
-            // Veritabanında click_score'u güncelle
-            self.collection.update_one(filter, doc! { "$set": { "click_score": user.click_score } }, None).await?;
-            
-            Ok(Some(user))
-        } else {
-            Ok(None)
-        }
+        let update = vec![doc! {
+            "$set": { "game_pass": { "$add": [{ "$ifNull": ["$game_pass", 0] }, amount] } }
+        }];
+        let result = self.collection.update_one(filter, update, None).await?;
+        Ok(result.matched_count == 1)
+    }
+
+    pub async fn touch_last_seen(&self, telegram_id: i64, now: i64) -> Result<()> {
+        let filter = doc! { "telegram_id": telegram_id };
+        self.collection.update_one(filter, doc! { "$set": { "last_seen": now } }, None).await.map(|_| ())
     }
-    
 }
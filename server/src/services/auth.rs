@@ -0,0 +1,19 @@
+// services/auth.rs
+// Canlı oyun ve tıklama soketlerinde kimlik, API'nin /auth ile verdiği JWT'den okunur (aynı jwt_secret).
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+// api/src/jwt/claims.rs ile aynı biçim
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String, // telegram_id
+    pub exp: usize,
+}
+
+// Geçerli bir token için telegram_id döner
+pub fn verify_token(token: &str, secret: &str) -> Result<i64, String> {
+    let claims = decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default())
+        .map(|data| data.claims)
+        .map_err(|_| "Invalid token".to_string())?;
+    claims.sub.parse::<i64>().map_err(|_| "Invalid token subject".to_string())
+}
@@ -0,0 +1,131 @@
+// services/boost_engine.rs
+// Boost seviyelerinin tıklama kazancına etkisi ve süresi dolan boost'ların temizlenmesi.
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use mongodb::Client;
+use tokio::time::{self, Duration};
+use crate::config::app_config::AppConfig;
+use crate::models::user::User;
+use crate::repository::user_repository::UserRepository;
+
+// Seviye -> kazanç çarpanı (yüzde). Boost yoksa ya da süresi dolmuşsa çarpan %100'dür.
+#[derive(Debug, Clone)]
+pub struct BoostEngine {
+    multipliers_pct: Vec<i64>, // [level 1, level 2, level 3, ...]
+}
+
+impl BoostEngine {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(config.boost_multipliers_pct.clone())
+    }
+
+    pub fn new(multipliers_pct: Vec<i64>) -> Self {
+        BoostEngine { multipliers_pct }
+    }
+
+    // `now` (Unix saniye) anında kullanıcının kazanç çarpanı
+    pub fn multiplier_pct(&self, user: &User, now: i64) -> i64 {
+        user.boost.as_ref()
+            .filter(|boost| boost.is_active(now))
+            .map_or(100, |boost| self.level_multiplier_pct(boost.level))
+    }
+
+    // Seviyenin çarpanı; tanımsız seviyeler kazancı değiştirmez
+    pub fn level_multiplier_pct(&self, level: i32) -> i64 {
+        usize::try_from(level).ok()
+            .and_then(|level| level.checked_sub(1))
+            .and_then(|index| self.multipliers_pct.get(index).copied())
+            .unwrap_or(100)
+    }
+
+    // Temel kazanca boost çarpanını uygular (aşağı yuvarlanır)
+    pub fn apply(&self, user: &User, base_earnings: i64, now: i64) -> i64 {
+        base_earnings.saturating_mul(self.multiplier_pct(user, now)) / 100
+    }
+
+    // Etkin boost'un kalan süresi (saniye)
+    pub fn remaining_secs(user: &User, now: i64) -> Option<i64> {
+        user.boost.as_ref()
+            .filter(|boost| boost.is_active(now))
+            .map(|boost| boost.ends_at() - now)
+    }
+}
+
+pub fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64
+}
+
+// Süresi dolan boost'ları belirli aralıklarla kullanıcı belgelerinden kaldırır
+pub async fn run_boost_sweeper(mongo_client: Client, config: Arc<AppConfig>) {
+    let user_repo = UserRepository::new(&mongo_client);
+    let mut interval = time::interval(Duration::from_secs(config.boost_sweep_interval_secs));
+
+    loop {
+        interval.tick().await;
+        match user_repo.expire_boosts(now_secs()).await {
+            Ok(0) => {}
+            Ok(expired) => println!("{} kullanıcının boost süresi doldu", expired),
+            Err(e) => eprintln!("Boost süpürücü hatası: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::Boost;
+
+    const DAY: i64 = 86_400;
+
+    fn engine() -> BoostEngine {
+        BoostEngine::new(vec![200, 300, 500])
+    }
+
+    fn boosted(level: i32) -> User {
+        let mut user = User::for_test(1);
+        user.boost = Some(Boost { level, start_time: 1_000, duration_days: 1 });
+        user
+    }
+
+    #[test]
+    fn levels_map_to_configured_multipliers() {
+        let engine = engine();
+        assert_eq!(engine.level_multiplier_pct(1), 200);
+        assert_eq!(engine.level_multiplier_pct(2), 300);
+        assert_eq!(engine.level_multiplier_pct(3), 500);
+        for level in [4, 0, -1, i32::MIN] {
+            assert_eq!(engine.level_multiplier_pct(level), 100, "level {}", level);
+        }
+    }
+
+    #[test]
+    fn multiplier_ends_with_the_boost() {
+        let (engine, user) = (engine(), boosted(2));
+        assert_eq!(engine.multiplier_pct(&user, 1_000), 300);
+        assert_eq!(engine.multiplier_pct(&user, 1_000 + DAY - 1), 300);
+        assert_eq!(engine.multiplier_pct(&user, 1_000 + DAY), 100);
+        assert_eq!(engine.multiplier_pct(&User::for_test(1), 1_000), 100);
+    }
+
+    #[test]
+    fn apply_rounds_down() {
+        let engine = BoostEngine::new(vec![150]);
+        let user = boosted(1);
+        assert_eq!(engine.apply(&user, 3, 2_000), 4);
+        assert_eq!(engine.apply(&user, 3, 1_000 + DAY), 3);
+        assert_eq!(engine.apply(&user, i64::MAX, 2_000), i64::MAX / 100);
+    }
+
+    #[test]
+    fn remaining_secs_counts_down_to_expiry() {
+        let user = boosted(1);
+        assert_eq!(BoostEngine::remaining_secs(&user, 1_000), Some(DAY));
+        assert_eq!(BoostEngine::remaining_secs(&user, 1_000 + DAY - 1), Some(1));
+        assert_eq!(BoostEngine::remaining_secs(&user, 1_000 + DAY), None);
+        assert_eq!(BoostEngine::remaining_secs(&User::for_test(1), 1_000), None);
+    }
+}
@@ -0,0 +1,90 @@
+// services/bot_players.rs
+// Bot oyuncuların zarları. Zar borcu olan her bot, stratejisinin (models/bot.rs) seçtiği süre kadar
+// bekledikten sonra zarını insan oyuncular gibi `GameEvent::Roll` ile atar; roll-off turlarında da
+// aynı şekilde. Bekleme süresi her zar için yeniden seçilir. Botun zarıyla biten oyunlar burada
+// sonuçlandırılır.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use mongodb::Client;
+use tokio::time::{self, Duration, Instant};
+use crate::config::app_config::AppConfig;
+use crate::models::bot::BotStrategy;
+use crate::models::livegame::{GameState, ParticipantKind};
+use crate::repository::bot_repository::BotRepository;
+use crate::repository::live_game_repository::LiveGameRepository;
+use crate::services::economy_cache::EconomyCache;
+use crate::services::game_engine::{finished_outcome, GameEvent};
+use crate::services::game_rooms::{RoomKey, SharedRooms};
+use crate::services::live_game_socket_services::{drive_game, finish_game};
+use crate::services::table_lifecycle::ActiveGames;
+
+// Botun sıradaki zarı: (oyun, bot, nonce)
+type PendingRoll = (String, i64, usize);
+
+pub async fn run_bot_players(
+    mongo_client: Client,
+    config: Arc<AppConfig>,
+    economy: Arc<EconomyCache>,
+    active_games: ActiveGames,
+    rooms: SharedRooms,
+    checkpoints: LiveGameRepository,
+) {
+    let bot_repo = BotRepository::new(&mongo_client);
+    let rules = config.turn_rules();
+    let mut strategies: HashMap<i64, BotStrategy> = HashMap::new();
+    let mut due: HashMap<PendingRoll, Instant> = HashMap::new();
+    let mut interval = time::interval(Duration::from_millis(250));
+
+    loop {
+        interval.tick().await;
+        let pending: Vec<(RoomKey, PendingRoll)> = {
+            let games = active_games.lock().await;
+            games.iter()
+                .filter(|(_, game)| game.state == GameState::Started)
+                .flat_map(|(key, game)| {
+                    game.players.iter().enumerate()
+                        .filter(|(index, p)| p.kind == ParticipantKind::Bot && game.owes_roll(*index))
+                        .map(|(index, p)| (key.clone(), (game.game_id.clone(), p.player_id, game.next_nonce(index))))
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        };
+
+        let unknown: Vec<i64> = pending.iter().map(|(_, (_, bot_id, _))| *bot_id).filter(|id| !strategies.contains_key(id)).collect();
+        if !unknown.is_empty() {
+            match bot_repo.find_by_ids(&unknown).await {
+                Ok(bots) => {
+                    strategies.extend(bots.into_iter().map(|bot| (bot.bot_id, bot.strategy)));
+                    // Kaydı olmayan botlar varsayılan stratejiyle oynar
+                    for bot_id in unknown {
+                        strategies.entry(bot_id).or_default();
+                    }
+                }
+                Err(e) => eprintln!("Bot stratejileri okunamadı: {:?}", e),
+            }
+        }
+
+        // Atılmış ya da oyunu biten zarların bekleme süreleri bırakılır
+        due.retain(|roll, _| pending.iter().any(|(_, pending_roll)| pending_roll == roll));
+
+        let now = Instant::now();
+        for (key, roll) in pending {
+            let strategy = strategies.get(&roll.1).copied().unwrap_or_default();
+            let at = *due.entry(roll.clone()).or_insert_with(|| now + strategy.roll_delay(rules.roll_timeout_secs));
+            if now < at {
+                continue;
+            }
+            due.remove(&roll);
+
+            match drive_game(&active_games, &key, &rules, GameEvent::Roll { player_id: roll.1 }, &rooms, &checkpoints).await {
+                Ok((events, game)) => {
+                    if let Some(outcome) = finished_outcome(&events) {
+                        finish_game(&game, outcome, &mongo_client, &economy.current(), &rooms, active_games.clone(), &checkpoints).await;
+                    }
+                }
+                Err(e) => eprintln!("Bot {} oyun {} için zar atamadı: {}", roll.1, roll.0, e.message()),
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// services/economy_cache.rs
+// API'nin yönettiği ekonomi belgesinin bellekteki kopyası; belirli aralıklarla yenilenir,
+// böylece yönetici değişiklikleri sunucu yeniden başlatılmadan bir sonraki oyunda geçerli olur.
+
+use std::sync::{Arc, RwLock};
+use mongodb::Client;
+use tokio::time::{self, Duration};
+use crate::models::economy::EconomyConfig;
+use crate::repository::economy_repository::EconomyRepository;
+
+pub struct EconomyCache {
+    repository: EconomyRepository,
+    current: RwLock<Arc<EconomyConfig>>,
+}
+
+impl EconomyCache {
+    // Belge okunamazsa başlangıç değerleriyle açılır; yenileyici sonradan yakalar
+    pub async fn load(mongo_client: &Client) -> Self {
+        let repository = EconomyRepository::new(mongo_client);
+        let current = match repository.load().await {
+            Ok(Some(config)) => config,
+            Ok(None) => EconomyConfig::default(),
+            Err(e) => {
+                eprintln!("Ekonomi yapılandırması okunamadı, varsayılanlar kullanılıyor: {:?}", e);
+                EconomyConfig::default()
+            }
+        };
+        EconomyCache { repository, current: RwLock::new(Arc::new(current)) }
+    }
+
+    pub fn current(&self) -> Arc<EconomyConfig> {
+        self.current.read().expect("economy lock poisoned").clone()
+    }
+
+    async fn reload(&self) -> mongodb::error::Result<()> {
+        if let Some(latest) = self.repository.load().await? {
+            if latest.version > self.current().version {
+                println!("Ekonomi yapılandırması yenilendi (sürüm {})", latest.version);
+                *self.current.write().expect("economy lock poisoned") = Arc::new(latest);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn run_economy_reloader(economy: Arc<EconomyCache>, interval_secs: u64) {
+    let mut interval = time::interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(e) = economy.reload().await {
+            eprintln!("Ekonomi yapılandırması yenilenemedi: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,468 @@
+// services/game_engine.rs
+// Zar oyununun kuralları tek bir saf durum makinesinde toplanır. Canlı oyun soketi, zar süresi ve
+// bot oyuncular oyunu yalnızca `apply` ile olay (GameEvent) vererek ilerletir; motor oyunu
+// günceller ve olanları (EngineEvent) döner. Motorda ağ, veritabanı ya da saat yoktur: zaman
+// olayla birlikte gelir; mesajları göndermek, checkpoint yazmak ve ödemeler çağıranın işidir.
+//
+// Botlar da birer katılımcıdır (ParticipantKind::Bot): bağlantıları ve zar süreleri yoktur; zarlarını
+// insanlar gibi `GameEvent::Roll` ile atarlar (services/bot_players.rs).
+
+use crate::config::app_config::RollTimeoutAction;
+use crate::models::livegame::{GameState, LiveGame, ParticipantKind, VoidReason};
+use crate::models::salon::TieRule;
+use crate::services::tie_break::{placings, roll_off_players, tied_for_first, Scorecard, MAX_ROLL_OFF_ROUNDS};
+
+pub const MAX_ROLLS: usize = 5; // Oyuncu başına zar
+
+// Sunucu yapılandırmasından gelen süre kuralları (AppConfig::turn_rules)
+#[derive(Debug, Clone, Copy)]
+pub struct TurnRules {
+    pub roll_timeout_secs: u64,
+    pub roll_warning_secs: u64,
+    pub reconnect_grace_secs: u64,
+    pub timeout_action: RollTimeoutAction,
+}
+
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    Roll { player_id: i64 },
+    SetClientSeed { player_id: i64, client_seed: String },
+    Disconnect { player_id: i64 },
+    Reconnect { player_id: i64 },
+    Resume,           // Sunucu yeniden başladı: insan oyuncular şu andan itibaren kopuk sayılır
+    Tick(TurnRules),  // Saniyede bir: zar süreleri ve bağlantı süreleri
+    Void(VoidReason),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineEvent {
+    Rolled { player_id: i64, roll: i32 },
+    ClientSeedAccepted { player_id: i64 },
+    RollCountdown { player_id: i64, seconds_left: u64 },
+    RollTimedOut { player_id: i64, roll: i32 },
+    PlayerDisconnected { player_id: i64 },
+    PlayerReconnected { player_id: i64 },
+    PlayerForfeited { player_id: i64 }, // Süresinde dönmedi, kalan zarları 0 sayıldı
+    RollOffStarted { round: usize, players: Vec<i64> },
+    Finished(GameOutcome),              // Oyun Completed oldu; sonuçlandırılmalı
+    Abandoned,                          // Tüm insan oyuncular kopup dönmedi; oyun iptal edilmeli
+    Voided(VoidReason),
+}
+
+impl EngineEvent {
+    // Oyunun checkpoint'e yazılması gereken bir değişiklik mi
+    pub fn changes_game(&self) -> bool {
+        !matches!(self, EngineEvent::RollCountdown { .. } | EngineEvent::Abandoned)
+    }
+
+    // Oyuncuların zarları değişti mi
+    pub fn changes_rolls(&self) -> bool {
+        matches!(
+            self,
+            EngineEvent::Rolled { .. } | EngineEvent::RollTimedOut { .. } | EngineEvent::PlayerForfeited { .. } | EngineEvent::RollOffStarted { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameOutcome {
+    pub placings: Vec<Vec<i64>>, // En iyi yer önce; bir gruptaki oyuncular yeri paylaşır
+    pub tied_for_first: bool,    // Birincilik eşit zar toplamıyla bitti (kural uygulandı)
+}
+
+impl GameOutcome {
+    pub fn winner_ids(&self) -> &[i64] {
+        &self.placings[0]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineError {
+    NotStarted,
+    NotInGame,
+    NoRollsLeft,
+    AlreadyRolled,
+}
+
+impl EngineError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            EngineError::NotStarted => "Oyun sürmüyor",
+            EngineError::NotInGame => "Oyuncu bu oyunda değil",
+            EngineError::NoRollsLeft => "Zar hakkı kalmadı",
+            EngineError::AlreadyRolled => "Client seed ilk zardan önce gönderilmeli",
+        }
+    }
+}
+
+// Olayı oyuna uygular. Oyun yalnızca `Started` iken ilerler; hata durumunda oyun değişmez.
+pub fn apply(game: &mut LiveGame, now: u64, event: GameEvent) -> Result<Vec<EngineEvent>, EngineError> {
+    if game.state != GameState::Started {
+        return Err(EngineError::NotStarted);
+    }
+
+    let mut events = vec![];
+    match event {
+        GameEvent::Roll { player_id } => {
+            let index = index_of(game, player_id)?;
+            if !game.owes_roll(index) {
+                return Err(EngineError::NoRollsLeft);
+            }
+            // Zar, açıklanacak seed'lerden ve zar sırasından (nonce) türetilir; derive_roll her zaman 1..=6 döner
+            let roll = game.fairness.roll(&player_id.to_string(), game.next_nonce(index));
+            record_roll(game, index, roll);
+            game.players[index].last_roll_time = Some(now);
+            events.push(EngineEvent::Rolled { player_id, roll });
+        }
+        GameEvent::SetClientSeed { player_id, client_seed } => {
+            let index = index_of(game, player_id)?;
+            if game.next_nonce(index) > 0 {
+                return Err(EngineError::AlreadyRolled);
+            }
+            game.fairness.set_client_seed(&player_id.to_string(), client_seed);
+            events.push(EngineEvent::ClientSeedAccepted { player_id });
+        }
+        GameEvent::Disconnect { player_id } => {
+            let index = index_of(game, player_id)?;
+            let owes_roll = game.owes_roll(index);
+            let player = &mut game.players[index];
+            // Zarları bitmiş oyuncunun kopması oyunu etkilemez
+            if owes_roll && player.disconnected_at.is_none() {
+                player.is_active = false;
+                player.disconnected_at = Some(now);
+                events.push(EngineEvent::PlayerDisconnected { player_id });
+            }
+        }
+        GameEvent::Reconnect { player_id } => {
+            let index = index_of(game, player_id)?;
+            let player = &mut game.players[index];
+            // Zar süresi dönüşten itibaren yeniden başlar
+            if player.disconnected_at.take().is_some() {
+                player.is_active = true;
+                player.last_roll_time = Some(now);
+                events.push(EngineEvent::PlayerReconnected { player_id });
+            }
+        }
+        GameEvent::Resume => {
+            for player in game.players.iter_mut().filter(|p| p.kind == ParticipantKind::Human) {
+                player.is_active = false;
+                player.disconnected_at = Some(now);
+                player.last_roll_time = Some(now);
+                events.push(EngineEvent::PlayerDisconnected { player_id: player.player_id });
+            }
+        }
+        GameEvent::Tick(rules) => tick(game, &rules, now, &mut events),
+        GameEvent::Void(reason) => {
+            game.state = GameState::Voided;
+            game.void_reason = Some(reason.clone());
+            events.push(EngineEvent::Voided(reason));
+        }
+    }
+
+    if game.state == GameState::Started && !events.contains(&EngineEvent::Abandoned) {
+        settle_if_done(game, now, &mut events);
+    }
+    if events.iter().any(EngineEvent::changes_game) {
+        game.touch();
+    }
+    Ok(events)
+}
+
+// Zarları bitmiş oyunun sıralaması; Completed checkpoint'inden sonucu yeniden kurmak için de kullanılır
+pub fn outcome(game: &LiveGame) -> GameOutcome {
+    let cards = scorecards(game);
+    GameOutcome {
+        placings: placings(&cards, game.tie_rule),
+        tied_for_first: tied_for_first(&cards),
+    }
+}
+
+// Olaylar oyunu bitirdiyse sonucu
+pub fn finished_outcome(events: &[EngineEvent]) -> Option<&GameOutcome> {
+    events.iter().find_map(|event| match event {
+        EngineEvent::Finished(outcome) => Some(outcome),
+        _ => None,
+    })
+}
+
+impl LiveGame {
+    // Oyuncunun atması gereken zar kaldı mı: oyunun zarları ya da süren roll-off turunun zarı
+    pub fn owes_roll(&self, index: usize) -> bool {
+        let player = &self.players[index];
+        player.rolls.len() < self.max_rolls
+            || (self.roll_off.contains(&player.player_id) && player.tiebreak_rolls.len() < self.roll_off_round)
+    }
+
+    // Oyuncunun sıradaki zarının nonce'u; roll-off zarları oyunun zarlarının ardından gelir
+    pub fn next_nonce(&self, index: usize) -> usize {
+        let player = &self.players[index];
+        player.rolls.len() + player.tiebreak_rolls.len()
+    }
+
+    fn all_rolled(&self) -> bool {
+        (0..self.players.len()).all(|index| !self.owes_roll(index))
+    }
+}
+
+fn index_of(game: &LiveGame, player_id: i64) -> Result<usize, EngineError> {
+    game.players.iter().position(|p| p.player_id == player_id).ok_or(EngineError::NotInGame)
+}
+
+// Zarı oyuncunun sıradaki hakkına yazar
+fn record_roll(game: &mut LiveGame, index: usize, roll: i32) {
+    let max_rolls = game.max_rolls;
+    let player = &mut game.players[index];
+    if player.rolls.len() < max_rolls {
+        player.rolls.push(roll);
+    } else {
+        player.tiebreak_rolls.push(roll);
+    }
+}
+
+// Zar süreleri: kopuk oyuncunun süresi işlemez, `reconnect_grace_secs` içinde dönmezse kalan zarları
+// (roll-off zarı dahil) 0 sayılır; bağlı oyuncunun süresi dolunca `timeout_action` uygulanır
+fn tick(game: &mut LiveGame, rules: &TurnRules, now: u64, events: &mut Vec<EngineEvent>) {
+    let grace_expired = |disconnected_at: Option<u64>| disconnected_at.is_some_and(|at| now >= at + rules.reconnect_grace_secs);
+    let mut humans = game.players.iter().filter(|p| p.kind == ParticipantKind::Human).peekable();
+    if humans.peek().is_some() && humans.all(|p| grace_expired(p.disconnected_at)) {
+        events.push(EngineEvent::Abandoned);
+        return;
+    }
+
+    for index in 0..game.players.len() {
+        if game.players[index].kind == ParticipantKind::Bot || !game.owes_roll(index) {
+            continue;
+        }
+        let player_id = game.players[index].player_id;
+
+        if let Some(disconnected_at) = game.players[index].disconnected_at {
+            if now < disconnected_at + rules.reconnect_grace_secs {
+                continue;
+            }
+            let max_rolls = game.max_rolls;
+            let round = game.roll_off_round;
+            let in_roll_off = game.roll_off.contains(&player_id);
+            let player = &mut game.players[index];
+            player.rolls.resize(max_rolls, 0);
+            if in_roll_off && player.tiebreak_rolls.len() < round {
+                player.tiebreak_rolls.resize(round, 0);
+            }
+            events.push(EngineEvent::PlayerForfeited { player_id });
+            continue;
+        }
+
+        let player = &mut game.players[index];
+        let last_roll_time = *player.last_roll_time.get_or_insert(now);
+        let seconds_left = (last_roll_time + rules.roll_timeout_secs).saturating_sub(now);
+        if seconds_left > 0 {
+            if seconds_left <= rules.roll_warning_secs {
+                events.push(EngineEvent::RollCountdown { player_id, seconds_left });
+            }
+            continue;
+        }
+
+        player.last_roll_time = Some(now);
+        let roll = match rules.timeout_action {
+            RollTimeoutAction::AutoRoll => game.fairness.roll(&player_id.to_string(), game.next_nonce(index)),
+            RollTimeoutAction::Forfeit => 0,
+        };
+        record_roll(game, index, roll);
+        events.push(EngineEvent::RollTimedOut { player_id, roll });
+    }
+}
+
+// Tüm zarlar atıldıysa oyun biter; salonun kuralı roll_off ise ve ödül alan bir sırada eşitlik
+// varsa önce eşit oyuncular için yeni bir roll-off turu başlar
+fn settle_if_done(game: &mut LiveGame, now: u64, events: &mut Vec<EngineEvent>) {
+    if !game.all_rolled() {
+        return;
+    }
+
+    let roll_off = match game.tie_rule {
+        TieRule::RollOff if game.roll_off_round < MAX_ROLL_OFF_ROUNDS => {
+            roll_off_players(&placings(&scorecards(game), TieRule::RollOff), game.paid_places)
+        }
+        _ => vec![],
+    };
+    if roll_off.is_empty() {
+        game.state = GameState::Completed;
+        events.push(EngineEvent::Finished(outcome(game)));
+        return;
+    }
+
+    for player in game.players.iter_mut().filter(|p| roll_off.contains(&p.player_id)) {
+        player.last_roll_time = Some(now);
+    }
+    game.roll_off = roll_off;
+    game.roll_off_round += 1;
+    events.push(EngineEvent::RollOffStarted { round: game.roll_off_round, players: game.roll_off.clone() });
+}
+
+fn scorecards(game: &LiveGame) -> Vec<Scorecard<'_, i64>> {
+    game.players.iter().map(|p| Scorecard {
+        player_id: p.player_id,
+        rolls: &p.rolls,
+        tiebreak_rolls: &p.tiebreak_rolls,
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::livegame::Player;
+    use crate::services::provably_fair::Fairness;
+
+    const RULES: TurnRules = TurnRules {
+        roll_timeout_secs: 10,
+        roll_warning_secs: 3,
+        reconnect_grace_secs: 30,
+        timeout_action: RollTimeoutAction::AutoRoll,
+    };
+
+    fn player(player_id: i64, kind: ParticipantKind) -> Player {
+        Player {
+            player_id,
+            rolls: vec![],
+            is_active: true,
+            last_roll_time: Some(0),
+            disconnected_at: None,
+            tiebreak_rolls: vec![],
+            kind,
+        }
+    }
+
+    fn game(players: Vec<Player>) -> LiveGame {
+        LiveGame {
+            game_id: "game".to_string(),
+            players,
+            state: GameState::Started,
+            salon_id: "1".to_string(),
+            table_id: "1".to_string(),
+            fairness: Fairness::generate(),
+            entry_fee: 1,
+            version: 0,
+            void_reason: None,
+            tie_rule: TieRule::SplitPot,
+            paid_places: 1,
+            roll_off: vec![],
+            roll_off_round: 0,
+            max_rolls: MAX_ROLLS,
+        }
+    }
+
+    fn humans(ids: &[i64]) -> LiveGame {
+        game(ids.iter().map(|id| player(*id, ParticipantKind::Human)).collect())
+    }
+
+    #[test]
+    fn rolls_are_die_faces_and_limited_to_max_rolls() {
+        let mut game = humans(&[1, 2]);
+        for _ in 0..MAX_ROLLS {
+            let events = apply(&mut game, 1, GameEvent::Roll { player_id: 1 }).unwrap();
+            assert!(matches!(events[0], EngineEvent::Rolled { player_id: 1, roll } if (1..=6).contains(&roll)));
+        }
+        assert_eq!(game.players[0].rolls.len(), MAX_ROLLS);
+        assert_eq!(apply(&mut game, 1, GameEvent::Roll { player_id: 1 }), Err(EngineError::NoRollsLeft));
+        assert_eq!(apply(&mut game, 1, GameEvent::Roll { player_id: 3 }), Err(EngineError::NotInGame));
+    }
+
+    #[test]
+    fn client_seed_is_rejected_after_the_first_roll() {
+        let mut game = humans(&[1, 2]);
+        let seed = |player_id| GameEvent::SetClientSeed { player_id, client_seed: "seed".to_string() };
+        assert_eq!(apply(&mut game, 1, seed(1)), Ok(vec![EngineEvent::ClientSeedAccepted { player_id: 1 }]));
+
+        apply(&mut game, 1, GameEvent::Roll { player_id: 2 }).unwrap();
+        assert_eq!(apply(&mut game, 1, seed(2)), Err(EngineError::AlreadyRolled));
+        assert_eq!(game.fairness.client_seed("2"), "");
+    }
+
+    #[test]
+    fn disconnected_player_forfeits_after_the_grace_period() {
+        let mut game = humans(&[1, 2]);
+        apply(&mut game, 100, GameEvent::Disconnect { player_id: 1 }).unwrap();
+
+        // Süre dolmadan kopuk oyuncunun zar süresi işlemez
+        let events = apply(&mut game, 100 + RULES.reconnect_grace_secs - 1, GameEvent::Tick(RULES)).unwrap();
+        assert!(!events.iter().any(|e| matches!(e, EngineEvent::PlayerForfeited { .. } | EngineEvent::RollTimedOut { player_id: 1, .. })));
+        assert!(game.players[0].rolls.is_empty());
+
+        let events = apply(&mut game, 100 + RULES.reconnect_grace_secs, GameEvent::Tick(RULES)).unwrap();
+        assert!(events.contains(&EngineEvent::PlayerForfeited { player_id: 1 }));
+        assert_eq!(game.players[0].rolls, vec![0; MAX_ROLLS]);
+    }
+
+    #[test]
+    fn reconnecting_within_the_grace_period_keeps_the_rolls() {
+        let mut game = humans(&[1, 2]);
+        apply(&mut game, 100, GameEvent::Disconnect { player_id: 1 }).unwrap();
+        let events = apply(&mut game, 110, GameEvent::Reconnect { player_id: 1 }).unwrap();
+        assert_eq!(events, vec![EngineEvent::PlayerReconnected { player_id: 1 }]);
+        assert_eq!(game.players[0].last_roll_time, Some(110));
+
+        let events = apply(&mut game, 100 + RULES.reconnect_grace_secs, GameEvent::Tick(RULES)).unwrap();
+        assert!(!events.contains(&EngineEvent::PlayerForfeited { player_id: 1 }));
+    }
+
+    #[test]
+    fn game_is_abandoned_when_every_human_stays_away() {
+        let mut game = game(vec![player(1, ParticipantKind::Human), player(-1, ParticipantKind::Bot)]);
+        apply(&mut game, 100, GameEvent::Disconnect { player_id: 1 }).unwrap();
+
+        let events = apply(&mut game, 100 + RULES.reconnect_grace_secs, GameEvent::Tick(RULES)).unwrap();
+        assert_eq!(events, vec![EngineEvent::Abandoned]);
+        // İptal çağıranın işidir; motor oyunu bitirmez
+        assert_eq!(game.state, GameState::Started);
+    }
+
+    #[test]
+    fn timed_out_roll_is_auto_rolled_or_forfeited() {
+        let mut game = humans(&[1, 2]);
+        let events = apply(&mut game, RULES.roll_timeout_secs, GameEvent::Tick(RULES)).unwrap();
+        for player_id in [1, 2] {
+            assert!(events.iter().any(|e| matches!(e, EngineEvent::RollTimedOut { player_id: id, roll } if *id == player_id && (1..=6).contains(roll))));
+        }
+
+        let forfeit = TurnRules { timeout_action: RollTimeoutAction::Forfeit, ..RULES };
+        let mut game = humans(&[1, 2]);
+        let events = apply(&mut game, RULES.roll_timeout_secs, GameEvent::Tick(forfeit)).unwrap();
+        assert!(events.contains(&EngineEvent::RollTimedOut { player_id: 1, roll: 0 }));
+        assert_eq!(game.players[0].rolls, vec![0]);
+    }
+
+    #[test]
+    fn completed_game_is_finished_only_once() {
+        let mut game = humans(&[1, 2]);
+        let mut finished = 0;
+        for _ in 0..MAX_ROLLS {
+            for player_id in [1, 2] {
+                let events = apply(&mut game, 1, GameEvent::Roll { player_id }).unwrap();
+                finished += events.iter().filter(|e| matches!(e, EngineEvent::Finished(_))).count();
+            }
+        }
+        assert_eq!(finished, 1);
+        assert_eq!(game.state, GameState::Completed);
+
+        assert_eq!(apply(&mut game, 2, GameEvent::Roll { player_id: 1 }), Err(EngineError::NotStarted));
+        assert_eq!(apply(&mut game, 2, GameEvent::Tick(RULES)), Err(EngineError::NotStarted));
+        assert_eq!(apply(&mut game, 2, GameEvent::Void(VoidReason::ServerRestart)), Err(EngineError::NotStarted));
+    }
+
+    #[test]
+    fn roll_off_still_tied_after_the_last_round_finishes_the_game() {
+        let mut game = humans(&[1, 2]);
+        game.tie_rule = TieRule::RollOff;
+        game.roll_off = vec![1, 2];
+        game.roll_off_round = MAX_ROLL_OFF_ROUNDS;
+        for player in &mut game.players {
+            player.rolls = vec![3; MAX_ROLLS];
+            player.tiebreak_rolls = vec![4; MAX_ROLL_OFF_ROUNDS];
+        }
+
+        let events = apply(&mut game, 1, GameEvent::Tick(RULES)).unwrap();
+        let outcome = finished_outcome(&events).expect("oyun bitmeli");
+        assert_eq!(outcome.placings, vec![vec![1, 2]]);
+        assert!(outcome.tied_for_first);
+        assert!(!events.iter().any(|e| matches!(e, EngineEvent::RollOffStarted { .. })));
+    }
+}
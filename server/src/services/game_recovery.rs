@@ -0,0 +1,118 @@
+// services/game_recovery.rs
+// Açılışta live_games checkpoint'lerinden oyunlar geri yüklenir:
+//   Started    masa hâlâ aynı oyuncularla Started ise oyun sürdürülür (resume_games_on_restart).
+//              Tüm oyuncular kopuk sayılır; `reconnect_grace_secs` içinde join_table ile dönebilirler.
+//              Sürdürülemeyen oyun iptal edilir ve giriş ücretleri iade edilir.
+//   Voided     yarıda kalan iptal tamamlanır
+//   Completed  sonuçlandırılırken sunucu durmuş; sonuçlandırma yeniden çalıştırılır (ödenmiş ödüller ve
+//              itibar tekrarlanmaz), masa hâlâ bu oyundaysa boşaltılır ve checkpoint silinir
+//   Waiting/Ready başlamamış oyunun checkpoint'i olmamalı; bulunursa iptal edilip iade edilir
+// Checkpoint'i olmadan Started kalmış masalar (checkpoint yazılamadan duran sunucu) da iptal edilir.
+
+use std::collections::HashSet;
+use mongodb::Client;
+use crate::config::app_config::AppConfig;
+use crate::models::bot::is_bot_id;
+use crate::models::livegame::{GameState, LiveGame, ParticipantKind, Player, VoidReason};
+use crate::models::salon::Salon;
+use crate::models::table::GameState as TableState;
+use crate::repository::live_game_repository::LiveGameRepository;
+use crate::repository::salon_repository::SalonRepository;
+use crate::services::economy_cache::EconomyCache;
+use crate::services::game_engine::{apply, outcome, GameEvent};
+use crate::services::game_rooms::SharedRooms;
+use crate::services::game_settlement::settle_game;
+use crate::services::game_void::void_game;
+use crate::services::live_game_socket_services::{checkpoint, start_game, unix_now};
+use crate::services::table_lifecycle::ActiveGames;
+
+pub async fn recover_games(
+    mongo_client: &Client,
+    config: &AppConfig,
+    economy: &EconomyCache,
+    checkpoints: &LiveGameRepository,
+    active_games: &ActiveGames,
+    rooms: &SharedRooms,
+) {
+    if let Err(e) = checkpoints.ensure_indexes().await {
+        eprintln!("live_games indeksleri oluşturulamadı: {:?}", e);
+    }
+    let salon_repo = SalonRepository::new(mongo_client);
+    let (games, salons) = match (checkpoints.find_all().await, salon_repo.get_all_salons().await) {
+        (Ok(games), Ok(salons)) => (games, salons),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Süren oyunlar geri yüklenemedi: {:?}", e);
+            return;
+        }
+    };
+
+    let now = unix_now();
+    let mut recovered_tables = HashSet::new();
+    for mut game in games {
+        // Completed oyunun masasında artık başka bir oyun olabilir; o masa aşağıda ayrıca ele alınır
+        if game.state != GameState::Completed || is_same_table(&salons, &game) {
+            recovered_tables.insert((game.salon_id.clone(), game.table_id.clone()));
+        }
+        match game.state {
+            GameState::Started if config.resume_games_on_restart && is_same_table(&salons, &game) => {
+                if let Err(e) = apply(&mut game, now, GameEvent::Resume) {
+                    eprintln!("Oyun {} sürdürülemedi: {}", game.game_id, e.message());
+                    continue;
+                }
+                checkpoint(checkpoints, &game).await;
+                println!("Oyun {} sürdürülüyor: salon_id: {}, table_id: {}", game.game_id, game.salon_id, game.table_id);
+                active_games.lock().await.insert((game.salon_id.clone(), game.table_id.clone()), game);
+            }
+            GameState::Started | GameState::Voided | GameState::Waiting | GameState::Ready => {
+                void_game(mongo_client, checkpoints, rooms, &mut game, VoidReason::ServerRestart).await;
+            }
+            GameState::Completed => {
+                settle_game(mongo_client, &economy.current(), &game, &outcome(&game)).await;
+                if is_same_table(&salons, &game) {
+                    if let (Ok(salon_id), Ok(table_id)) = (game.salon_id.parse::<i32>(), game.table_id.parse::<i32>()) {
+                        if let Err(e) = salon_repo.reset_table(salon_id, table_id, TableState::Started).await {
+                            eprintln!("Masa {}/{} boşaltılamadı: {:?}", salon_id, table_id, e);
+                        }
+                    }
+                }
+                if let Err(e) = checkpoints.remove(&game.game_id).await {
+                    eprintln!("Oyun {} checkpoint'i silinemedi: {:?}", game.game_id, e);
+                }
+                println!("Oyun {} yeniden sonuçlandırıldı: salon_id: {}, table_id: {}", game.game_id, game.salon_id, game.table_id);
+            }
+        }
+    }
+
+    for salon in &salons {
+        for table in salon.tables.iter().filter(|t| matches!(t.game_state, TableState::Started)) {
+            if recovered_tables.contains(&(salon.salon_id.to_string(), table.table_id.to_string())) {
+                continue;
+            }
+            let players = table.players.iter().filter(|p| p.has_paid).map(|p| Player {
+                player_id: p.player_id,
+                rolls: vec![],
+                is_active: false,
+                last_roll_time: None,
+                disconnected_at: None,
+                tiebreak_rolls: vec![],
+                kind: if is_bot_id(p.player_id) { ParticipantKind::Bot } else { ParticipantKind::Human },
+            }).collect();
+            let mut game = start_game(players, salon, table.table_id).await;
+            void_game(mongo_client, checkpoints, rooms, &mut game, VoidReason::ServerRestart).await;
+        }
+    }
+}
+
+// Masa hâlâ bu oyunun oyuncularıyla Started mı
+fn is_same_table(salons: &[Salon], game: &LiveGame) -> bool {
+    let table = salons.iter()
+        .filter(|s| s.salon_id.to_string() == game.salon_id)
+        .flat_map(|s| s.tables.iter())
+        .find(|t| t.table_id.to_string() == game.table_id);
+    let Some(table) = table.filter(|t| matches!(t.game_state, TableState::Started)) else {
+        return false;
+    };
+    let seated: HashSet<i64> = table.players.iter().map(|p| p.player_id).collect();
+    let playing: HashSet<i64> = game.players.iter().map(|p| p.player_id).collect();
+    seated == playing
+}
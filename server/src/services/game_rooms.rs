@@ -0,0 +1,57 @@
+// services/game_rooms.rs
+// Canlı oyun soketindeki yayın odaları. Her oyun (salon_id, table_id) anahtarlı bir odadır;
+// masadaki oyuncular ve izleyiciler odaya abone olur, oyunun tüm olayları yalnızca o odaya gider.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message;
+
+pub type RoomKey = (String, String); // (salon_id, table_id)
+pub type ConnectionId = u64;
+pub type SharedRooms = Arc<Mutex<GameRooms>>;
+
+pub struct Subscriber {
+    pub player_id: Option<i64>, // İzleyicilerde None
+    pub sender: UnboundedSender<Message>,
+}
+
+#[derive(Default)]
+pub struct GameRooms {
+    rooms: HashMap<RoomKey, HashMap<ConnectionId, Subscriber>>,
+}
+
+impl GameRooms {
+    // Bağlantıyı odaya ekler; bir bağlantı aynı anda tek bir odadadır
+    pub fn subscribe(&mut self, key: RoomKey, connection_id: ConnectionId, subscriber: Subscriber) {
+        self.unsubscribe(connection_id);
+        self.rooms.entry(key).or_default().insert(connection_id, subscriber);
+    }
+
+    pub fn unsubscribe(&mut self, connection_id: ConnectionId) {
+        self.rooms.retain(|_, subscribers| {
+            subscribers.remove(&connection_id);
+            !subscribers.is_empty()
+        });
+    }
+
+    // Oyuncunun herhangi bir odada açık bağlantısı var mı
+    pub fn has_player(&self, player_id: i64) -> bool {
+        self.rooms.values().flat_map(|subscribers| subscribers.values()).any(|s| s.player_id == Some(player_id))
+    }
+
+    pub fn broadcast(&self, key: &RoomKey, message: &Value) {
+        self.broadcast_with(key, |_| message.clone());
+    }
+
+    // Aboneye göre değişen mesajlar için (örn. kazanana ayrı metin)
+    pub fn broadcast_with(&self, key: &RoomKey, message_for: impl Fn(Option<i64>) -> Value) {
+        if let Some(subscribers) = self.rooms.get(key) {
+            for subscriber in subscribers.values() {
+                let _ = subscriber.sender.send(Message::Text(message_for(subscriber.player_id).to_string()));
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// services/game_settlement.rs
+// Biten oyunun (EngineEvent::Finished) sonuçlandırılması; insan ve botlu masalar için aynıdır.
+// Ödüller salon belgesinden hesaplanır: masanın havuzu, salonun kesintisi ve sıralamaya göre
+// dağılımı. Botlar sıralamada yer alır ama ödül ve itibar almaz; botun yerinin ödülü sistemde kalır.
+// Sonuçlandırma tekrar çalıştırılabilir (sunucu yarıda durduysa açılışta yeniden çalışır): oyun için
+// GameWin hareketi olan oyuncuya ödül, itibarı bu oyun için artırılmış oyuncuya itibar yeniden
+// verilmez; sonuç game_id'ye göre yazılır.
+
+use mongodb::{Client, Collection};
+use mongodb::options::ReplaceOptions;
+use mongodb::bson::{doc, to_bson, Bson, Document};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::models::economy::EconomyConfig;
+use crate::models::ledger::{user_account, Asset, LedgerTransaction, Reason, SYSTEM_GAME_PRIZES};
+use crate::models::livegame::{LiveGame, ParticipantKind};
+use crate::models::salon::TieRule;
+use crate::repository::ledger_repository::LedgerRepository;
+use crate::repository::salon_repository::SalonRepository;
+use crate::services::game_engine::GameOutcome;
+use crate::services::provably_fair::Fairness;
+use crate::services::tie_break::{standings, Standing};
+
+// Kullanıcıda tutulan, itibarı artırılmış son oyunların sayısı (users.reputation_games)
+const RECENT_REPUTATION_GAMES: i32 = 50;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GameResult {
+    pub game_id: String,
+    pub winner_id: i64,         // Birinciliği paylaşan varsa ilki
+    pub winner_ids: Vec<i64>,   // Birinciliği paylaşan tüm oyuncular
+    pub tie_rule: TieRule,
+    pub tied_for_first: bool,   // Birincilik eşit zar toplamıyla bitti (kural uygulandı)
+    pub players: Vec<PlayerResult>,
+    pub fairness: Fairness,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlayerResult {
+    pub player_id: i64,
+    pub rolls: Vec<i32>,
+    pub tiebreak_rolls: Vec<i32>,
+    pub place: usize,
+    pub payout: i32, // HP
+}
+
+// Ödülleri ve itibar artışlarını uygular, sonucu games.game_results'a yazar; oyuncuların yerlerini
+// ve ödüllerini döner
+pub async fn settle_game(
+    mongo_client: &Client,
+    economy: &EconomyConfig,
+    live_game: &LiveGame,
+    outcome: &GameOutcome,
+) -> Vec<Standing<i64>> {
+    let salon_id = live_game.salon_id.parse::<i32>().unwrap_or_default();
+    let table_id = live_game.table_id.parse::<i32>().unwrap_or_default();
+    let payouts = match SalonRepository::new(mongo_client).find_salon_by_id(salon_id).await {
+        Ok(Some(salon)) => salon.payouts(table_id),
+        Ok(None) => {
+            eprintln!("Salon {} bulunamadı, oyun {} için ödül ödenmedi", salon_id, live_game.game_id);
+            vec![]
+        }
+        Err(e) => {
+            eprintln!("Salon {} okunamadı, oyun {} için ödül ödenmedi: {:?}", salon_id, live_game.game_id, e);
+            vec![]
+        }
+    };
+
+    let mut standings = standings(&outcome.placings, &payouts);
+    for standing in standings.iter_mut() {
+        if !is_human(live_game, standing.player_id) {
+            standing.payout = 0;
+            continue;
+        }
+        update_reputation_points(mongo_client, standing.player_id, economy.reputation_reward_pct(standing.place - 1), &live_game.game_id).await;
+        if standing.payout > 0 && !prize_paid(mongo_client, standing.player_id, &live_game.game_id).await {
+            update_winner_ton_amount(mongo_client, standing.player_id, standing.payout, &live_game.game_id).await;
+        }
+    }
+
+    save_game_result_to_db(mongo_client, live_game, outcome, &standings).await;
+    standings
+}
+
+// Oyuncunun bu oyun için ödülü deftere yazılmış mı; okunamazsa ödenmiş sayılır (çift ödeme yerine elle inceleme)
+async fn prize_paid(mongo_client: &Client, player_id: i64, game_id: &str) -> bool {
+    match LedgerRepository::new(mongo_client).exists(Reason::GameWin, game_id, &user_account(player_id)).await {
+        Ok(paid) => paid,
+        Err(e) => {
+            eprintln!("Oyun {} için player {} ödülü kontrol edilemedi, ödenmedi: {:?}", game_id, player_id, e);
+            true
+        }
+    }
+}
+
+fn is_human(live_game: &LiveGame, player_id: i64) -> bool {
+    live_game.players.iter().any(|p| p.player_id == player_id && p.kind == ParticipantKind::Human)
+}
+
+// Kazananı (birincilik paylaşıldıysa kazananları) ve sıralamayı duyuran mesaj; `viewer` mesajı
+// alan oyuncudur (izleyiciler için None)
+pub fn winner_message(live_game: &LiveGame, outcome: &GameOutcome, standings: &[Standing<i64>], viewer: Option<i64>) -> serde_json::Value {
+    let winner_ids = outcome.winner_ids();
+    let winners = winner_ids.iter().map(|id| format!("Player {}", id)).collect::<Vec<_>>().join(", ");
+    let won = viewer.is_some_and(|pid| winner_ids.contains(&pid));
+    let message = match (won, winner_ids.len() > 1) {
+        (true, false) => "Kazandınız!".to_string(),
+        (true, true) => "Berabere! Ödül paylaşıldı.".to_string(),
+        (false, false) => format!("Kazanan: {}", winners),
+        (false, true) => format!("Berabere: {}", winners),
+    };
+    let standings: Vec<_> = standings.iter().map(|standing| {
+        let player = live_game.players.iter().find(|p| p.player_id == standing.player_id);
+        json!({
+            "player_id": standing.player_id,
+            "place": standing.place,
+            "total_roll": player.map_or(0, |p| p.rolls.iter().sum::<i32>()),
+            "tiebreak_rolls": player.map(|p| p.tiebreak_rolls.clone()).unwrap_or_default(),
+            "payout": standing.payout
+        })
+    }).collect();
+
+    json!({
+        "action": "winner_announced",
+        "message": message,
+        "winner_id": winner_ids[0],
+        "winner_ids": winner_ids,
+        "tie_rule": live_game.tie_rule,
+        "tied": outcome.tied_for_first,
+        "standings": standings,
+        "fairness": live_game.fairness.reveal()
+    })
+}
+
+// Kullanıcının tüm item'lerinin reputation_points değerini oranla artırır. Artış bu oyun için
+// zaten yapıldıysa (`reputation_games`) hiçbir şey yazılmaz.
+async fn update_reputation_points(mongo_client: &Client, player_id: i64, increase_percentage: i32, game_id: &str) {
+    let collection: Collection<Document> = mongo_client.database("users").collection("users");
+    let filter = doc! { "telegram_id": player_id, "reputation_games": { "$ne": game_id } };
+
+    let user_doc = match collection.find_one(filter.clone(), None).await {
+        Ok(user_doc) => user_doc,
+        Err(e) => {
+            eprintln!("Player {} reputation points için okunamadı: {:?}", player_id, e);
+            return;
+        }
+    };
+    if let Some(mut user_doc) = user_doc {
+        if let Some(items) = user_doc.get_array_mut("items").ok() {
+            for item in items.iter_mut() {
+                if let Some(item_doc) = item.as_document_mut() {
+                    if let Some(rep_points) = item_doc.get_i32("reputation_points").ok() {
+                        let updated_points = rep_points + rep_points * increase_percentage / 100;
+                        item_doc.insert("reputation_points", updated_points);
+                    }
+                }
+            }
+
+            let update = doc! {
+                "$set": { "items": items },
+                "$push": { "reputation_games": { "$each": [game_id], "$slice": -RECENT_REPUTATION_GAMES } }
+            };
+            if let Err(e) = collection.update_one(filter, update, None).await {
+                eprintln!("Reputation points güncellenirken hata oluştu: {:?}", e);
+            } else {
+                println!("Player {} için reputation points başarıyla güncellendi.", player_id);
+            }
+        }
+    }
+}
+
+async fn update_winner_ton_amount(mongo_client: &Client, winner_id: i64, amount_to_add: i32, game_id: &str) {
+    let collection: Collection<Document> = mongo_client.database("users").collection("users");
+
+    let filter = doc! { "telegram_id": winner_id };
+    let update = doc! { "$inc": { "hp": amount_to_add } };
+
+    match collection.update_one(filter, update, None).await {
+        Ok(_) => {
+            println!("Player {}'in ton_amount değeri başarıyla güncellendi. Eklenen miktar: {}", winner_id, amount_to_add);
+            let transaction = LedgerTransaction::transfer(
+                Reason::GameWin,
+                Asset::Hp,
+                SYSTEM_GAME_PRIZES,
+                user_account(winner_id),
+                amount_to_add as i64,
+                Some(game_id.to_string()),
+            );
+            if let Err(e) = LedgerRepository::new(mongo_client).record(transaction).await {
+                eprintln!("Oyun ödülü deftere yazılamadı: {:?}", e);
+            }
+        }
+        Err(e) => eprintln!("ton_amount güncellenirken hata oluştu: {:?}", e),
+    }
+}
+
+async fn save_game_result_to_db(
+    mongo_client: &Client,
+    live_game: &LiveGame,
+    outcome: &GameOutcome,
+    standings: &[Standing<i64>],
+) {
+    let collection: Collection<Document> = mongo_client.database("games").collection("game_results");
+    let winner_ids = outcome.winner_ids();
+
+    let game_result = GameResult {
+        game_id: live_game.game_id.clone(),
+        winner_id: winner_ids[0],
+        winner_ids: winner_ids.to_vec(),
+        tie_rule: live_game.tie_rule,
+        tied_for_first: outcome.tied_for_first,
+        players: standings.iter().filter_map(|standing| {
+            let player = live_game.players.iter().find(|p| p.player_id == standing.player_id)?;
+            Some(PlayerResult {
+                player_id: player.player_id,
+                rolls: player.rolls.clone(),
+                tiebreak_rolls: player.tiebreak_rolls.clone(),
+                place: standing.place,
+                payout: standing.payout,
+            })
+        }).collect(),
+        fairness: live_game.fairness.clone(),
+    };
+
+    let result_doc = doc! {
+        "game_id": game_result.game_id,
+        "winner_id": game_result.winner_id,
+        "winner_ids": game_result.winner_ids,
+        "tie_rule": to_bson(&game_result.tie_rule).unwrap_or(Bson::Null),
+        "tied_for_first": game_result.tied_for_first,
+        "players": game_result.players.iter().map(|p| {
+            doc! {
+                "player_id": p.player_id,
+                "rolls": p.rolls.clone(),
+                "tiebreak_rolls": p.tiebreak_rolls.clone(),
+                "place": p.place as i32,
+                "payout": p.payout,
+            }
+        }).collect::<Vec<Document>>(),
+        "fairness": to_bson(&game_result.fairness).unwrap_or(Bson::Null),
+    };
+
+    let options = ReplaceOptions::builder().upsert(true).build();
+    if let Err(e) = collection.replace_one(doc! { "game_id": &live_game.game_id }, result_doc, options).await {
+        eprintln!("Oyun sonucu MongoDB'ye kaydedilirken hata oluştu: {:?}", e);
+    } else {
+        println!("Oyun sonucu başarıyla MongoDB'ye kaydedildi.");
+    }
+}
@@ -0,0 +1,155 @@
+// services/game_void.rs
+// Oyunun iptali: oyun nedeniyle birlikte `Voided` olarak checkpoint'e yazılır, ödeme yapmış her
+// oyuncuya giriş ücreti (game pass) iade edilir, odaya `game_voided` gönderilir, masa boşaltılır,
+// oyun games.voided_games'e kaydedilir ve checkpoint silinir. İade defterde oyuna bağlı olduğundan
+// yarıda kalan bir iptal tekrar çalıştırıldığında aynı oyuncuya ikinci kez iade yapılmaz.
+
+use mongodb::Client;
+use serde_json::json;
+use tokio::time::{self, Duration};
+use crate::models::ledger::{user_account, Asset, LedgerTransaction, Reason, SYSTEM_BETS};
+use crate::models::livegame::{GameState, LiveGame, VoidReason, VoidedGame};
+use crate::models::bot::is_bot_id;
+use crate::models::table::GameState as TableState;
+use crate::repository::ledger_repository::LedgerRepository;
+use crate::repository::live_game_repository::LiveGameRepository;
+use crate::repository::salon_repository::SalonRepository;
+use crate::repository::user_repository::UserRepository;
+use crate::services::game_engine::{apply, GameEvent};
+use crate::services::game_rooms::{RoomKey, SharedRooms};
+use crate::services::live_game_socket_services::{checkpoint, unix_now};
+use crate::services::table_lifecycle::ActiveGames;
+
+// Oyun `active_games`'ten önceden çıkarılmış olmalı; böylece iptal sürerken zar atılamaz
+pub async fn void_game(
+    mongo_client: &Client,
+    checkpoints: &LiveGameRepository,
+    rooms: &SharedRooms,
+    game: &mut LiveGame,
+    reason: VoidReason,
+) {
+    // Yarıda kalmış bir iptal ilk nedeniyle tamamlanır
+    let reason = game.void_reason.clone().unwrap_or(reason);
+    println!("Oyun {} iptal ediliyor ({:?}): salon_id: {}, table_id: {}", game.game_id, reason, game.salon_id, game.table_id);
+    if game.state != GameState::Voided {
+        match apply(game, unix_now(), GameEvent::Void(reason.clone())) {
+            Ok(_) => checkpoint(checkpoints, game).await,
+            Err(e) => eprintln!("Oyun {} iptal edilemedi: {}", game.game_id, e.message()),
+        }
+    }
+
+    // Botlar ödeme yapmaz
+    let player_ids: Vec<i64> = game.players.iter().map(|p| p.player_id).filter(|id| !is_bot_id(*id)).collect();
+    let refunded_players = refund_entry_fees(mongo_client, &game.game_id, &player_ids, game.entry_fee).await;
+
+    let key: RoomKey = (game.salon_id.clone(), game.table_id.clone());
+    let message = json!({
+        "action": "game_voided",
+        "game_id": game.game_id,
+        "reason": reason,
+        "refund": game.entry_fee
+    });
+    rooms.lock().await.broadcast(&key, &message);
+
+    if let (Ok(salon_id), Ok(table_id)) = (game.salon_id.parse::<i32>(), game.table_id.parse::<i32>()) {
+        if let Err(e) = SalonRepository::new(mongo_client).reset_table(salon_id, table_id, TableState::Started).await {
+            eprintln!("Masa {}/{} boşaltılamadı: {:?}", salon_id, table_id, e);
+        }
+    }
+
+    // İadesi yapılamayan oyuncu varsa checkpoint `Voided` olarak kalır; açılışta iptal yeniden denenir
+    if refunded_players.len() < player_ids.len() {
+        return;
+    }
+    let voided = VoidedGame {
+        game_id: game.game_id.clone(),
+        salon_id: game.salon_id.clone(),
+        table_id: game.table_id.clone(),
+        reason,
+        refunded_players,
+        refund_per_player: game.entry_fee,
+        voided_at: unix_now() as i64,
+    };
+    if let Err(e) = checkpoints.record_voided(&voided).await {
+        eprintln!("Oyun {} iptal kaydı yazılamadı: {:?}", game.game_id, e);
+    }
+    if let Err(e) = checkpoints.remove(&game.game_id).await {
+        eprintln!("Oyun {} checkpoint'i silinemedi: {:?}", game.game_id, e);
+    }
+}
+
+// Oyuncuların giriş ücretlerini iade eder; iadesi yapılmış (ya da daha önce yapılmış) oyuncuları döner
+pub async fn refund_entry_fees(mongo_client: &Client, game_id: &str, player_ids: &[i64], entry_fee: i32) -> Vec<i64> {
+    let mut refunded = vec![];
+    for player_id in player_ids {
+        match refund_entry_fee(mongo_client, game_id, *player_id, entry_fee).await {
+            Ok(()) => refunded.push(*player_id),
+            Err(e) => eprintln!("Oyun {} için Player {} iadesi yapılamadı: {:?}", game_id, player_id, e),
+        }
+    }
+    refunded
+}
+
+async fn refund_entry_fee(mongo_client: &Client, game_id: &str, player_id: i64, entry_fee: i32) -> mongodb::error::Result<()> {
+    if entry_fee <= 0 {
+        return Ok(());
+    }
+    let ledger = LedgerRepository::new(mongo_client);
+    let account = user_account(player_id);
+    if ledger.exists(Reason::GameRefund, game_id, &account).await? {
+        return Ok(());
+    }
+
+    if UserRepository::new(mongo_client).credit_game_pass(player_id, entry_fee).await? {
+        ledger.record(LedgerTransaction::transfer(
+            Reason::GameRefund,
+            Asset::GamePass,
+            SYSTEM_BETS,
+            account,
+            entry_fee as i64,
+            Some(game_id.to_string()),
+        )).await?;
+        println!("Player {} için {} game pass iade edildi (oyun {})", player_id, entry_fee, game_id);
+    } else {
+        eprintln!("Player {} bulunamadı, oyun {} iadesi yapılmadı", player_id, game_id);
+    }
+    Ok(())
+}
+
+// Süren oyunu `active_games`'ten çıkarır; oyun yoksa ya da sonuçlandırılıyorsa None
+pub async fn take_started_game(active_games: &ActiveGames, game_id: &str) -> Option<LiveGame> {
+    let mut games = active_games.lock().await;
+    let key = games.iter()
+        .find(|(_, game)| game.game_id == game_id && game.state == GameState::Started)
+        .map(|(key, _)| key.clone())?;
+    games.remove(&key)
+}
+
+// Yöneticinin API'den (POST /admin/games/<game_id>/void) istediği iptalleri uygular
+pub async fn run_void_requests(mongo_client: Client, checkpoints: LiveGameRepository, active_games: ActiveGames, rooms: SharedRooms) {
+    let mut interval = time::interval(Duration::from_secs(1));
+
+    loop {
+        interval.tick().await;
+        let requests = match checkpoints.pending_void_requests().await {
+            Ok(requests) => requests,
+            Err(e) => {
+                eprintln!("İptal istekleri okunamadı: {:?}", e);
+                continue;
+            }
+        };
+
+        for request in requests {
+            match take_started_game(&active_games, &request.game_id).await {
+                Some(mut game) => {
+                    let reason = VoidReason::Admin { admin_id: request.admin_id, note: request.note.clone() };
+                    void_game(&mongo_client, &checkpoints, &rooms, &mut game, reason).await;
+                }
+                None => eprintln!("İptal istenen oyun {} sürmüyor, istek yok sayıldı", request.game_id),
+            }
+            if let Err(e) = checkpoints.remove_void_request(&request.game_id).await {
+                eprintln!("Oyun {} iptal isteği silinemedi: {:?}", request.game_id, e);
+            }
+        }
+    }
+}
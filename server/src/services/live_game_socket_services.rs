@@ -1,10 +1,10 @@
 use futures_util::{StreamExt, SinkExt};
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::json;
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::accept_async;
 use tokio::net::TcpListener;
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use std::sync::Arc;
@@ -13,66 +13,76 @@ use mongodb::{Client, Collection};
 use mongodb::bson::{doc, Document};
 use uuid::Uuid;
 use std::collections::HashMap;
-
-const MAX_ROLLS: usize = 5;
-const ROLL_TIMEOUT: u64 = 10;
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Player {
-    pub player_id: i64,
-    #[serde(rename = "dice_rolls")]
-    pub rolls: Vec<i32>,
-    pub is_active: bool,
-    pub last_roll_time: Option<u64>, // Zar atma süresi için Unix timestamp
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct LiveGame {
-    pub game_id: String,
-    pub players: Vec<Player>,
-    pub state: GameState,
-    pub salon_id: String,
-    pub table_id: String,
-}
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub enum GameState {
-    Waiting,
-    Ready,
-    Started,
-    Completed,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GameResult {
-    pub game_id: String,
-    pub winner_id: i64,
-    pub players: Vec<PlayerResult>,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct PlayerResult {
-    pub player_id: i64,
-    pub rolls: Vec<i32>,
-}
-
+use crate::services::provably_fair::Fairness;
+use crate::config::app_config::AppConfig;
+use crate::repository::salon_repository::SalonRepository;
+use crate::repository::live_game_repository::LiveGameRepository;
+use crate::models::livegame::{GameState, LiveGame, Player};
+use crate::services::game_recovery::recover_games;
+use crate::services::game_void::run_void_requests;
+use crate::services::economy_cache::EconomyCache;
+use crate::services::table_lifecycle::{run_table_lifecycle, ActiveGames};
+use crate::services::roll_timer::run_roll_timer;
+use crate::services::bot_players::run_bot_players;
+use crate::services::game_rooms::{GameRooms, RoomKey, SharedRooms, Subscriber};
+use crate::services::auth::verify_token;
+use crate::services::game_engine::{apply, finished_outcome, EngineError, EngineEvent, GameEvent, GameOutcome, TurnRules, MAX_ROLLS};
+use crate::services::game_settlement::{settle_game, winner_message};
+use crate::models::table::GameState as TableState;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use crate::models::economy::EconomyConfig;
+use crate::models::salon::Salon;
+
+// Canlı oyun soketinin komut biçimi
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CommandMessage {
     pub action: String,
+    #[serde(default, deserialize_with = "player_id_from_number_or_string")]
     pub player_id: Option<i64>,
-    pub roll: Option<i32>,
-    pub players: Option<Vec<Player>>,
     pub salon_id: Option<String>,
     pub table_id: Option<String>,
+    pub client_seed: Option<String>,
+    pub token: Option<String>, // API'nin verdiği JWT (join_table)
+}
+
+// Eski istemciler player_id'yi string olarak gönderir
+fn player_id_from_number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum PlayerId {
+        Number(i64),
+        Text(String),
+    }
+    match Option::<PlayerId>::deserialize(deserializer)? {
+        Some(PlayerId::Number(player_id)) => Ok(Some(player_id)),
+        Some(PlayerId::Text(player_id)) => player_id.parse().map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
 }
 
-pub async fn run_live_game_websocket_server(mongo_client: &Client) {
-    let addr = "127.0.0.1:9003";
-    let listener = TcpListener::bind(addr).await.expect("WebSocket sunucusu başlatılamadı!");
+pub async fn run_live_game_websocket_server(mongo_client: &Client, config: Arc<AppConfig>, economy: Arc<EconomyCache>) {
+    let addr = config.address(config.live_game_port);
+    let listener = TcpListener::bind(&addr).await.expect("WebSocket sunucusu başlatılamadı!");
 
     println!("Live Game WebSocket sunucusu {} adresinde çalışıyor", addr);
 
-    let active_games = Arc::new(Mutex::new(HashMap::new()));
+    let active_games: ActiveGames = Arc::new(Mutex::new(HashMap::new()));
+    let rooms: SharedRooms = Arc::new(Mutex::new(GameRooms::default()));
+    let checkpoints = LiveGameRepository::new(mongo_client);
+    let rules = config.turn_rules();
 
+    // Yeniden başlatmadan önce süren oyunlar sürdürülür ya da iptal edilip ödemeler iade edilir
+    recover_games(mongo_client, &config, &economy, &checkpoints, &active_games, &rooms).await;
+
+    // Oyunları istemciler değil, masaların yaşam döngüsü başlatır
+    tokio::spawn(run_table_lifecycle(mongo_client.clone(), config.clone(), active_games.clone(), rooms.clone(), checkpoints.clone()));
+    tokio::spawn(broadcast_game_state_loop(active_games.clone(), rooms.clone()));
+    // Zar atmayan oyuncular masayı kilitlemesin diye süreleri sunucu işletir
+    tokio::spawn(run_void_requests(mongo_client.clone(), checkpoints.clone(), active_games.clone(), rooms.clone()));
+    tokio::spawn(run_roll_timer(mongo_client.clone(), config.clone(), economy.clone(), active_games.clone(), rooms.clone(), checkpoints.clone()));
+    tokio::spawn(run_bot_players(mongo_client.clone(), config.clone(), economy.clone(), active_games.clone(), rooms.clone(), checkpoints.clone()));
+
+    let mut next_connection_id = 0;
     while let Ok((stream, _)) = listener.accept().await {
         let ws_stream = match accept_async(stream).await {
             Ok(ws) => ws,
@@ -82,14 +92,28 @@ pub async fn run_live_game_websocket_server(mongo_client: &Client) {
             }
         };
 
-        let (write, mut read) = ws_stream.split();
-        let write = Arc::new(Mutex::new(write));
-        tokio::spawn(broadcast_game_state_loop(write.clone(), active_games.clone()));
+        let (mut write, mut read) = ws_stream.split();
+        next_connection_id += 1;
+        let connection_id = next_connection_id;
+
+        // Bu bağlantıya giden tüm mesajlar (yanıtlar ve oda yayınları) bu kanaldan akar
+        let (sender, mut outbox) = unbounded_channel::<Message>();
+        tokio::spawn(async move {
+            while let Some(message) = outbox.recv().await {
+                if write.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
         tokio::spawn({
             let mongo_client = mongo_client.clone();
             let active_games = active_games.clone();
+            let rooms = rooms.clone();
+            let economy = economy.clone();
+            let config = config.clone();
+            let checkpoints = checkpoints.clone();
             async move {
-                let mut live_game: Option<LiveGame> = None;
                 let mut player_id = None;
 
                 loop {
@@ -97,48 +121,70 @@ pub async fn run_live_game_websocket_server(mongo_client: &Client) {
                         msg = read.next() => {
                             match msg {
                                 Some(Ok(Message::Text(text))) => {
-                                    println!("Alınan mesaj: {:?}", text);
                                     match serde_json::from_str::<CommandMessage>(&text) {
                                         Ok(command) => {
                                             match command.action.as_str() {
+                                                // Oyuncu kimliğini doğrular ve masasının odasına (ya da süren oyununa) bağlanır
+                                                "join_table" => {
+                                                    match join_table(&command, &mongo_client, &config, &active_games, &rooms, &checkpoints).await {
+                                                        Ok(joined) => {
+                                                            player_id = Some(joined.player_id);
+                                                            let subscriber = Subscriber { player_id: Some(joined.player_id), sender: sender.clone() };
+                                                            rooms.lock().await.subscribe(joined.key, connection_id, subscriber);
+                                                            let _ = sender.send(Message::Text(joined.reply.to_string()));
+                                                        }
+                                                        Err(message) => send_error(&sender, &message),
+                                                    }
+                                                }
+                                                // Oturmadan masanın olaylarını izler
+                                                "spectate" => {
+                                                    if let (Some(salon_id), Some(table_id)) = (command.salon_id.clone(), command.table_id.clone()) {
+                                                        let subscriber = Subscriber { player_id: None, sender: sender.clone() };
+                                                        rooms.lock().await.subscribe((salon_id.clone(), table_id.clone()), connection_id, subscriber);
+                                                        let message = json!({"action": "spectating", "salon_id": salon_id, "table_id": table_id});
+                                                        let _ = sender.send(Message::Text(message.to_string()));
+                                                    } else {
+                                                        eprintln!("Salon/masa ID'si eksik");
+                                                    }
+                                                }
                                                 "start_game" => {
-                                                    if let (Some(players), Some(salon_id), Some(table_id)) = (command.players, command.salon_id.clone(), command.table_id.clone()) {
-                                                        live_game = Some(start_game(players, salon_id.clone(), table_id.clone()).await);
-                                                        active_games.lock().await.insert((salon_id, table_id), live_game.clone().unwrap());
-
-                                                        let message = json!({"action": "game_started", "game_id": live_game.as_ref().unwrap().game_id});
-                                                        let _ = write.lock().await.send(Message::Text(message.to_string())).await;
+                                                    send_error(&sender, "Oyun, masa dolup herkes ödediğinde sunucu tarafından başlatılır");
+                                                }
+                                                "set_client_seed" => {
+                                                    if player_id.is_none() {
+                                                        send_error(&sender, NOT_JOINED);
+                                                    } else if let (Some(pid), Some(client_seed)) = (player_id, command.client_seed) {
+                                                        // Yalnızca ilk zardan önce kabul edilir
+                                                        let event = GameEvent::SetClientSeed { player_id: pid, client_seed: client_seed.clone() };
+                                                        let message = match drive_player(&active_games, pid, &rules, event, &rooms, &checkpoints).await {
+                                                            Ok(_) => json!({"action": "client_seed_accepted", "player_id": pid, "client_seed": client_seed}),
+                                                            Err(e) => json!({"action": "client_seed_rejected", "player_id": pid, "message": e.message()}),
+                                                        };
+                                                        let _ = sender.send(Message::Text(message.to_string()));
                                                     } else {
-                                                        eprintln!("Oyuncu listesi ya da salon/masa ID'si eksik");
+                                                        eprintln!("Client seed eksik");
                                                     }
                                                 }
                                                 "roll_dice" => {
                                                     println!("roll_dice komutu alındı");
-                                                    if let Some(game) = &mut live_game {
-                                                        if let Some(pid) = command.player_id {
-                                                            player_id = Some(pid);
-                                                            if let Some(roll_value) = command.roll {
-                                                                handle_dice_roll(
-                                                                    pid,
-                                                                    roll_value,
-                                                                    game.salon_id.clone(),
-                                                                    game.table_id.clone(),
-                                                                    active_games.clone(),
-                                                                    write.clone()
-                                                                ).await;
-
-
-                                                                let response_message = json!({"action": "roll_acknowledged", "player_id": pid, "roll": roll_value});
-                                                                let _ = write.lock().await.send(Message::Text(response_message.to_string())).await;
-                                                                if game.players.iter().all(|p| p.rolls.len() == MAX_ROLLS) {
-                                                                    check_winner(game, &mongo_client, write.clone(), active_games.clone()).await;
+                                                    // Zar, bağlantının doğrulanmış oyuncusu için atılır
+                                                    if let Some(pid) = player_id {
+                                                        // Zar değeri istemciden alınmaz, sunucu üretir
+                                                        match drive_player(&active_games, pid, &rules, GameEvent::Roll { player_id: pid }, &rooms, &checkpoints).await {
+                                                            Ok((events, game)) => {
+                                                                if let Some(EngineEvent::Rolled { roll, .. }) = events.first() {
+                                                                    println!("Player {} zar attı: {}", pid, roll);
+                                                                    let response_message = json!({"action": "roll_acknowledged", "player_id": pid, "roll": roll});
+                                                                    let _ = sender.send(Message::Text(response_message.to_string()));
+                                                                }
+                                                                if let Some(outcome) = finished_outcome(&events) {
+                                                                    finish_game(&game, outcome, &mongo_client, &economy.current(), &rooms, active_games.clone(), &checkpoints).await;
                                                                 }
-                                                            } else {
-                                                                eprintln!("Zar değeri eksik");
                                                             }
-                                                        } else {
-                                                            eprintln!("Player ID eksik");
+                                                            Err(e) => send_error(&sender, e.message()),
                                                         }
+                                                    } else {
+                                                        send_error(&sender, NOT_JOINED);
                                                     }
                                                 }
                                                 _ => {}
@@ -150,9 +196,18 @@ pub async fn run_live_game_websocket_server(mongo_client: &Client) {
                                     }
                                 }
                                 Some(Ok(Message::Close(_))) | None => {
-                                    if let Some(pid) = player_id {
-                                        if let Some(game) = &mut live_game {
-                                            handle_player_disconnect(pid, game).await;
+                                    let still_connected = {
+                                        let mut rooms = rooms.lock().await;
+                                        rooms.unsubscribe(connection_id);
+                                        player_id.is_some_and(|pid| rooms.has_player(pid))
+                                    };
+                                    // Oyuncu başka bir bağlantıyla zaten döndüyse oyunu etkilenmez
+                                    // Zar hakları `reconnect_grace_secs` boyunca korunur; dönmezse motor kalan zarlarını 0 sayar
+                                    if let (Some(pid), false) = (player_id, still_connected) {
+                                        if let Ok((events, _)) = drive_player(&active_games, pid, &rules, GameEvent::Disconnect { player_id: pid }, &rooms, &checkpoints).await {
+                                            if events.contains(&EngineEvent::PlayerDisconnected { player_id: pid }) {
+                                                println!("Player {} bağlantısı koptu, {} saniye içinde dönebilir.", pid, rules.reconnect_grace_secs);
+                                            }
                                         }
                                     }
                                     break;
@@ -167,186 +222,294 @@ pub async fn run_live_game_websocket_server(mongo_client: &Client) {
     }
 }
 
-async fn handle_player_disconnect(player_id: i64, live_game: &mut LiveGame) {
-    if let Some(player) = live_game.players.iter_mut().find(|p| p.player_id == player_id) {
-        while player.rolls.len() < 5 {
-            player.rolls.push(0);
+const NOT_JOINED: &str = "Önce token ile join_table gönderilmeli";
+
+fn send_error(sender: &UnboundedSender<Message>, message: &str) {
+    let _ = sender.send(Message::Text(json!({"action": "error", "message": message}).to_string()));
+}
+
+struct JoinedTable {
+    player_id: i64,
+    key: RoomKey,
+    reply: serde_json::Value, // table_joined ya da game_snapshot
+}
+
+// Oyuncuyu token ile doğrular ve abone olacağı odayı bulur. Süren bir oyunu varsa (örn. uygulama
+// arka plana geçip bağlantı koptuysa) o oyuna yeniden bağlanır ve `game_snapshot` alır.
+async fn join_table(
+    command: &CommandMessage,
+    mongo_client: &Client,
+    config: &AppConfig,
+    active_games: &ActiveGames,
+    rooms: &SharedRooms,
+    checkpoints: &LiveGameRepository,
+) -> Result<JoinedTable, String> {
+    let token = command.token.as_deref().ok_or("Token eksik")?;
+    let player_id = verify_token(token, &config.jwt_secret)?;
+    if command.player_id.is_some_and(|pid| pid != player_id) {
+        return Err("player_id token ile eşleşmiyor".to_string());
+    }
+
+    if let Some((key, reply, reconnected)) = reattach_player(player_id, &config.turn_rules(), active_games, rooms, checkpoints).await {
+        if reconnected {
+            println!("Player {} oyuna yeniden bağlandı: salon_id: {}, table_id: {}", player_id, key.0, key.1);
         }
-        println!("Player {} bağlantısı koptu, kalan zar hakları sıfırlandı.", player_id);
+        return Ok(JoinedTable { player_id, key, reply });
+    }
+
+    let (Some(salon_id), Some(table_id)) = (command.salon_id.clone(), command.table_id.clone()) else {
+        return Err("Salon/masa ID'si eksik".to_string());
+    };
+    if !is_seated(mongo_client, player_id, &salon_id, &table_id).await {
+        return Err(format!("Player {} bu masada oturmuyor", player_id));
+    }
+    let reply = json!({"action": "table_joined", "player_id": player_id, "salon_id": salon_id, "table_id": table_id});
+    Ok(JoinedTable { player_id, key: (salon_id, table_id), reply })
+}
+
+// Oyuncu süren bir oyundaysa bağlantı kopukluğunu kaldırır (zar süresi dönüşten itibaren yeniden
+// başlar, odaya `player_reconnected` gider); oyunun anahtarını, tam durumunu ve oyuncunun kopuk
+// olup olmadığını döndürür
+async fn reattach_player(
+    player_id: i64,
+    rules: &TurnRules,
+    active_games: &ActiveGames,
+    rooms: &SharedRooms,
+    checkpoints: &LiveGameRepository,
+) -> Option<(RoomKey, serde_json::Value, bool)> {
+    let key = find_game_key(active_games, player_id).await?;
+    let (events, game) = drive_game(active_games, &key, rules, GameEvent::Reconnect { player_id }, rooms, checkpoints).await.ok()?;
+    let reconnected = events.contains(&EngineEvent::PlayerReconnected { player_id });
+    Some((key, game_snapshot(&game, rules.roll_timeout_secs, unix_now()), reconnected))
+}
+
+// Yeniden bağlanan istemcinin oyunu baştan çizebilmesi için tam durum
+fn game_snapshot(game: &LiveGame, roll_timeout_secs: u64, now: u64) -> serde_json::Value {
+    let players: Vec<_> = game.players.iter().enumerate().map(|(index, p)| {
+        let rolling = game.owes_roll(index) && p.disconnected_at.is_none();
+        json!({
+            "player_id": p.player_id,
+            "rolls": p.rolls,
+            "total_roll": p.rolls.iter().sum::<i32>(),
+            "tiebreak_rolls": p.tiebreak_rolls,
+            "rolls_left": game.max_rolls.saturating_sub(p.rolls.len()),
+            "connected": p.disconnected_at.is_none(),
+            "seconds_to_roll": p.last_roll_time
+                .filter(|_| rolling)
+                .map(|last| (last + roll_timeout_secs).saturating_sub(now))
+        })
+    }).collect();
+
+    json!({
+        "action": "game_snapshot",
+        "game_id": game.game_id,
+        "salon_id": game.salon_id,
+        "table_id": game.table_id,
+        "max_rolls": game.max_rolls,
+        "server_seed_hash": game.fairness.server_seed_hash,
+        "client_seeds": game.fairness.client_seeds,
+        "tie_rule": game.tie_rule,
+        "roll_off": game.roll_off,
+        "roll_off_round": game.roll_off_round,
+        "players": players
+    })
+}
+
+// Oyuncu gerçekten bu masada mı (salon belgesine göre)
+async fn is_seated(mongo_client: &Client, player_id: i64, salon_id: &str, table_id: &str) -> bool {
+    let (Ok(salon_id), Ok(table_id)) = (salon_id.parse::<i32>(), table_id.parse::<i32>()) else {
+        return false;
+    };
+    match SalonRepository::new(mongo_client).find_salon_by_id(salon_id).await {
+        Ok(Some(salon)) => salon.tables.iter()
+            .any(|t| t.table_id == table_id && t.players.iter().any(|p| p.player_id == player_id)),
+        _ => false,
     }
 }
 
-async fn start_game(players: Vec<Player>, salon_id: String, table_id: String) -> LiveGame {
+async fn find_game_key(active_games: &ActiveGames, player_id: i64) -> Option<RoomKey> {
+    let games = active_games.lock().await;
+    game_key_for_player(&games, player_id)
+}
+
+// Oyuncunun içinde olduğu aktif oyunun anahtarı
+fn game_key_for_player(games: &HashMap<(String, String), LiveGame>, player_id: i64) -> Option<(String, String)> {
+    games.iter()
+        .find(|(_, game)| game.players.iter().any(|p| p.player_id == player_id))
+        .map(|(key, _)| key.clone())
+}
+
+pub async fn start_game(players: Vec<Player>, salon: &Salon, table_id: i32) -> LiveGame {
     let game_id = Uuid::new_v4().to_string();
 
     LiveGame {
         game_id,
         players,
         state: GameState::Started,
-        salon_id,
-        table_id,
+        salon_id: salon.salon_id.to_string(),
+        table_id: table_id.to_string(),
+        fairness: Fairness::generate(),
+        entry_fee: salon.entry_fee,
+        version: 0,
+        void_reason: None,
+        tie_rule: salon.tie_rule,
+        paid_places: salon.payout_split_bps.len(),
+        roll_off: vec![],
+        roll_off_round: 0,
+        max_rolls: MAX_ROLLS,
+    }
+}
+
+// Oyunun son halini live_games'e yazar; yazılamazsa oyun bellekte sürer
+pub async fn checkpoint(checkpoints: &LiveGameRepository, game: &LiveGame) {
+    if let Err(e) = checkpoints.save(game).await {
+        eprintln!("Oyun {} checkpoint'i yazılamadı: {:?}", game.game_id, e);
     }
 }
 
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
 
-async fn handle_dice_roll<S>(
+// Motora olay verir; olayları oyunun odasına yayınlar ve oyun değiştiyse checkpoint'e yazar.
+// Olaylar ve oyunun son hali döner; oyun bittiyse (`EngineEvent::Finished`) `finish_game` ile
+// sonuçlandırılmalıdır. Oyun kilit altında `Completed` olduğundan her oyun yalnızca bir kez biter.
+pub async fn drive_game(
+    active_games: &ActiveGames,
+    key: &RoomKey,
+    rules: &TurnRules,
+    event: GameEvent,
+    rooms: &SharedRooms,
+    checkpoints: &LiveGameRepository,
+) -> Result<(Vec<EngineEvent>, LiveGame), EngineError> {
+    let mut games = active_games.lock().await;
+    let game = games.get_mut(key).ok_or(EngineError::NotInGame)?;
+    let events = apply(game, unix_now(), event)?;
+    let game = game.clone();
+    drop(games);
+
+    let messages = event_messages(&game, &events, rules);
+    if !messages.is_empty() {
+        let rooms = rooms.lock().await;
+        for message in &messages {
+            rooms.broadcast(key, message);
+        }
+    }
+    if events.iter().any(EngineEvent::changes_game) {
+        checkpoint(checkpoints, &game).await;
+    }
+    Ok((events, game))
+}
+
+// Oyuncunun içinde olduğu oyuna olay verir
+async fn drive_player(
+    active_games: &ActiveGames,
     player_id: i64,
-    roll_value: i32,
-    salon_id: String,
-    table_id: String,
-    active_games: Arc<Mutex<HashMap<(String, String), LiveGame>>>,
-    write: Arc<Mutex<futures_util::stream::SplitSink<S, Message>>>,
-) where
-    S: futures_util::Sink<Message> + Unpin + std::fmt::Debug + Send + 'static,
-{
-    // Oyunları kilitleyerek erişiyoruz
-    let mut active_games_guard = active_games.lock().await;
-
-    if let Some(game) = active_games_guard.get_mut(&(salon_id.clone(), table_id.clone())) {
-        if let Some(player) = game.players.iter_mut().find(|p| p.player_id == player_id) {
-            if player.rolls.len() < MAX_ROLLS {
-                // Zar atıldıktan sonra kaydediliyor
-                player.rolls.push(roll_value);
-                player.last_roll_time = Some(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
-
-                println!("Player {} zar attı: {}. Şu anki zar listesi: {:?}", player_id, roll_value, player.rolls);
-
-                // Güncel durumu tüm oyunculara yayınlama
-
-                broadcast_game_state(Arc::new(Mutex::new(game.clone())), write.clone()).await;
-
-            } else {
-                println!("Player {} için maksimum zar sayısına ulaşıldı.", player_id);
-            }
+    rules: &TurnRules,
+    event: GameEvent,
+    rooms: &SharedRooms,
+    checkpoints: &LiveGameRepository,
+) -> Result<(Vec<EngineEvent>, LiveGame), EngineError> {
+    let key = find_game_key(active_games, player_id).await.ok_or(EngineError::NotInGame)?;
+    drive_game(active_games, &key, rules, event, rooms, checkpoints).await
+}
+
+// Motorun olaylarından odaya gidecek mesajlar; zarlar değiştiyse ardından oyunun güncel durumu
+fn event_messages(game: &LiveGame, events: &[EngineEvent], rules: &TurnRules) -> Vec<serde_json::Value> {
+    let mut messages: Vec<_> = events.iter().filter_map(|event| match event {
+        EngineEvent::RollCountdown { player_id, seconds_left } => {
+            Some(json!({"action": "roll_countdown", "player_id": player_id, "seconds_left": seconds_left}))
+        }
+        EngineEvent::RollTimedOut { player_id, roll } => {
+            println!("Player {} zar süresini aştı, zar: {}", player_id, roll);
+            Some(json!({"action": "roll_timed_out", "player_id": player_id, "outcome": rules.timeout_action, "roll": roll}))
+        }
+        EngineEvent::PlayerDisconnected { player_id } => {
+            Some(json!({"action": "player_disconnected", "player_id": player_id, "grace_secs": rules.reconnect_grace_secs}))
+        }
+        EngineEvent::PlayerReconnected { player_id } => Some(json!({"action": "player_reconnected", "player_id": player_id})),
+        EngineEvent::PlayerForfeited { player_id } => {
+            println!("Player {} geri dönmedi, kalan zar hakları sıfırlandı.", player_id);
+            Some(json!({"action": "player_forfeited", "player_id": player_id}))
         }
+        EngineEvent::RollOffStarted { round, players } => {
+            println!("Oyun {} için {}. roll-off turu: {:?}", game.game_id, round, players);
+            Some(json!({"action": "roll_off_started", "game_id": game.game_id, "round": round, "players": players}))
+        }
+        _ => None,
+    }).collect();
+
+    if events.iter().any(EngineEvent::changes_rolls) {
+        messages.push(game_state_message(game));
     }
+    messages
 }
-async fn broadcast_game_state<S>(
-    live_game: Arc<Mutex<LiveGame>>,
-    write: Arc<Mutex<futures_util::stream::SplitSink<S, Message>>>,
-) where
-    S: futures_util::Sink<Message> + Unpin + Send + 'static,
-{
-    let game_guard = live_game.lock().await;
-
-    // Tüm oyuncuların güncel durumunu clientlara gönder
-    let players_state: Vec<_> = game_guard.players.iter().map(|p| {
+
+// Oyunun tüm oyuncularının güncel zarları
+pub fn game_state_message(game: &LiveGame) -> serde_json::Value {
+    let players_state: Vec<_> = game.players.iter().map(|p| {
         json!({
             "player_id": p.player_id,
             "rolls": p.rolls,
-            "total_roll": p.rolls.iter().sum::<i32>()
+            "total_roll": p.rolls.iter().sum::<i32>(),
+            "tiebreak_rolls": p.tiebreak_rolls
         })
     }).collect();
 
-    let game_id = game_guard.game_id.clone();
-
-    let game_state_message = json!({
+    json!({
         "action": "roll_update",
-        "game_id": game_id,
-        "players": players_state
-    });
-
-    if let Err(e) = write.lock().await.send(Message::Text(game_state_message.to_string())).await {
-
-    }
-
-    println!("Güncel oyun durumu gönderildi: {:?}", game_state_message);
+        "game_id": game.game_id,
+        "players": players_state,
+        "roll_off": game.roll_off
+    })
 }
 
-
-
-async fn broadcast_game_state_loop<S>(
-    write: Arc<Mutex<futures_util::stream::SplitSink<S, Message>>>,
-    active_games: Arc<Mutex<HashMap<(String, String), LiveGame>>>,
-) where
-    S: futures_util::Sink<Message> + Unpin + std::fmt::Debug,
-    <S as futures_util::Sink<Message>>::Error: std::fmt::Debug,
-{
+// Her saniye her oyunun durumunu yalnızca kendi odasına gönderir
+async fn broadcast_game_state_loop(active_games: ActiveGames, rooms: SharedRooms) {
     let mut interval = tokio::time::interval(Duration::from_secs(1));
 
     loop {
         interval.tick().await;
 
-        let games_guard = active_games.lock().await;
-
-        for game in games_guard.values() {
-            let players_state: Vec<_> = game.players.iter().map(|p| {
-                json!({
-                    "player_id": p.player_id,
-                    "rolls": p.rolls,
-                    "total_roll": p.rolls.iter().sum::<i32>()
-                })
-            }).collect();
-
-            let game_state_message = json!({
-                "action": "roll_update",
-                "game_id": game.game_id,
-                "players": players_state
-            });
-
-            if let Err(e) = write.lock().await.send(Message::Text(game_state_message.to_string())).await {
-                eprintln!("Durum güncellenirken hata oluştu: {:?}", e);
-            }
-
-            println!("Güncel oyun durumu gönderildi: {:?}", game_state_message);
+        let messages: Vec<(RoomKey, serde_json::Value)> = active_games.lock().await.iter()
+            .map(|(key, game)| (key.clone(), game_state_message(game)))
+            .collect();
+        let rooms = rooms.lock().await;
+        for (key, message) in &messages {
+            rooms.broadcast(key, message);
         }
     }
 }
 
 
-async fn check_winner<S>(
-    live_game: &mut LiveGame,
+// Motorun bitirdiği oyunu sonuçlandırır: ödüller ve sonuç (game_settlement), kazananın duyurusu
+// ve masanın yeni oyun için boşaltılması
+pub async fn finish_game(
+    live_game: &LiveGame,
+    outcome: &GameOutcome,
     mongo_client: &Client,
-    write: Arc<Mutex<futures_util::stream::SplitSink<S, Message>>>,
-    active_games: Arc<Mutex<HashMap<(String, String), LiveGame>>>,
-) where
-    S: futures_util::Sink<Message> + Unpin + std::fmt::Debug,
-    <S as futures_util::Sink<Message>>::Error: std::fmt::Debug,
-{
-    if live_game.state == GameState::Completed {
-        println!("Bu oyun zaten tamamlanmış.");
-        return;
-    }
-
-    live_game.state = GameState::Completed;
-
-    let mut sorted_players: Vec<_> = live_game.players.iter().collect();
-    sorted_players.sort_by_key(|p| -p.rolls.iter().sum::<i32>());
-
-    for (index, player) in sorted_players.iter().enumerate() {
-        match index {
-            0 => update_reputation_points(mongo_client, player.player_id, 20).await,
-            1 => update_reputation_points(mongo_client, player.player_id, 10).await,
-            2 => update_reputation_points(mongo_client, player.player_id, 5).await,
-            _ => update_reputation_points(mongo_client, player.player_id, 1).await,
-        }
-    }
-
-    let winner_id = sorted_players[0].player_id;
-    send_winner_notification(winner_id, &live_game.players, write.clone()).await;
-
-    let (bet_amount, percentage) = match live_game.salon_id.as_str() {
-        "1" => (20, 0.80),
-        "2" => (60, 0.85),
-        "3" => (100, 0.90),
-        "4" => (200, 0.95),
-        "5" => (300, 0.98),
-        "6" => (500, 0.99),
-        _ => (20, 0.80),
-    };
-    let amount_to_add = (bet_amount as f64 * percentage) as i32;
-    update_winner_ton_amount(mongo_client, winner_id, amount_to_add).await;
-
-    save_game_result_to_db(live_game, winner_id, mongo_client).await;
+    economy: &EconomyConfig,
+    rooms: &SharedRooms,
+    active_games: ActiveGames,
+    checkpoints: &LiveGameRepository,
+) {
+    let standings = settle_game(mongo_client, economy, live_game, outcome).await;
+
+    // Kazanan oyunun odasındaki herkese (oyuncular ve izleyiciler) duyurulur
+    let key = (live_game.salon_id.clone(), live_game.table_id.clone());
+    rooms.lock().await.broadcast_with(&key, |viewer| winner_message(live_game, outcome, &standings, viewer));
 
     let filter = doc! {
         "salon_id": live_game.salon_id.parse::<i32>().unwrap(),
         "tables.table_id": live_game.table_id.parse::<i32>().unwrap()
     };
 
+    // Masa boşaltılır ve yeni oyun için yeniden beklemeye alınır
     let update = doc! {
         "$set": {
-            "tables.$.players": []
+            "tables.$.players": [],
+            "tables.$.game_state": TableState::Waiting.to_string(),
+            "tables.$.fill_with_bots": false
         }
     };
 
@@ -357,104 +520,9 @@ async fn check_winner<S>(
         println!("Salon ve masadaki oyuncular temizlendi.");
     }
 
-    let key = (live_game.salon_id.clone(), live_game.table_id.clone());
     active_games.lock().await.remove(&key);
-    println!("Oyun salon_id: {}, table_id: {} sonlandı ve aktif oyunlardan kaldırıldı.", live_game.salon_id, live_game.table_id);
-}
-
-// Kullanıcının tüm item'lerinin reputation_points değerini oranla artırır
-async fn update_reputation_points(mongo_client: &Client, player_id: i64, increase_percentage: i32) {
-    let collection: Collection<Document> = mongo_client.database("users").collection("users");
-    let filter = doc! { "telegram_id": player_id };
-
-    if let Some(mut user_doc) = collection.find_one(filter.clone(), None).await.unwrap() {
-        if let Some(items) = user_doc.get_array_mut("items").ok() {
-            for item in items.iter_mut() {
-                if let Some(item_doc) = item.as_document_mut() {
-                    if let Some(rep_points) = item_doc.get_i32("reputation_points").ok() {
-                        let updated_points = rep_points + rep_points * increase_percentage / 100;
-                        item_doc.insert("reputation_points", updated_points);
-                    }
-                }
-            }
-
-            if let Err(e) = collection.update_one(filter, doc! { "$set": { "items": items } }, None).await {
-                eprintln!("Reputation points güncellenirken hata oluştu: {:?}", e);
-            } else {
-                println!("Player {} için reputation points başarıyla güncellendi.", player_id);
-            }
-        }
-    }
-}
-
-async fn update_winner_ton_amount(mongo_client: &Client, winner_id: i64, amount_to_add: i32) {
-    let collection: Collection<Document> = mongo_client.database("users").collection("users");
-
-    let filter = doc! { "telegram_id": winner_id };
-    let update = doc! { "$inc": { "hp": amount_to_add } };
-
-    match collection.update_one(filter, update, None).await {
-        Ok(_) => println!("Player {}'in ton_amount değeri başarıyla güncellendi. Eklenen miktar: {}", winner_id, amount_to_add),
-        Err(e) => eprintln!("ton_amount güncellenirken hata oluştu: {:?}", e),
-    }
-}
-
-async fn send_winner_notification<S>(
-    winner_id: i64,
-    players: &[Player],
-    write: Arc<Mutex<futures_util::stream::SplitSink<S, Message>>>
-) where
-    S: futures_util::Sink<Message> + Unpin + std::fmt::Debug,
-    <S as futures_util::Sink<Message>>::Error: std::fmt::Debug,
-{
-    for player in players {
-        let message = if player.player_id == winner_id {
-            json!({
-                "action": "winner_announced",
-                "message": "Kazandınız!",
-                "winner_id": winner_id
-            })
-        } else {
-            json!({
-                "action": "winner_announced",
-                "message": format!("Kazanan: Player {}", winner_id),
-                "winner_id": winner_id
-            })
-        };
-
-        let mut write_guard = write.lock().await;
-        if let Err(e) = write_guard.send(Message::Text(message.to_string())).await {
-            eprintln!("Mesaj gönderiminde hata: {:?}", e);
-        }
-    }
-}
-
-async fn save_game_result_to_db(live_game: &LiveGame, winner_id: i64, mongo_client: &Client) {
-    let collection: Collection<Document> = mongo_client.database("games").collection("game_results");
-
-    let game_result = GameResult {
-        game_id: live_game.game_id.clone(),
-        winner_id,
-        players: live_game.players.iter().map(|p| PlayerResult {
-            player_id: p.player_id,
-            rolls: p.rolls.clone(),
-        }).collect(),
-    };
-
-    let result_doc = doc! {
-        "game_id": game_result.game_id,
-        "winner_id": game_result.winner_id,
-        "players": game_result.players.iter().map(|p| {
-            doc! {
-                "player_id": p.player_id,
-                "rolls": p.rolls.clone(),
-            }
-        }).collect::<Vec<Document>>(),
-    };
-
-    if let Err(e) = collection.insert_one(result_doc, None).await {
-        eprintln!("Oyun sonucu MongoDB'ye kaydedilirken hata oluştu: {:?}", e);
-    } else {
-        println!("Oyun sonucu başarıyla MongoDB'ye kaydedildi.");
+    if let Err(e) = checkpoints.remove(&live_game.game_id).await {
+        eprintln!("Oyun {} checkpoint'i silinemedi: {:?}", live_game.game_id, e);
     }
+    println!("Oyun salon_id: {}, table_id: {} sonlandı ve aktif oyunlardan kaldırıldı.", live_game.salon_id, live_game.table_id);
 }
@@ -2,4 +2,17 @@ pub mod websocket_service;
 pub mod salon_websocket_service;
 pub mod redis_service;
 pub mod live_game_socket_services;
-pub mod live_game_socket_services_bot;
\ No newline at end of file
+pub mod provably_fair;pub mod tap_accounting;
+pub mod boost_engine;
+pub mod passive_income;
+pub mod economy_cache;
+pub mod table_lifecycle;
+pub mod game_rooms;
+pub mod roll_timer;
+pub mod auth;
+pub mod game_void;
+pub mod game_recovery;
+pub mod tie_break;
+pub mod game_engine;
+pub mod game_settlement;
+pub mod bot_players;
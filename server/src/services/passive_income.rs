@@ -0,0 +1,135 @@
+// services/passive_income.rs
+// Uygulama kapalıyken biriken pasif kazanç: item'ların itibar puanları saatlik bir oran belirler,
+// etkin boost bu oranı boost'un sürdüğü kısım için çarpar. Birikim en fazla `max_hours` sürer.
+// Item başına itibar puanı API'nin item tavanıyla, saatlik oran `passive_income_max_per_hour` ile sınırlıdır.
+
+use crate::config::app_config::AppConfig;
+use crate::models::user::User;
+use crate::services::boost_engine::BoostEngine;
+
+// api/src/models/economy.rs MAX_ITEM_REPUTATION_POINTS ile aynı; daha yüksek puanlı eski item'lar bu değerden sayılır
+const MAX_ITEM_REPUTATION_POINTS: i64 = 10_000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PassiveAccrual {
+    pub earned: i64,       // click_score
+    pub offline_secs: i64, // Son görülmeden bu yana geçen süre
+    pub capped: bool,      // Süre üst sınırı aşıldı mı
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PassiveIncome {
+    per_reputation_per_hour: i64,
+    max_per_hour: i64,
+    max_secs: i64,
+}
+
+impl PassiveIncome {
+    pub fn from_config(config: &AppConfig) -> Self {
+        PassiveIncome {
+            per_reputation_per_hour: config.passive_income_per_reputation_per_hour,
+            max_per_hour: config.passive_income_max_per_hour,
+            max_secs: config.passive_income_max_hours * 3600,
+        }
+    }
+
+    // Boost'suz saatlik kazanç
+    pub fn hourly_rate(&self, user: &User) -> i64 {
+        let reputation: i64 = user.items.iter().flatten()
+            .map(|item| (item.reputation_points as i64).clamp(0, MAX_ITEM_REPUTATION_POINTS))
+            .sum();
+        reputation.saturating_mul(self.per_reputation_per_hour).min(self.max_per_hour)
+    }
+
+    // `last_seen`'den `now`'a (Unix saniye) kadar biriken kazanç
+    pub fn accrue(&self, user: &User, boosts: &BoostEngine, last_seen: i64, now: i64) -> PassiveAccrual {
+        let offline_secs = (now - last_seen).max(0);
+        let counted_secs = offline_secs.min(self.max_secs);
+        let (from, to) = (last_seen, last_seen + counted_secs);
+        let rate = self.hourly_rate(user);
+
+        // Boost yalnızca sayılan aralıkla kesiştiği süre boyunca çarpar
+        let bonus = match &user.boost {
+            Some(boost) => {
+                let boosted_secs = (to.min(boost.ends_at()) - from.max(boost.start_time)).max(0);
+                let extra_pct = boosts.level_multiplier_pct(boost.level) - 100;
+                rate.saturating_mul(boosted_secs).saturating_mul(extra_pct) / 100
+            }
+            None => 0,
+        };
+
+        PassiveAccrual {
+            earned: rate.saturating_mul(counted_secs).saturating_add(bonus) / 3600,
+            offline_secs,
+            capped: offline_secs > counted_secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::Boost;
+
+    const DAY: i64 = 86_400;
+
+    fn passive() -> PassiveIncome {
+        PassiveIncome { per_reputation_per_hour: 1, max_per_hour: 10_000, max_secs: 3 * 3600 }
+    }
+
+    fn boosts() -> BoostEngine {
+        BoostEngine::new(vec![200, 300, 500])
+    }
+
+    #[test]
+    fn boost_that_expires_mid_offline_counts_until_it_ends() {
+        let mut user = User::for_test(1).with_items(&[3600]);
+        user.boost = Some(Boost { level: 1, start_time: 0, duration_days: 1 });
+
+        // Yarım saat boost'lu (%200), yarım saat boost'suz
+        let accrual = passive().accrue(&user, &boosts(), DAY - 1800, DAY + 1800);
+        assert_eq!(accrual.earned, 3600 + 1800);
+        assert!(!accrual.capped);
+    }
+
+    #[test]
+    fn hourly_rate_clamps_items_and_caps_the_total() {
+        let passive = passive();
+        assert_eq!(passive.hourly_rate(&User::for_test(1)), 0);
+        assert_eq!(passive.hourly_rate(&User::for_test(1).with_items(&[100, 250])), 350);
+        // Negatif puan sayılmaz, item başına en fazla MAX_ITEM_REPUTATION_POINTS
+        assert_eq!(passive.hourly_rate(&User::for_test(1).with_items(&[-500, 20_000])), MAX_ITEM_REPUTATION_POINTS);
+        assert_eq!(passive.hourly_rate(&User::for_test(1).with_items(&[i32::MAX, i32::MAX])), passive.max_per_hour);
+    }
+
+    #[test]
+    fn accrual_stops_at_max_hours() {
+        let user = User::for_test(1).with_items(&[3600]);
+        let accrual = passive().accrue(&user, &boosts(), 0, 2 * 3600);
+        assert_eq!(accrual, PassiveAccrual { earned: 7200, offline_secs: 2 * 3600, capped: false });
+
+        let accrual = passive().accrue(&user, &boosts(), 0, 10 * 3600);
+        assert_eq!(accrual, PassiveAccrual { earned: 3 * 3600, offline_secs: 10 * 3600, capped: true });
+    }
+
+    #[test]
+    fn boost_started_mid_offline_counts_from_its_start() {
+        let mut user = User::for_test(1).with_items(&[3600]);
+        user.boost = Some(Boost { level: 2, start_time: 1800, duration_days: 1 });
+
+        // İlk yarım saat boost'suz, sonraki yarım saat %300
+        let accrual = passive().accrue(&user, &boosts(), 0, 3600);
+        assert_eq!(accrual.earned, 1800 + 1800 * 3);
+
+        // Sayılmayan (max_hours sonrası) süredeki boost kazanç getirmez
+        user.boost = Some(Boost { level: 2, start_time: 4 * 3600, duration_days: 1 });
+        assert_eq!(passive().accrue(&user, &boosts(), 0, 5 * 3600).earned, 3 * 3600);
+    }
+
+    #[test]
+    fn last_seen_in_the_future_earns_nothing() {
+        let user = User::for_test(1).with_items(&[3600]);
+        let accrual = passive().accrue(&user, &boosts(), 10_000, 5_000);
+        assert_eq!(accrual, PassiveAccrual { earned: 0, offline_secs: 0, capped: false });
+    }
+}
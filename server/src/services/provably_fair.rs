@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Provably-fair zar türetimi (commit/reveal).
+//
+// Oyun başında sunucu rastgele bir `server_seed` üretir ve yalnızca SHA-256 özetini
+// (`server_seed_hash`) yayınlar. Her oyuncu ilk zarından önce bir `client_seed` gönderebilir.
+// Her zar şu şekilde türetilir:
+//
+//   digest = HMAC-SHA256(key = server_seed, msg = "{client_seed}:{player_id}:{nonce}")
+//
+// `nonce` oyuncunun kaçıncı zarı olduğudur (0'dan başlar). Digest'in byte'ları sırayla
+// okunur; 252'den küçük ilk byte için zar `byte % 6 + 1` olur (252 = 6 * 42, modulo
+// yanlılığı olmaması için). Oyun bitince `server_seed` açıklanır ve herkes zarları
+// yeniden hesaplayabilir.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fairness {
+    pub server_seed: String,
+    pub server_seed_hash: String,
+    pub client_seeds: HashMap<String, String>, // player_id -> client_seed
+}
+
+impl Fairness {
+    pub fn generate() -> Self {
+        let mut seed_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut seed_bytes);
+        let server_seed = to_hex(&seed_bytes);
+        let server_seed_hash = to_hex(&Sha256::digest(server_seed.as_bytes()));
+
+        Fairness {
+            server_seed,
+            server_seed_hash,
+            client_seeds: HashMap::new(),
+        }
+    }
+
+    pub fn client_seed(&self, player_id: &str) -> &str {
+        self.client_seeds.get(player_id).map(String::as_str).unwrap_or("")
+    }
+
+    pub fn set_client_seed(&mut self, player_id: &str, client_seed: String) {
+        self.client_seeds.insert(player_id.to_string(), client_seed);
+    }
+
+    // Oyuncunun `nonce` numaralı zarını türetir
+    pub fn roll(&self, player_id: &str, nonce: usize) -> i32 {
+        derive_roll(&self.server_seed, self.client_seed(player_id), player_id, nonce)
+    }
+
+    // Oyun sonunda `winner_announced` ile açıklanan bilgiler
+    pub fn reveal(&self) -> serde_json::Value {
+        json!({
+            "server_seed": self.server_seed,
+            "server_seed_hash": self.server_seed_hash,
+            "client_seeds": self.client_seeds,
+        })
+    }
+}
+
+pub fn derive_roll(server_seed: &str, client_seed: &str, player_id: &str, nonce: usize) -> i32 {
+    let mut mac = HmacSha256::new_from_slice(server_seed.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{}:{}:{}", client_seed, player_id, nonce).as_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let byte = digest.iter().copied().find(|b| *b < 252).unwrap_or(digest[0]);
+    (byte % 6) as i32 + 1
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bilinen cevaplar; server/src/services/provably_fair.rs ve api/src/services/provably_fair.rs
+    // aynı vektörleri kullanır. Değerler türetimden bağımsız (Python hmac/hashlib) hesaplanmıştır.
+    #[test]
+    fn derive_roll_matches_known_answers() {
+        let rolls: Vec<i32> = (0..10).map(|nonce| derive_roll("server-seed", "client-seed", "42", nonce)).collect();
+        assert_eq!(rolls, vec![4, 5, 6, 2, 2, 5, 4, 6, 3, 1]);
+
+        let without_client_seed: Vec<i32> = (0..5).map(|nonce| derive_roll("server-seed", "", "42", nonce)).collect();
+        assert_eq!(without_client_seed, vec![6, 4, 5, 6, 4]);
+    }
+
+    // Digest'in ilk byte'ı 254'tür (>= 252) ve atlanır; zar sonraki byte'tan (102) gelir
+    #[test]
+    fn derive_roll_skips_biased_bytes() {
+        assert_eq!(derive_roll("server-seed", "client-seed", "42", 52), 1);
+    }
+
+    #[test]
+    fn derive_roll_stays_on_die_faces() {
+        assert!((0..1_000).all(|nonce| (1..=6).contains(&derive_roll("server-seed", "client-seed", "42", nonce))));
+    }
+}
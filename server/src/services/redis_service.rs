@@ -4,8 +4,8 @@ pub struct RedisConnection {
     pub connection: Connection,
 }
 
-pub async fn setup_redis() -> Result<RedisConnection, redis::RedisError> {
-    let client = Client::open("redis://127.0.0.1/")?;
+pub async fn setup_redis(redis_url: &str) -> Result<RedisConnection, redis::RedisError> {
+    let client = Client::open(redis_url)?;
     let connection = client.get_async_connection().await?;
 
     // Redis'e başarılı bir şekilde bağlandığında log yazdırıyoruz
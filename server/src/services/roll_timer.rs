@@ -0,0 +1,57 @@
+// services/roll_timer.rs
+// Zar süresi. Oyuncu her zarını bir öncekinden (ilk zarda oyunun başlangıcından) itibaren
+// `roll_timeout_secs` içinde atmalıdır. Son `roll_warning_secs` boyunca odaya `roll_countdown`
+// gider; süre dolunca zar `roll_timeout_action`'a göre sunucuca atılır ya da 0 sayılır.
+// Roll-off turundaki zarlar da aynı süreye tabidir.
+// Bağlantısı kopan oyuncunun süresi işlemez; `reconnect_grace_secs` içinde dönmezse kalan zarları 0 sayılır.
+// Zarı kalan oyuncuların hepsi kopup süresinde dönmezse oyun terk edilmiş sayılır ve iptal edilir.
+// Tüm zarları atılmış oyunlar da burada sonuçlandırılır, böylece hiçbir masa takılı kalmaz.
+// Kurallar oyun motorundadır (game_engine); bu döngü her saniye her oyuna `GameEvent::Tick` verir.
+
+use std::sync::Arc;
+use mongodb::Client;
+use tokio::time::{self, Duration};
+use crate::config::app_config::AppConfig;
+use crate::services::economy_cache::EconomyCache;
+use crate::services::game_engine::{finished_outcome, EngineEvent, GameEvent};
+use crate::services::game_rooms::{RoomKey, SharedRooms};
+use crate::services::game_void::{take_started_game, void_game};
+use crate::models::livegame::{GameState, VoidReason};
+use crate::repository::live_game_repository::LiveGameRepository;
+use crate::services::live_game_socket_services::{drive_game, finish_game};
+use crate::services::table_lifecycle::ActiveGames;
+
+pub async fn run_roll_timer(
+    mongo_client: Client,
+    config: Arc<AppConfig>,
+    economy: Arc<EconomyCache>,
+    active_games: ActiveGames,
+    rooms: SharedRooms,
+    checkpoints: LiveGameRepository,
+) {
+    let rules = config.turn_rules();
+    let mut interval = time::interval(Duration::from_secs(1));
+
+    loop {
+        interval.tick().await;
+        let keys: Vec<RoomKey> = active_games.lock().await.iter()
+            .filter(|(_, game)| game.state == GameState::Started)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in keys {
+            let Ok((events, game)) = drive_game(&active_games, &key, &rules, GameEvent::Tick(rules), &rooms, &checkpoints).await else {
+                continue;
+            };
+
+            if events.contains(&EngineEvent::Abandoned) {
+                if let Some(mut game) = take_started_game(&active_games, &game.game_id).await {
+                    void_game(&mongo_client, &checkpoints, &rooms, &mut game, VoidReason::Abandoned).await;
+                }
+            } else if let Some(outcome) = finished_outcome(&events) {
+                // Zar süresi dolmasıyla ya da bağlantı kopmasıyla biten oyunlar
+                finish_game(&game, outcome, &mongo_client, &economy.current(), &rooms, active_games.clone(), &checkpoints).await;
+            }
+        }
+    }
+}
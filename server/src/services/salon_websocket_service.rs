@@ -10,6 +10,7 @@ use crate::repository::salon_repository::SalonRepository;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use mongodb::bson::{doc, Bson, Document}; // BSON doc makrosunu içe aktarıyoruz
+use crate::config::app_config::AppConfig;
 
 #[derive(Serialize, Deserialize, Debug)]
 struct SalonMessage {
@@ -17,9 +18,9 @@ struct SalonMessage {
     telegram_id: Option<i64>, // Telegram ID ile bağlanacağız
 }
 
-pub async fn run_salon_websocket_server(mongo_client: Client) {
-    let addr = "127.0.0.1:9002";
-    let listener = TcpListener::bind(addr).await.expect("WebSocket sunucusu başlatılamadı!");
+pub async fn run_salon_websocket_server(mongo_client: Client, config: Arc<AppConfig>) {
+    let addr = config.address(config.salon_port);
+    let listener = TcpListener::bind(&addr).await.expect("WebSocket sunucusu başlatılamadı!");
 
     let salon_repo = Arc::new(SalonRepository::new(&mongo_client));
     let active_connections = Arc::new(Mutex::new(std::collections::HashMap::new())); // Kullanıcıların bağlantılarını tutuyoruz.
@@ -178,9 +179,10 @@ where
     if let Ok(mut salons) = salon_repo.get_all_salons().await {
         // Sadece `salon_id` 0 olanı bul
         if let Some(salon) = salons.iter_mut().find(|s| s.salon_id == 0) {
-            // `players` sayısı 4'ten az veya boş olan masaları temizle
+            // Dolmamış (oyuncu sayısı `max_players`'tan az) masaları temizle
+            let max_players = salon.max_players as usize;
             for table in &mut salon.tables {
-                if table.players.len() < 4 {
+                if table.players.len() < max_players {
                     table.players.clear();
                 }
             }
@@ -0,0 +1,250 @@
+// services/table_lifecycle.rs
+// Masaların yaşam döngüsü sunucudadır; istemci oyun başlatamaz:
+//   Waiting -> Ready     tüm koltuklar dolu ve herkes ödedi; geri sayım başlar
+//   Ready   -> Waiting   geri sayım sırasında biri kalktı (API kalkan oyuncunun iadesini yapıp masayı kendisi de geri alır)
+//   Ready   -> Started   geri sayım bitti; LiveGame sunucuda oluşturulur
+//   Started -> Waiting   oyun bitti, masa boşaltıldı (check_winner)
+// Beklemedeki masada oturan oyuncu `bot_fill_after_secs` beklediyse (join_with_bots ile oturduysa
+// hemen) boş koltuklar botlarla dolar; masada yalnızca botlar kaldıysa botlar kalkar.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use mongodb::Client;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tokio::time::{self, Duration, Instant};
+use crate::config::app_config::AppConfig;
+use crate::models::bot::is_bot_id;
+use crate::models::salon::Salon;
+use crate::models::table::{GameState, Table};
+use crate::repository::bot_repository::BotRepository;
+use crate::repository::salon_repository::SalonRepository;
+use crate::services::game_rooms::{RoomKey, SharedRooms};
+use crate::models::livegame::{LiveGame, ParticipantKind, Player};
+use crate::repository::live_game_repository::LiveGameRepository;
+use crate::services::live_game_socket_services::{checkpoint, start_game, unix_now};
+
+pub type ActiveGames = Arc<Mutex<HashMap<RoomKey, LiveGame>>>;
+
+// Masada en az bir insan oyuncu olmalı; botlar oturdukları anda hazırdır
+fn is_ready_to_start(salon: &Salon, table: &Table) -> bool {
+    table.players.len() >= salon.max_players as usize
+        && table.players.iter().all(|p| p.has_paid)
+        && table.players.iter().any(|p| !is_bot_id(p.player_id))
+}
+
+pub async fn run_table_lifecycle(
+    mongo_client: Client,
+    config: Arc<AppConfig>,
+    active_games: ActiveGames,
+    rooms: SharedRooms,
+    checkpoints: LiveGameRepository,
+) {
+    let salon_repo = SalonRepository::new(&mongo_client);
+    let bot_repo = BotRepository::new(&mongo_client);
+    if let Err(e) = bot_repo.ensure_indexes().await {
+        eprintln!("Bot indeksleri oluşturulamadı: {:?}", e);
+    }
+    let countdown = Duration::from_secs(config.game_start_countdown_secs);
+    let mut deadlines: HashMap<(i32, i32), Instant> = HashMap::new();
+    let mut waiting_since: HashMap<(i32, i32), Instant> = HashMap::new();
+    let mut interval = time::interval(Duration::from_secs(1));
+
+    loop {
+        interval.tick().await;
+        let salons = match salon_repo.get_all_salons().await {
+            Ok(salons) => salons,
+            Err(e) => {
+                eprintln!("Masa durumu okunamadı: {:?}", e);
+                continue;
+            }
+        };
+
+        // Aynı turda doldurulan masalara aynı bot oturtulmasın diye oturtulan botlar da eklenir
+        let mut seated_bots: Vec<i64> = salons.iter()
+            .flat_map(|salon| salon.tables.iter())
+            .flat_map(|table| table.players.iter().map(|p| p.player_id))
+            .filter(|id| is_bot_id(*id))
+            .collect();
+
+        for salon in &salons {
+            for table in &salon.tables {
+                let key = (salon.salon_id, table.table_id);
+                let room: RoomKey = (salon.salon_id.to_string(), table.table_id.to_string());
+                let ready = is_ready_to_start(salon, table);
+
+                let only_bots = !table.players.is_empty() && table.players.iter().all(|p| is_bot_id(p.player_id));
+                if only_bots && matches!(table.game_state, GameState::Waiting | GameState::Ready) {
+                    remove_bots(&salon_repo, salon, table, &rooms).await;
+                    continue;
+                }
+                if !matches!(table.game_state, GameState::Waiting) {
+                    waiting_since.remove(&key);
+                } else if bots_due(salon, table, &config, &mut waiting_since) {
+                    fill_seats(&salon_repo, &bot_repo, &config, salon, table, &mut seated_bots, &rooms).await;
+                    continue;
+                }
+
+                match table.game_state {
+                    GameState::Waiting if ready => {
+                        if !transition(&salon_repo, key, GameState::Waiting, GameState::Ready).await {
+                            continue;
+                        }
+                        deadlines.insert(key, Instant::now() + countdown);
+                        let message = json!({
+                            "action": "game_starting",
+                            "salon_id": salon.salon_id.to_string(),
+                            "table_id": table.table_id.to_string(),
+                            "seconds": countdown.as_secs()
+                        });
+                        rooms.lock().await.broadcast(&room, &message);
+                    }
+                    GameState::Ready if !ready => {
+                        if !transition(&salon_repo, key, GameState::Ready, GameState::Waiting).await {
+                            continue;
+                        }
+                        deadlines.remove(&key);
+                        rooms.lock().await.broadcast(&room, &countdown_cancelled(salon, table));
+                    }
+                    // Geri sayım sırasında API masayı (oyuncu kalkınca) kendisi beklemeye aldı
+                    GameState::Waiting if deadlines.contains_key(&key) => {
+                        deadlines.remove(&key);
+                        rooms.lock().await.broadcast(&room, &countdown_cancelled(salon, table));
+                    }
+                    GameState::Ready => {
+                        // Sunucu geri sayım sırasında yeniden başladıysa geri sayım baştan sayılır
+                        let deadline = *deadlines.entry(key).or_insert_with(|| Instant::now() + countdown);
+                        if Instant::now() < deadline {
+                            continue;
+                        }
+                        deadlines.remove(&key);
+                        if transition(&salon_repo, key, GameState::Ready, GameState::Started).await {
+                            launch_game(salon, table, &active_games, &rooms, &checkpoints).await;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+// Boş koltuklar botlarla dolmalı mı: masada oturan oyuncu `bot_fill_after_secs` kadar bekledi
+// (join_with_bots ile oturduysa beklemez)
+fn bots_due(salon: &Salon, table: &Table, config: &AppConfig, waiting_since: &mut HashMap<(i32, i32), Instant>) -> bool {
+    let key = (salon.salon_id, table.table_id);
+    let seated_human = table.players.iter().any(|p| !is_bot_id(p.player_id));
+    if !seated_human || table.players.len() >= salon.max_players as usize {
+        waiting_since.remove(&key);
+        return false;
+    }
+    if table.fill_with_bots {
+        return true;
+    }
+    if config.bot_fill_after_secs == 0 {
+        return false;
+    }
+    let since = *waiting_since.entry(key).or_insert_with(Instant::now);
+    since.elapsed() >= Duration::from_secs(config.bot_fill_after_secs)
+}
+
+async fn fill_seats(
+    salon_repo: &SalonRepository,
+    bot_repo: &BotRepository,
+    config: &AppConfig,
+    salon: &Salon,
+    table: &Table,
+    seated_bots: &mut Vec<i64>,
+    rooms: &SharedRooms,
+) {
+    let seats = salon.max_players as usize - table.players.len();
+    let bots = match bot_repo.pick_idle(seated_bots, seats, &config.bot_strategies, unix_now() as i64).await {
+        Ok(bots) => bots,
+        Err(e) => {
+            eprintln!("Masa {}/{} için bot bulunamadı: {:?}", salon.salon_id, table.table_id, e);
+            return;
+        }
+    };
+    let bot_ids: Vec<i64> = bots.iter().map(|bot| bot.bot_id).collect();
+    match salon_repo.seat_bots(salon.salon_id, table.table_id, table.players.len(), &bot_ids).await {
+        Ok(true) => {
+            println!("Masa {}/{} botlarla dolduruldu: {:?}", salon.salon_id, table.table_id, bot_ids);
+            seated_bots.extend(&bot_ids);
+            let message = json!({
+                "action": "bots_joined",
+                "salon_id": salon.salon_id.to_string(),
+                "table_id": table.table_id.to_string(),
+                "bots": bots.iter().map(|bot| json!({ "player_id": bot.bot_id, "name": bot.name })).collect::<Vec<_>>()
+            });
+            let room: RoomKey = (salon.salon_id.to_string(), table.table_id.to_string());
+            rooms.lock().await.broadcast(&room, &message);
+        }
+        // Masa okunduğundan beri değişti; bir sonraki turda yeniden denenir
+        Ok(false) => {}
+        Err(e) => eprintln!("Masa {}/{} botlarla doldurulamadı: {:?}", salon.salon_id, table.table_id, e),
+    }
+}
+
+async fn remove_bots(salon_repo: &SalonRepository, salon: &Salon, table: &Table, rooms: &SharedRooms) {
+    match salon_repo.remove_bots(salon.salon_id, table.table_id).await {
+        Ok(true) => {
+            let message = json!({
+                "action": "bots_left",
+                "salon_id": salon.salon_id.to_string(),
+                "table_id": table.table_id.to_string()
+            });
+            let room: RoomKey = (salon.salon_id.to_string(), table.table_id.to_string());
+            rooms.lock().await.broadcast(&room, &message);
+        }
+        Ok(false) => {}
+        Err(e) => eprintln!("Masa {}/{} botları kaldırılamadı: {:?}", salon.salon_id, table.table_id, e),
+    }
+}
+
+fn countdown_cancelled(salon: &Salon, table: &Table) -> Value {
+    json!({
+        "action": "countdown_cancelled",
+        "salon_id": salon.salon_id.to_string(),
+        "table_id": table.table_id.to_string()
+    })
+}
+
+async fn transition(salon_repo: &SalonRepository, (salon_id, table_id): (i32, i32), from: GameState, to: GameState) -> bool {
+    match salon_repo.set_table_state(salon_id, table_id, from, to).await {
+        Ok(changed) => changed,
+        Err(e) => {
+            eprintln!("Masa {}/{} durumu güncellenemedi: {:?}", salon_id, table_id, e);
+            false
+        }
+    }
+}
+
+async fn launch_game(salon: &Salon, table: &Table, active_games: &ActiveGames, rooms: &SharedRooms, checkpoints: &LiveGameRepository) {
+    // İlk zarın süresi oyunun başlangıcından itibaren sayılır
+    let started_at = unix_now();
+    let players = table.players.iter().map(|p| Player {
+        player_id: p.player_id,
+        rolls: vec![],
+        is_active: true,
+        last_roll_time: Some(started_at),
+        disconnected_at: None,
+        tiebreak_rolls: vec![],
+        kind: if is_bot_id(p.player_id) { ParticipantKind::Bot } else { ParticipantKind::Human },
+    }).collect();
+    let mut game = start_game(players, salon, table.table_id).await;
+    game.touch();
+    checkpoint(checkpoints, &game).await;
+
+    let message = json!({
+        "action": "game_started",
+        "game_id": game.game_id,
+        "salon_id": game.salon_id,
+        "table_id": game.table_id,
+        "players": table.players.iter().map(|p| p.player_id).collect::<Vec<_>>(),
+        "server_seed_hash": game.fairness.server_seed_hash
+    });
+    println!("Oyun {} başladı: salon_id: {}, table_id: {}", game.game_id, game.salon_id, game.table_id);
+    let room = (game.salon_id.clone(), game.table_id.clone());
+    active_games.lock().await.insert(room.clone(), game);
+    rooms.lock().await.broadcast(&room, &message);
+}
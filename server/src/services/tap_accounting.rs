@@ -0,0 +1,277 @@
+// services/tap_accounting.rs
+// Tıklamaların sunucu tarafı muhasebesi: enerji havuzu, saniye başına tıklama sınırı ve
+// otomatik tıklayıcı tespiti. Bu modülde G/Ç yoktur; kalıcılık websocket_service'tedir.
+
+use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use crate::config::app_config::AppConfig;
+
+// İstemcinin toplu gönderdiği tıklamalar: `at` anında (Unix ms) `count` tıklama
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct TapBatch {
+    pub count: u32,
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TapRules {
+    pub energy_max: i32,
+    pub energy_regen_per_sec: i32,
+    pub max_clicks_per_sec: u32,
+    pub max_batch_age_ms: i64,  // Daha eski zaman damgalı tıklamalar sayılmaz
+    pub max_clock_skew_ms: i64, // İstemci saati sunucudan en fazla bu kadar ileride olabilir
+}
+
+impl TapRules {
+    pub fn from_config(config: &AppConfig) -> Self {
+        TapRules {
+            energy_max: config.tap_energy_max,
+            energy_regen_per_sec: config.tap_energy_regen_per_sec,
+            max_clicks_per_sec: config.tap_max_clicks_per_sec,
+            max_batch_age_ms: config.tap_max_batch_age_ms,
+            max_clock_skew_ms: config.tap_max_clock_skew_ms,
+        }
+    }
+}
+
+// Kullanıcı belgesinde saklanan enerji: son güncellemeden bu yana zamanla dolar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnergyPool {
+    pub energy: i32,
+    pub updated_at: i64, // Unix ms
+}
+
+impl EnergyPool {
+    // Kayıtlı enerjisi olmayan kullanıcı dolu havuzla başlar
+    pub fn from_stored(energy: Option<i32>, updated_at: Option<i64>, rules: &TapRules, now: i64) -> Self {
+        match (energy, updated_at) {
+            (Some(energy), Some(updated_at)) => EnergyPool { energy: energy.clamp(0, rules.energy_max), updated_at },
+            _ => EnergyPool { energy: rules.energy_max, updated_at: now },
+        }
+    }
+
+    // `now` anına kadar dolan enerji. Kesirli birikim kaybolmasın diye zaman damgası yalnızca
+    // kazanılan enerji kadar ilerletilir; havuz dolunca `now`'a eşitlenir.
+    pub fn regenerate(self, rules: &TapRules, now: i64) -> Self {
+        let elapsed = (now - self.updated_at).max(0);
+        let gained = elapsed.saturating_mul(rules.energy_regen_per_sec as i64) / 1000;
+        let energy = self.energy as i64 + gained;
+        if energy >= rules.energy_max as i64 {
+            return EnergyPool { energy: rules.energy_max, updated_at: now };
+        }
+        EnergyPool {
+            energy: energy as i32,
+            updated_at: self.updated_at + gained * 1000 / rules.energy_regen_per_sec as i64,
+        }
+    }
+}
+
+// İncelemeye alınacak şüpheli davranış türleri
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TapAnomaly {
+    RateExceeded,      // Saniye başına sınırın üzerinde tıklama
+    InvalidTimestamp,  // Çok eski ya da gelecekteki zaman damgası
+    UniformIntervals,  // İnsan için fazla düzenli aralıklar (otomatik tıklayıcı)
+}
+
+impl TapAnomaly {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TapAnomaly::RateExceeded => "rate_exceeded",
+            TapAnomaly::InvalidTimestamp => "invalid_timestamp",
+            TapAnomaly::UniformIntervals => "uniform_intervals",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TapOutcome {
+    pub accepted: u32,
+    pub rejected: u32,
+    pub anomalies: Vec<TapAnomaly>,
+}
+
+// Otomatik tıklayıcı tespiti için incelenen son aralık sayısı
+const INTERVAL_WINDOW: usize = 30;
+// Ortalama aralık bundan kısa ve sapma bundan küçükse aralıklar insan için fazla düzenli sayılır
+const UNIFORM_MAX_MEAN_MS: f64 = 250.0;
+const UNIFORM_MAX_STDDEV_MS: f64 = 3.0;
+
+// Bağlantı başına tutulan tıklama geçmişi: saniye başına sayaçlar ve son zaman damgaları
+#[derive(Debug, Default)]
+pub struct TapLimiter {
+    per_second: VecDeque<(i64, u32)>, // (Unix saniye, o saniyede kabul edilen tıklama)
+    recent: VecDeque<i64>,            // Son tekil tıklamaların zaman damgaları (ms)
+}
+
+impl TapLimiter {
+    // Toplu tıklamaları süzer: geçersiz zaman damgalarını ve saniye başına sınırı aşanları düşer.
+    // Dönen `accepted` henüz enerjiyle sınırlanmamıştır.
+    pub fn admit(&mut self, batches: &[TapBatch], rules: &TapRules, now: i64) -> TapOutcome {
+        let mut outcome = TapOutcome::default();
+        let mut sorted: Vec<TapBatch> = batches.iter().copied().filter(|b| b.count > 0).collect();
+        sorted.sort_by_key(|b| b.at);
+
+        for batch in sorted {
+            if batch.at < now - rules.max_batch_age_ms || batch.at > now + rules.max_clock_skew_ms {
+                outcome.rejected += batch.count;
+                push_unique(&mut outcome.anomalies, TapAnomaly::InvalidTimestamp);
+                continue;
+            }
+
+            let second = batch.at.div_euclid(1000);
+            let used = self.per_second.iter().find(|(s, _)| *s == second).map_or(0, |(_, c)| *c);
+            let allowed = rules.max_clicks_per_sec.saturating_sub(used).min(batch.count);
+            if allowed < batch.count {
+                outcome.rejected += batch.count - allowed;
+                push_unique(&mut outcome.anomalies, TapAnomaly::RateExceeded);
+            }
+            if allowed > 0 {
+                match self.per_second.iter_mut().find(|(s, _)| *s == second) {
+                    Some((_, count)) => *count += allowed,
+                    None => self.per_second.push_back((second, allowed)),
+                }
+                outcome.accepted += allowed;
+            }
+            if batch.count == 1 {
+                self.recent.push_back(batch.at);
+                if self.recent.len() > INTERVAL_WINDOW + 1 {
+                    self.recent.pop_front();
+                }
+            }
+        }
+
+        // Zaman penceresinin dışına çıkan saniyeleri unut
+        let oldest = (now - rules.max_batch_age_ms).div_euclid(1000);
+        self.per_second.retain(|(s, _)| *s >= oldest);
+
+        if self.has_uniform_intervals() {
+            push_unique(&mut outcome.anomalies, TapAnomaly::UniformIntervals);
+            self.recent.clear();
+        }
+        outcome
+    }
+
+    fn has_uniform_intervals(&self) -> bool {
+        if self.recent.len() <= INTERVAL_WINDOW {
+            return false;
+        }
+        let intervals: Vec<f64> = self.recent.iter().zip(self.recent.iter().skip(1)).map(|(a, b)| (b - a) as f64).collect();
+        let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+        let variance = intervals.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / intervals.len() as f64;
+        mean < UNIFORM_MAX_MEAN_MS && variance.sqrt() < UNIFORM_MAX_STDDEV_MS
+    }
+}
+
+// Kabul edilen tıklamaları enerjiyle sınırlar; harcanan enerji tıklama başına 1'dir
+pub fn spend_energy(pool: EnergyPool, outcome: &mut TapOutcome) -> EnergyPool {
+    let affordable = outcome.accepted.min(pool.energy.max(0) as u32);
+    outcome.rejected += outcome.accepted - affordable;
+    outcome.accepted = affordable;
+    EnergyPool { energy: pool.energy - affordable as i32, updated_at: pool.updated_at }
+}
+
+fn push_unique(anomalies: &mut Vec<TapAnomaly>, anomaly: TapAnomaly) {
+    if !anomalies.contains(&anomaly) {
+        anomalies.push(anomaly);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: TapRules = TapRules {
+        energy_max: 100,
+        energy_regen_per_sec: 3,
+        max_clicks_per_sec: 20,
+        max_batch_age_ms: 10_000,
+        max_clock_skew_ms: 2_000,
+    };
+
+    fn single_taps(times: impl IntoIterator<Item = i64>) -> Vec<TapBatch> {
+        times.into_iter().map(|at| TapBatch { count: 1, at }).collect()
+    }
+
+    #[test]
+    fn regeneration_carries_fractional_energy() {
+        let pool = EnergyPool { energy: 0, updated_at: 0 };
+        // 500 ms'de 1,5 enerji: 1 kazanılır, zaman damgası yalnızca 1 enerjilik süre (333 ms) ilerler
+        let pool = pool.regenerate(&RULES, 500);
+        assert_eq!(pool, EnergyPool { energy: 1, updated_at: 333 });
+        // Kalan yarım enerji kaybolmaz: 1 saniyede toplam 3
+        let pool = pool.regenerate(&RULES, 1_000);
+        assert_eq!(pool.energy, 3);
+        // Saat geri giderse enerji azalmaz
+        assert_eq!(pool.regenerate(&RULES, 0), pool);
+    }
+
+    #[test]
+    fn regeneration_stops_at_the_energy_cap() {
+        let pool = EnergyPool { energy: 99, updated_at: 0 }.regenerate(&RULES, 60_000);
+        assert_eq!(pool, EnergyPool { energy: RULES.energy_max, updated_at: 60_000 });
+        assert_eq!(EnergyPool::from_stored(Some(500), Some(0), &RULES, 0).energy, RULES.energy_max);
+        assert_eq!(EnergyPool::from_stored(None, None, &RULES, 7), EnergyPool { energy: RULES.energy_max, updated_at: 7 });
+    }
+
+    #[test]
+    fn rate_cap_spans_batches_in_the_same_second() {
+        let mut limiter = TapLimiter::default();
+        let now = 5_900;
+        let first = limiter.admit(&[TapBatch { count: 15, at: 5_100 }], &RULES, now);
+        assert_eq!((first.accepted, first.rejected), (15, 0));
+
+        let second = limiter.admit(&[TapBatch { count: 10, at: 5_800 }, TapBatch { count: 4, at: 6_000 }], &RULES, now + 100);
+        assert_eq!((second.accepted, second.rejected), (9, 5));
+        assert_eq!(second.anomalies, vec![TapAnomaly::RateExceeded]);
+    }
+
+    #[test]
+    fn stale_and_future_timestamps_are_rejected() {
+        let mut limiter = TapLimiter::default();
+        let now = 100_000;
+        let batches = [
+            TapBatch { count: 2, at: now - RULES.max_batch_age_ms - 1 },
+            TapBatch { count: 3, at: now + RULES.max_clock_skew_ms + 1 },
+            TapBatch { count: 4, at: now },
+        ];
+        let outcome = limiter.admit(&batches, &RULES, now);
+        assert_eq!((outcome.accepted, outcome.rejected), (4, 5));
+        assert_eq!(outcome.anomalies, vec![TapAnomaly::InvalidTimestamp]);
+    }
+
+    #[test]
+    fn seconds_outside_the_window_are_pruned() {
+        let mut limiter = TapLimiter::default();
+        limiter.admit(&[TapBatch { count: 20, at: 1_000 }], &RULES, 1_000);
+        limiter.admit(&[], &RULES, 1_000 + RULES.max_batch_age_ms + 1_000);
+        assert!(limiter.per_second.is_empty());
+    }
+
+    #[test]
+    fn energy_limits_accepted_taps() {
+        let mut outcome = TapOutcome { accepted: 8, rejected: 1, anomalies: vec![] };
+        let pool = spend_energy(EnergyPool { energy: 5, updated_at: 42 }, &mut outcome);
+        assert_eq!((outcome.accepted, outcome.rejected), (5, 4));
+        assert_eq!(pool, EnergyPool { energy: 0, updated_at: 42 });
+    }
+
+    #[test]
+    fn uniform_tap_stream_is_flagged_as_autoclicker() {
+        let mut limiter = TapLimiter::default();
+        let taps = single_taps((0..=INTERVAL_WINDOW as i64).map(|i| 10_000 + i * 100));
+        let outcome = limiter.admit(&taps, &RULES, 13_000);
+        assert_eq!(outcome.accepted, taps.len() as u32);
+        assert_eq!(outcome.anomalies, vec![TapAnomaly::UniformIntervals]);
+        assert!(limiter.recent.is_empty());
+    }
+
+    #[test]
+    fn jittered_tap_stream_is_not_flagged() {
+        let mut limiter = TapLimiter::default();
+        let jitter = [0, 35, -20, 10, -40, 25, -5, 40, -30, 15];
+        let taps = single_taps((0..=INTERVAL_WINDOW).map(|i| 10_000 + i as i64 * 100 + jitter[i % jitter.len()]));
+        let outcome = limiter.admit(&taps, &RULES, 13_100);
+        assert!(outcome.anomalies.is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+// services/tie_break.rs
+// Oyun sonunda oyuncular zar toplamına göre sıralanır; toplamları eşit oyuncular salonun
+// `tie_rule`'una göre ayrılır:
+//   roll_off     eşitlik ödül alan bir sırayı etkiliyorsa eşit oyuncular birer zar daha atar
+//                (tur tur, en fazla MAX_ROLL_OFF_ROUNDS); yine eşit kalanlar ödülü bölüşür
+//   split_pot    eşit oyuncular aynı yeri paylaşır ve o sıraların ödüllerini eşit bölüşür
+//   highest_die  tek zarda en yüksek değer (eşitse ikinci en yüksek, ...) öne geçer; zarları
+//                birebir aynı olanlar ödülü bölüşür
+// Bölüşmede kalan küsurat (aşağı yuvarlama) sistemde kalır. Bu modül yalnızca hesap yapar.
+
+use crate::models::salon::TieRule;
+
+pub const MAX_ROLL_OFF_ROUNDS: usize = 3;
+
+pub struct Scorecard<'a, Id> {
+    pub player_id: Id,
+    pub rolls: &'a [i32],
+    pub tiebreak_rolls: &'a [i32], // roll_off turlarında atılan zarlar
+}
+
+#[derive(Debug, Clone)]
+pub struct Standing<Id> {
+    pub player_id: Id,
+    pub place: usize, // 1'den başlar; eşit kalan oyuncular aynı yeri paylaşır
+    pub payout: i32,
+}
+
+// Oyuncuları yerlerine göre gruplar, en iyi yer önce; bir gruptaki oyuncular aynı yeri paylaşır
+pub fn placings<Id: Clone>(cards: &[Scorecard<Id>], rule: TieRule) -> Vec<Vec<Id>> {
+    let mut ranked: Vec<_> = cards.iter().map(|card| (score(card, rule), card.player_id.clone())).collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut groups: Vec<(_, Vec<Id>)> = vec![];
+    for (key, player_id) in ranked {
+        match groups.last_mut() {
+            Some((last_key, group)) if *last_key == key => group.push(player_id),
+            _ => groups.push((key, vec![player_id])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+fn score<Id>(card: &Scorecard<Id>, rule: TieRule) -> (i32, Vec<i32>) {
+    let tiebreak = match rule {
+        TieRule::SplitPot => vec![],
+        TieRule::HighestDie => {
+            let mut dice = card.rolls.to_vec();
+            dice.sort_unstable_by(|a, b| b.cmp(a));
+            dice
+        }
+        TieRule::RollOff => card.tiebreak_rolls.to_vec(),
+    };
+    (card.rolls.iter().sum(), tiebreak)
+}
+
+// Ödül alan ilk `paid_places` sıradan birini paylaşan, yani roll-off'ta zar atması gereken oyuncular
+pub fn roll_off_players<Id: Clone>(placings: &[Vec<Id>], paid_places: usize) -> Vec<Id> {
+    let mut start = 0;
+    let mut players = vec![];
+    for group in placings {
+        if start >= paid_places {
+            break;
+        }
+        if group.len() > 1 {
+            players.extend(group.iter().cloned());
+        }
+        start += group.len();
+    }
+    players
+}
+
+// Her oyuncunun yeri ve ödülü; `payouts` sıralamaya göre ödüllerdir [1., 2., ...]
+pub fn standings<Id: Clone>(placings: &[Vec<Id>], payouts: &[i32]) -> Vec<Standing<Id>> {
+    let mut start = 0;
+    let mut standings = vec![];
+    for group in placings {
+        let pot: i32 = payouts.iter().skip(start).take(group.len()).sum();
+        let share = pot / group.len() as i32;
+        standings.extend(group.iter().map(|player_id| Standing { player_id: player_id.clone(), place: start + 1, payout: share }));
+        start += group.len();
+    }
+    standings
+}
+
+// Birinciliği birden fazla oyuncu aynı zar toplamıyla bitirdi mi
+pub fn tied_for_first<Id>(cards: &[Scorecard<Id>]) -> bool {
+    let totals: Vec<i32> = cards.iter().map(|card| card.rolls.iter().sum()).collect();
+    let best = totals.iter().copied().max().unwrap_or_default();
+    totals.iter().filter(|total| **total == best).count() > 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card<'a>(player_id: &'a str, rolls: &'a [i32], tiebreak_rolls: &'a [i32]) -> Scorecard<'a, &'a str> {
+        Scorecard { player_id, rolls, tiebreak_rolls }
+    }
+
+    fn payout_of(standings: &[Standing<&str>], player_id: &str) -> i32 {
+        standings.iter().find(|s| s.player_id == player_id).unwrap().payout
+    }
+
+    #[test]
+    fn split_pot_shares_a_paid_and_an_unpaid_place() {
+        let cards = [
+            card("a", &[6, 6, 6, 6, 6], &[]),
+            card("b", &[5, 5, 5, 5, 5], &[]),
+            card("c", &[5, 5, 5, 5, 5], &[]),
+            card("d", &[1, 1, 1, 1, 1], &[]),
+        ];
+        let placings = placings(&cards, TieRule::SplitPot);
+        assert_eq!(placings, vec![vec!["a"], vec!["b", "c"], vec!["d"]]);
+
+        // b ve c 2. (ödüllü) ve 3. (ödülsüz) sırayı paylaşır
+        let standings = standings(&placings, &[70, 30]);
+        assert_eq!(payout_of(&standings, "a"), 70);
+        assert_eq!(payout_of(&standings, "b"), 15);
+        assert_eq!(payout_of(&standings, "c"), 15);
+        assert_eq!(payout_of(&standings, "d"), 0);
+        let places: Vec<usize> = standings.iter().map(|s| s.place).collect();
+        assert_eq!(places, vec![1, 2, 2, 4]);
+        assert_eq!(roll_off_players(&placings, 2), vec!["b", "c"]);
+        assert!(roll_off_players(&placings, 1).is_empty());
+    }
+
+    #[test]
+    fn highest_die_with_identical_dice_shares_the_place() {
+        let cards = [
+            card("a", &[6, 5, 4, 3, 2], &[]),
+            card("b", &[2, 3, 4, 5, 6], &[]),
+            card("c", &[6, 6, 4, 2, 2], &[]),
+        ];
+        let placings = placings(&cards, TieRule::HighestDie);
+        assert_eq!(placings, vec![vec!["c"], vec!["a", "b"]]);
+        assert!(tied_for_first(&cards));
+    }
+
+    #[test]
+    fn roll_off_still_tied_after_the_last_round_shares_the_place() {
+        let tiebreaks = [4; MAX_ROLL_OFF_ROUNDS];
+        let cards = [
+            card("a", &[3, 3, 3, 3, 3], &tiebreaks),
+            card("b", &[3, 3, 3, 3, 3], &tiebreaks),
+            card("c", &[1, 1, 1, 1, 1], &[]),
+        ];
+        let placings = placings(&cards, TieRule::RollOff);
+        assert_eq!(placings, vec![vec!["a", "b"], vec!["c"]]);
+
+        let standings = standings(&placings, &[100]);
+        assert_eq!(payout_of(&standings, "a"), 50);
+        assert_eq!(payout_of(&standings, "b"), 50);
+    }
+
+    #[test]
+    fn rounding_remainder_stays_with_the_house() {
+        let placings = vec![vec!["a", "b", "c"]];
+        let standings = standings(&placings, &[100]);
+        assert!(standings.iter().all(|s| s.payout == 33));
+        assert_eq!(standings.iter().map(|s| s.payout).sum::<i32>(), 99);
+    }
+}
@@ -6,26 +6,167 @@ use serde_json::json;
 use mongodb::Client;
 use serde::{Deserialize, Serialize};
 use tokio_tungstenite::tungstenite::Message;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
+use crate::models::user::User;
 use crate::repository::user_repository::UserRepository;
+use crate::repository::ledger_repository::LedgerRepository;
+use crate::repository::tap_flag_repository::TapFlagRepository;
+use crate::models::ledger::{user_account, Asset, LedgerTransaction, Reason, SYSTEM_PASSIVE_INCOME, SYSTEM_TAPS};
+use crate::models::tap_flag::TapFlag;
+use crate::config::app_config::AppConfig;
+use crate::services::boost_engine::{now_secs, BoostEngine};
+use crate::services::auth::verify_token;
+use crate::services::passive_income::{PassiveAccrual, PassiveIncome};
+use crate::services::tap_accounting::{spend_energy, EnergyPool, TapAnomaly, TapBatch, TapLimiter, TapOutcome, TapRules};
 
+// İstemcinin gönderdiği `click_power` artık dikkate alınmaz; kazanç kayıtlı `User.click_power`'dan hesaplanır.
+// `taps` yoksa mesaj, sunucu zamanında tek bir tıklama sayılır (eski istemciler).
+// İlk mesaj API'nin `/auth` JWT'sini `token` olarak taşımalıdır; kullanıcı token'dan belirlenir ve
+// `telegram_id` gönderildiyse onunla aynı olmalıdır.
 #[derive(Serialize, Deserialize)]
 struct IncomingMessage {
-    telegram_id: i64,
+    telegram_id: Option<i64>,
+    token: Option<String>,
     click_power: Option<i32>,
+    taps: Option<Vec<TapBatch>>,
+}
+
+// Tek mesajda kabul edilen en fazla toplu tıklama sayısı
+const MAX_BATCHES_PER_MESSAGE: usize = 200;
+// Aynı bağlantıda aynı tür şüpheli davranış en fazla bu aralıkla işaretlenir
+const FLAG_COOLDOWN_MS: i64 = 60_000;
+// Enerji yazımı eşzamanlı bir yazmayla çakışırsa en fazla bu kadar yeniden denenir
+const MAX_APPLY_ATTEMPTS: usize = 3;
+// Bağlıyken `last_seen` bu aralıkla güncellenir; pasif kazanç yalnızca çevrimdışı süre için birikir
+const LAST_SEEN_HEARTBEAT_SECS: u64 = 30;
+
+// Kullanıcı başına tıklama sınırlayıcıları; aynı kullanıcının bütün bağlantıları tek sınırı paylaşır
+type SharedLimiters = Arc<Mutex<HashMap<i64, Arc<Mutex<TapLimiter>>>>>;
+
+// İlk mesajdaki token'ı doğrular ve kullanıcının telegram_id'sini döner
+fn authenticate(text: &str, secret: &str) -> Result<i64, String> {
+    let message: IncomingMessage = serde_json::from_str(text).map_err(|_| "Geçersiz mesaj formatı".to_string())?;
+    let token = message.token.ok_or_else(|| "token gerekli".to_string())?;
+    let telegram_id = verify_token(&token, secret)?;
+    if message.telegram_id.is_some_and(|claimed| claimed != telegram_id) {
+        return Err("telegram_id token ile eşleşmiyor".to_string());
+    }
+    Ok(telegram_id)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as i64
+}
+
+// Bağlanınca, son görülmeden bu yana biriken pasif kazancı click_score'a yatırır.
+// İlk bağlantıda yalnızca `last_seen` kaydedilir. Başka bir bağlantı kazancı zaten yatırdıysa None döner.
+async fn settle_passive_income(
+    user_repo: &UserRepository,
+    ledger_repo: &LedgerRepository,
+    passive: &PassiveIncome,
+    boosts: &BoostEngine,
+    telegram_id: i64,
+) -> mongodb::error::Result<Option<PassiveAccrual>> {
+    let user = match user_repo.find_user_by_telegram_id(telegram_id).await? {
+        Some(user) => user,
+        None => return Ok(None),
+    };
+
+    let now = now_secs();
+    let accrual = match user.last_seen {
+        Some(last_seen) => passive.accrue(&user, boosts, last_seen, now),
+        None => PassiveAccrual::default(),
+    };
+    let earned = accrual.earned.min(i32::MAX as i64) as i32;
+    if !user_repo.credit_passive_income(telegram_id, user.last_seen, now, earned).await? {
+        return Ok(None);
+    }
+
+    if earned > 0 {
+        let transaction = LedgerTransaction::transfer(
+            Reason::PassiveIncome,
+            Asset::ClickScore,
+            SYSTEM_PASSIVE_INCOME,
+            user_account(telegram_id),
+            earned as i64,
+            None,
+        );
+        if let Err(e) = ledger_repo.record(transaction).await {
+            eprintln!("Pasif kazanç deftere yazılamadı: {:?}", e);
+        }
+    }
+    Ok(Some(accrual))
+}
+
+// Tıklamaları enerji ve hız sınırlarıyla sayar, etkin boost çarpanını uygular ve kazancı kullanıcıya ve deftere yazar
+async fn process_taps(
+    user_repo: &UserRepository,
+    ledger_repo: &LedgerRepository,
+    telegram_id: i64,
+    outcome: &TapOutcome,
+    rules: &TapRules,
+    boosts: &BoostEngine,
+) -> mongodb::error::Result<Option<(User, TapOutcome, EnergyPool)>> {
+    for _ in 0..MAX_APPLY_ATTEMPTS {
+        let user = match user_repo.find_user_by_telegram_id(telegram_id).await? {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+
+        let now = now_millis();
+        let pool = EnergyPool::from_stored(user.energy, user.energy_updated_at, rules, now).regenerate(rules, now);
+        let mut counted = outcome.clone();
+        let pool = spend_energy(pool, &mut counted);
+        let base = counted.accepted as i64 * user.click_power.unwrap_or(1).max(0) as i64;
+        let earned = boosts.apply(&user, base, now_secs()).min(i32::MAX as i64) as i32;
+
+        if let Some(updated) = user_repo.apply_taps(telegram_id, (user.energy, user.energy_updated_at), pool, earned).await? {
+            if earned > 0 {
+                let transaction = LedgerTransaction::transfer(
+                    Reason::Tap,
+                    Asset::ClickScore,
+                    SYSTEM_TAPS,
+                    user_account(telegram_id),
+                    earned as i64,
+                    None,
+                );
+                if let Err(e) = ledger_repo.record(transaction).await {
+                    eprintln!("Tıklama deftere yazılamadı: {:?}", e);
+                }
+            }
+            return Ok(Some((updated, counted, pool)));
+        }
+    }
+    Err(mongodb::error::Error::custom(format!("Tıklama enerjisi {} için güncellenemedi", telegram_id)))
 }
 
-pub async fn run_websocket_server(mongo_client: Client) {
-    let addr = "127.0.0.1:9001";
-    let listener = TcpListener::bind(addr).await.expect("WebSocket sunucusu başlatılamadı!");
+pub async fn run_websocket_server(mongo_client: Client, config: Arc<AppConfig>) {
+    let addr = config.address(config.tap_port);
+    let listener = TcpListener::bind(&addr).await.expect("WebSocket sunucusu başlatılamadı!");
 
     let user_repo = Arc::new(UserRepository::new(&mongo_client));
+    let ledger_repo = Arc::new(LedgerRepository::new(&mongo_client));
+    let flag_repo = Arc::new(TapFlagRepository::new(&mongo_client));
+    let rules = TapRules::from_config(&config);
+    let boosts = Arc::new(BoostEngine::from_config(&config));
+    let passive = PassiveIncome::from_config(&config);
+    let limiters: SharedLimiters = Arc::new(Mutex::new(HashMap::new()));
 
     println!("WebSocket sunucusu {} adresinde çalışmaya başladı", addr);
 
     while let Ok((stream, _)) = listener.accept().await {
         let user_repo = Arc::clone(&user_repo);
+        let ledger_repo = Arc::clone(&ledger_repo);
+        let flag_repo = Arc::clone(&flag_repo);
+        let boosts = Arc::clone(&boosts);
+        let limiters = Arc::clone(&limiters);
+        let config = Arc::clone(&config);
 
         tokio::spawn(async move {
             let ws_stream = match accept_async(stream).await {
@@ -36,31 +177,58 @@ pub async fn run_websocket_server(mongo_client: Client) {
             let (write, mut read) = ws_stream.split();
             let write = Arc::new(Mutex::new(write));
 
-            // İlk mesajı alarak telegram_id'yi belirle
-            let telegram_id = match read.next().await {
-                Some(Ok(msg)) => {
-                    if let Ok(text) = msg.into_text() {
-                        if let Ok(parsed_msg) = serde_json::from_str::<IncomingMessage>(&text) {
-                            parsed_msg.telegram_id
-                        } else {
-                            return;
+            // İlk mesajdaki token ile kullanıcıyı doğrula
+            let first = match read.next().await {
+                Some(Ok(msg)) => msg.into_text().map_err(|_| "Geçersiz mesaj formatı".to_string())
+                    .and_then(|text| authenticate(&text, &config.jwt_secret)),
+                _ => return,
+            };
+            let telegram_id = match first {
+                Ok(telegram_id) => telegram_id,
+                Err(e) => {
+                    let _ = write.lock().await.send(json!({ "error": e }).to_string().into()).await;
+                    return;
+                }
+            };
+
+            // Çevrimdışıyken biriken kazancı yatır ve kullanıcıya bildir
+            match settle_passive_income(&user_repo, &ledger_repo, &passive, &boosts, telegram_id).await {
+                Ok(Some(accrual)) if accrual.earned > 0 => {
+                    let response = json!({
+                        "passive_income": {
+                            "earned": accrual.earned,
+                            "offline_secs": accrual.offline_secs,
+                            "capped": accrual.capped,
                         }
-                    } else {
+                    });
+                    if write.lock().await.send(response.to_string().into()).await.is_err() {
                         return;
                     }
                 }
-                _ => return,
-            };
+                Ok(_) => {}
+                Err(e) => eprintln!("Pasif kazanç yatırılamadı: {:?}", e),
+            }
 
             // Her saniye belirli `telegram_id` kullanıcısının verisini çekip gönderme
             let user_repo_clone = Arc::clone(&user_repo);
             let write_clone = Arc::clone(&write);
+            let boosts_clone = Arc::clone(&boosts);
             tokio::spawn(async move {
                 let mut interval = time::interval(Duration::from_secs(1));
+                let mut ticks: u64 = 0;
                 loop {
                     interval.tick().await;
+                    ticks += 1;
+                    if ticks.is_multiple_of(LAST_SEEN_HEARTBEAT_SECS) {
+                        if let Err(e) = user_repo_clone.touch_last_seen(telegram_id, now_secs()).await {
+                            eprintln!("last_seen güncellenemedi: {:?}", e);
+                        }
+                    }
 
                     if let Ok(Some(user)) = user_repo_clone.find_user_by_telegram_id(telegram_id).await {
+                        let now = now_millis();
+                        let energy = EnergyPool::from_stored(user.energy, user.energy_updated_at, &rules, now).regenerate(&rules, now);
+                        let boost_now = now_secs();
                         let response = json!({
                             "telegram_id": user.telegram_id,
                             "first_name": user.first_name,
@@ -72,11 +240,15 @@ pub async fn run_websocket_server(mongo_client: Client) {
                             "ton_amount": user.ton_amount,
                             "wallet_address": user.wallet_address,
                             "boost": user.boost,
+                            "boost_multiplier_pct": boosts_clone.multiplier_pct(&user, boost_now),
+                            "boost_remaining_secs": BoostEngine::remaining_secs(&user, boost_now),
                             "references": user.references,
                             "game_pass": user.game_pass,
                             "reputation_points": user.reputation_points,
                             "items": user.items,
-                            "friends": user.friends
+                            "friends": user.friends,
+                            "energy": energy.energy,
+                            "energy_max": rules.energy_max
                         });
                         if write_clone.lock().await.send(Message::text(response.to_string())).await.is_err() {
                             break;
@@ -86,60 +258,127 @@ pub async fn run_websocket_server(mongo_client: Client) {
             });
 
             // Diğer gelen mesajları işleme
+            let limiter = Arc::clone(limiters.lock().await.entry(telegram_id).or_default());
+            let mut last_flagged: HashMap<TapAnomaly, i64> = HashMap::new();
             while let Some(message) = read.next().await {
                 match message {
                     Ok(msg) => {
                         if let Ok(text) = msg.into_text() {
                             let incoming: Result<IncomingMessage, _> = serde_json::from_str(&text);
-                            
-                            match incoming {
-                                Ok(msg) => {
-                                    let click_power = msg.click_power.unwrap_or(0);
-
-                                    match user_repo.find_user_by_telegram_id(telegram_id).await {
-                                        Ok(Some(user)) => {
-                                            let response = json!({
-                                                "click_score": user.click_score,
-                                                "click_power": user.click_power,
-                                            });
-                                            if write.lock().await.send(response.to_string().into()).await.is_err() {
-                                                break;
-                                            }
-                                        }
-                                        _ => {
-                                            let error_response = json!({
-                                                "error": "Kullanıcı bulunamadı veya eksik veri"
-                                            });
-                                            if write.lock().await.send(error_response.to_string().into()).await.is_err() {
-                                                break;
-                                            }
-                                        }
-                                    }
 
-                                    if let Ok(Some(updated_user)) = user_repo.update_click_score(telegram_id, click_power).await {
-                                        let updated_response = json!({
-                                            "click_score": updated_user.click_score,
-                                            "click_power": updated_user.click_power,
-                                        });
-                                        if write.lock().await.send(updated_response.to_string().into()).await.is_err() {
-                                            break;
-                                        }
-                                    }
-                                }
-                                Err(_) => {
+                            let batches = match incoming {
+                                Ok(IncomingMessage { taps: Some(taps), .. }) if taps.len() <= MAX_BATCHES_PER_MESSAGE => taps,
+                                Ok(IncomingMessage { taps: None, .. }) => vec![TapBatch { count: 1, at: now_millis() }],
+                                _ => {
                                     let error_message = json!({
                                         "error": "Geçersiz mesaj formatı"
                                     });
                                     if write.lock().await.send(error_message.to_string().into()).await.is_err() {
                                         break;
                                     }
+                                    continue;
+                                }
+                            };
+
+                            let outcome = limiter.lock().await.admit(&batches, &rules, now_millis());
+                            let response = match process_taps(&user_repo, &ledger_repo, telegram_id, &outcome, &rules, &boosts).await {
+                                Ok(Some((user, counted, energy))) => {
+                                    let now = now_millis();
+                                    for anomaly in &counted.anomalies {
+                                        if last_flagged.get(anomaly).is_some_and(|at| now - at < FLAG_COOLDOWN_MS) {
+                                            continue;
+                                        }
+                                        last_flagged.insert(*anomaly, now);
+                                        let flag = TapFlag {
+                                            telegram_id,
+                                            anomaly: anomaly.as_str().to_string(),
+                                            accepted: counted.accepted,
+                                            rejected: counted.rejected,
+                                            reviewed: false,
+                                            created_at: now,
+                                        };
+                                        if let Err(e) = flag_repo.record(flag).await {
+                                            eprintln!("Şüpheli tıklama kaydedilemedi: {:?}", e);
+                                        }
+                                    }
+                                    json!({
+                                        "click_score": user.click_score,
+                                        "click_power": user.click_power,
+                                        "energy": energy.energy,
+                                        "energy_max": rules.energy_max,
+                                        "accepted": counted.accepted,
+                                        "rejected": counted.rejected,
+                                    })
                                 }
+                                Ok(None) => json!({
+                                    "error": "Kullanıcı bulunamadı veya eksik veri"
+                                }),
+                                Err(e) => {
+                                    eprintln!("Tıklamalar işlenemedi: {:?}", e);
+                                    json!({
+                                        "error": "Tıklamalar işlenemedi"
+                                    })
+                                }
+                            };
+                            if write.lock().await.send(response.to_string().into()).await.is_err() {
+                                break;
                             }
                         }
                     }
                     Err(_) => break,
                 }
             }
+
+            // Kullanıcının son bağlantısıysa sınırlayıcısı bırakılır
+            {
+                let mut limiters = limiters.lock().await;
+                if Arc::strong_count(&limiter) == 2 {
+                    limiters.remove(&telegram_id);
+                }
+            }
+
+            // Bağlantı kapandı: çevrimdışı süre buradan itibaren sayılır
+            if let Err(e) = user_repo.touch_last_seen(telegram_id, now_secs()).await {
+                eprintln!("last_seen güncellenemedi: {:?}", e);
+            }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::auth::Claims;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    const SECRET: &str = "test-secret";
+
+    fn token_for(telegram_id: i64) -> String {
+        let claims = Claims { sub: telegram_id.to_string(), exp: (now_secs() + 3600) as usize };
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn first_message_is_authenticated_by_token() {
+        let text = json!({ "token": token_for(42) }).to_string();
+        assert_eq!(authenticate(&text, SECRET), Ok(42));
+
+        let text = json!({ "telegram_id": 42, "token": token_for(42) }).to_string();
+        assert_eq!(authenticate(&text, SECRET), Ok(42));
+    }
+
+    #[test]
+    fn first_message_without_valid_token_is_rejected() {
+        assert!(authenticate(&json!({ "telegram_id": 42 }).to_string(), SECRET).is_err());
+        assert!(authenticate(&json!({ "token": "not-a-jwt" }).to_string(), SECRET).is_err());
+
+        let forged = encode(&Header::default(), &Claims { sub: "42".to_string(), exp: (now_secs() + 3600) as usize }, &EncodingKey::from_secret(b"other")).unwrap();
+        assert!(authenticate(&json!({ "token": forged }).to_string(), SECRET).is_err());
+    }
+
+    #[test]
+    fn claimed_telegram_id_must_match_token() {
+        let text = json!({ "telegram_id": 7, "token": token_for(42) }).to_string();
+        assert!(authenticate(&text, SECRET).is_err());
+    }
+}